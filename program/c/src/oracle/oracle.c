@@ -108,9 +108,10 @@ static uint64_t upd_price( SolParameters *prm, SolAccountInfo *ka )
     uint32_t status = cptr->status_;
 
     // Set publisher's status to unknown unless their CI is sufficiently tight.
-    int64_t threshold_conf = (cptr->price_ / PC_MAX_CI_DIVISOR);
+    int64_t max_ci_divisor = pptr->max_ci_divisor_ ? pptr->max_ci_divisor_ : PC_MAX_CI_DIVISOR;
+    int64_t threshold_conf = (cptr->price_ / max_ci_divisor);
     if (threshold_conf < 0) {
-      // Safe as long as threshold_conf isn't the min int64, which it isn't as long as PC_MAX_CI_DIVISOR > 1.
+      // Safe as long as threshold_conf isn't the min int64, which it isn't as long as max_ci_divisor > 1.
       threshold_conf = -threshold_conf;
     }
     if ( cptr->conf_ > (uint64_t) threshold_conf ) {
@@ -17,8 +17,15 @@ const uint64_t SUCCESSFULLY_UPDATED_AGGREGATE = 1000ULL;
 // Rust portion of the codebase.
 const uint64_t TIME_MACHINE_STRUCT_SIZE = 1864ULL;
 
+// The size of the space after pc_price_t in resized price accounts, holding
+// PC_EXTRA_COMP_SIZE components that extend comp_.
 const uint64_t EXTRA_PUBLISHER_SPACE = 1000ULL;
 
+// The size of the statistics of the PC_COMP_SIZE + PC_EXTRA_COMP_SIZE
+// publishers defined in the Rust portion of the codebase, stored after the
+// time machine.
+const uint64_t PUBLISHER_STATS_SIZE = 1680ULL;
+
 
 // magic number at head of account
 #define PC_MAGIC 0xa1b2c3d4
@@ -26,6 +33,11 @@ const uint64_t EXTRA_PUBLISHER_SPACE = 1000ULL;
 // current program version
 #define PC_VERSION            2
 
+// oldest version of instructions and accounts that the program still
+// supports. Accounts of an older supported version must be upgraded
+// with migrate_account before other instructions accept them.
+#define PC_MIN_VERSION        2
+
 // max latency in slots between send and receive
 #define PC_MAX_SEND_LATENCY  25
 
@@ -34,13 +46,19 @@ const uint64_t EXTRA_PUBLISHER_SPACE = 1000ULL;
 #define PC_PUBKEY_SIZE_64   (PC_PUBKEY_SIZE/sizeof(uint64_t))
 #define PC_MAP_TABLE_SIZE   640
 #define PC_COMP_SIZE         32
+// Number of additional components stored in the extra publisher space of
+// resized price accounts, right after comp_
+#define PC_EXTRA_COMP_SIZE   10
 // Bound on the range of the exponent in price accounts. This number is set such that the
 // PD-based EMA computation does not lose too much precision.
 #define PC_MAX_NUM_DECIMALS   8
 #define PC_PROD_ACC_SIZE    512
+// Product accounts can be resized up to this size to fit more metadata
+#define PC_MAX_PROD_ACC_SIZE 10240
 #define PC_EXP_DECAY         -9
 // If ci > price / PC_MAX_CI_DIVISOR, set publisher status to unknown.
 // (e.g., 20 means ci must be < 5% of price)
+// Price accounts can override this with max_ci_divisor_.
 #define PC_MAX_CI_DIVISOR    20
 
 #ifndef PC_HEAP_START
@@ -62,6 +80,7 @@ const uint64_t EXTRA_PUBLISHER_SPACE = 1000ULL;
 #define PC_ACCTYPE_PRODUCT    2
 #define PC_ACCTYPE_PRICE      3
 #define PC_ACCTYPE_TEST       4
+#define PC_ACCTYPE_PERMISSIONS 5
 
 // binary version of sysvar_clock account id
 const uint64_t sysvar_clock[] = {
@@ -170,7 +189,7 @@ typedef struct pc_price
   uint32_t        size_;              // price account size
   uint32_t        ptype_;             // price or calculation type
   int32_t         expo_;              // price exponent
-  uint32_t        num_;               // number of component prices
+  uint32_t        num_;               // number of component prices in comp_
   uint32_t        num_qt_;            // number of quoters that make up aggregate
   uint64_t        last_slot_;         // slot of last valid aggregate price
   uint64_t        valid_slot_;        // valid on-chain slot of agg. price
@@ -178,9 +197,9 @@ typedef struct pc_price
   pc_ema_t        twac_;              // time-weighted average conf interval
   int64_t         timestamp_;         // unix timestamp of aggregate price
   uint8_t         min_pub_;           // min publishers for valid price
-  int8_t          drv2_;              // space for future derived values
-  int16_t         drv3_;              // space for future derived values
-  int32_t         drv4_;              // space for future derived values
+  uint8_t         max_latency_;       // max publisher latency in slots (0 for PC_MAX_SEND_LATENCY)
+  uint16_t        max_ci_divisor_;    // conf divisor for valid quotes (0 for PC_MAX_CI_DIVISOR)
+  uint32_t        num_extra_;         // number of component prices in the extra publisher space
   pc_pub_key_t    prod_;              // product id/ref-account
   pc_pub_key_t    next_;              // next price account in list
   uint64_t        prev_slot_;         // valid slot of previous aggregate with TRADING status
@@ -193,50 +212,86 @@ typedef struct pc_price
 
 static_assert( sizeof( pc_price_t ) == 3312, "" );
 
-const uint64_t PRICE_ACCOUNT_SIZE = TIME_MACHINE_STRUCT_SIZE + EXTRA_PUBLISHER_SPACE + sizeof( pc_price_t );
+// authorities allowed to run the admin instructions, stored in the PDA of
+// the program at the seed "permissions". The master authority can run all
+// of them, the data curation authority all but the publisher and migration
+// instructions and the security authority the publisher instructions.
+typedef struct pc_permissions
+{
+  uint32_t        magic_;             // pyth magic number
+  uint32_t        ver_;               // program version
+  uint32_t        type_;              // account type
+  uint32_t        size_;              // size of populated region of account
+  pc_pub_key_t    master_authority_;
+  pc_pub_key_t    data_curation_authority_;
+  pc_pub_key_t    security_authority_;
+} pc_permissions_t;
+
+static_assert( sizeof( pc_permissions_t ) == 112, "" );
+
+const uint64_t PRICE_ACCOUNT_SIZE = TIME_MACHINE_STRUCT_SIZE + EXTRA_PUBLISHER_SPACE + PUBLISHER_STATS_SIZE + sizeof( pc_price_t );
 
 // command enumeration
 typedef enum {
 
-  // initialize first mapping list account
+  // initialize first mapping list account. If the system program is passed,
+  // the mapping account is created and funded by the funding account first.
   // key[0] funding account       [signer writable]
   // key[1] mapping account       [signer writable]
+  // key[2] system program        [readable], if any
+  // key[2 or 3] permissions account [readable]
   e_cmd_init_mapping = 0,
 
-  // initialize and add new mapping account
+  // initialize and add new mapping account. If the system program is passed,
+  // the new mapping account is created and funded by the funding account first.
   // key[0] funding account       [signer writable]
-  // key[1] tail mapping account  [signer writable]
+  // key[1] tail mapping account  [writable]
   // key[2] new mapping account   [signer writable]
+  // key[3] system program        [readable], if any
+  // key[3 or 4] permissions account [readable]
   e_cmd_add_mapping,
 
-  // initialize and add new product reference data account
+  // initialize and add new product reference data account. If the system
+  // program is passed, the new product account is created and funded by the
+  // funding account first.
   // key[0] funding account       [signer writable]
-  // key[1] mapping account       [signer writable]
+  // key[1] mapping account       [writable]
   // key[2] new product account   [signer writable]
+  // key[3] system program        [readable], if any
+  // key[3 or 4] permissions account [readable]
   e_cmd_add_product,
 
   // update product account
   // key[0] funding account       [signer writable]
-  // key[1] product account       [signer writable]
+  // key[1] product account       [writable]
+  // key[2] permissions account   [readable]
   e_cmd_upd_product,
 
-  // add new price account to a product account
+  // add new price account to a product account. If the system program is
+  // passed, the new price account is created and funded by the funding account
+  // first.
   // key[0] funding account       [signer writable]
-  // key[1] product account       [signer writable]
+  // key[1] product account       [writable]
   // key[2] new price account     [signer writable]
+  // key[3] system program        [readable], if any
+  // key[3 or 4] permissions account [readable]
   e_cmd_add_price,
 
-  // add publisher to symbol account
+  // add publisher to symbol account, up to PC_COMP_SIZE publishers or
+  // PC_COMP_SIZE + PC_EXTRA_COMP_SIZE publishers in resized price accounts
   // key[0] funding account       [signer writable]
-  // key[1] price account         [signer writable]
+  // key[1] price account         [writable]
+  // key[2] permissions account   [readable]
   e_cmd_add_publisher,
 
   // delete publisher from symbol account
   // key[0] funding account       [signer writable]
-  // key[1] price account         [signer writable]
+  // key[1] price account         [writable]
+  // key[2] permissions account   [readable]
   e_cmd_del_publisher,
 
-  // publish component price
+  // publish component price. The publisher's component is looked up at
+  // pub_index_ first, and among all the components if it isn't there
   // key[0] funding account       [signer writable]
   // key[1] price account         [writable]
   // key[2] sysvar_clock account  [readable]
@@ -250,7 +305,8 @@ typedef enum {
 
   // (re)initialize price account
   // key[0] funding account       [signer writable]
-  // key[1] new price account     [signer writable]
+  // key[1] price account         [writable]
+  // key[2] permissions account   [readable]
   e_cmd_init_price,
 
   // deprecated
@@ -261,7 +317,8 @@ typedef enum {
 
   // set min publishers
   // key[0] funding account       [signer writable]
-  // key[1] price account         [signer writable]
+  // key[1] price account         [writable]
+  // key[2] permissions account   [readable]
   e_cmd_set_min_pub,
 
   // publish component price, never returning an error even if the update failed
@@ -270,11 +327,128 @@ typedef enum {
   // key[2] sysvar_clock account  [readable]
   e_cmd_upd_price_no_fail_on_error,
 
-  // resizes a price account so that it fits the Time Machine
+  // resizes a price account so that it fits the Time Machine and the
+  // statistics of the publishers
   // key[0] funding account       [signer writable]
-  // key[1] price account         [Signer writable]
+  // key[1] price account         [writable]
   // key[2] system program        [readable]
+  // key[3] permissions account   [readable]
   e_cmd_resize_price_account,
+
+  // compute the simple moving average of the aggregate price over a window
+  // and return it through the return data. The window is made of whole one
+  // hour buckets, up to 24, and ends at the start of the current hour
+  // key[0] price account         [readable]
+  // key[1] sysvar_clock account  [readable]
+  e_cmd_query_time_machine,
+
+  // set max publisher latency
+  // key[0] funding account       [signer writable]
+  // key[1] price account         [writable]
+  // key[2] permissions account   [readable]
+  e_cmd_set_max_latency,
+
+  // set the confidence interval divisor of the price account
+  // key[0] funding account       [signer writable]
+  // key[1] price account         [writable]
+  // key[2] permissions account   [readable]
+  e_cmd_set_max_ci_divisor,
+
+  // delete a price account without publishers and reclaim its lamports
+  // key[0] funding account       [signer writable]
+  // key[1] product account       [writable]
+  // key[2] price account         [writable]
+  // key[3] previous price account in the product's list [writable], if any
+  // key[3 or 4] permissions account [readable]
+  e_cmd_del_price,
+
+  // delete a product account without price accounts, remove it from
+  // its mapping account and reclaim its lamports
+  // key[0] funding account       [signer writable]
+  // key[1] mapping account       [writable]
+  // key[2] product account       [writable]
+  // key[3] permissions account   [readable]
+  e_cmd_del_product,
+
+  // publish component prices of several price accounts, the i-th entry
+  // of the instruction updating the i-th price account
+  // key[0] funding account       [signer writable]
+  // key[1] sysvar_clock account  [readable]
+  // key[2..] price accounts      [writable]
+  e_cmd_upd_price_batch,
+
+  // set the value of a single product attribute, adding it if needed
+  // key[0] funding account       [signer writable]
+  // key[1] product account       [writable]
+  // key[2] permissions account   [readable]
+  e_cmd_set_product_attr,
+
+  // remove a single product attribute
+  // key[0] funding account       [signer writable]
+  // key[1] product account       [writable]
+  // key[2] permissions account   [readable]
+  e_cmd_del_product_attr,
+
+  // resizes a product account so that it fits more metadata. The account
+  // has to grow, up to PC_MAX_PROD_ACC_SIZE
+  // key[0] funding account       [signer writable]
+  // key[1] product account       [writable]
+  // key[2] system program        [readable]
+  // key[3] permissions account   [readable]
+  e_cmd_resize_product_account,
+
+  // upgrades an account of a supported older version to the layout of
+  // PC_VERSION. Price accounts resized before the statistics of the
+  // publishers were added are grown to hold them. Does nothing if the
+  // account already has the current layout
+  // key[0] funding account       [signer writable]
+  // key[1] account to upgrade    [writable]
+  // key[2] system program        [readable]
+  // key[3] permissions account   [readable]
+  e_cmd_migrate_account,
+
+  // set the authorities of the permissions account, creating it if needed.
+  // Only the upgrade authority of the program can run it.
+  // key[0] upgrade authority     [signer writable]
+  // key[1] programdata account   [readable]
+  // key[2] permissions account   [writable]
+  // key[3] system program        [readable]
+  e_cmd_upd_permissions,
+
+  // initialize the first mapping account of a new linked-list of mapping
+  // accounts, created at the PDA of the program with seeds ["mapping"]
+  // key[0] funding account       [signer writable]
+  // key[1] new mapping account   [writable]
+  // key[2] system program        [readable]
+  // key[3] permissions account   [readable]
+  e_cmd_init_mapping_pda,
+
+  // initialize and add new mapping account, created at the PDA of the program
+  // with seeds ["mapping", tail mapping account]
+  // key[0] funding account       [signer writable]
+  // key[1] tail mapping account  [writable]
+  // key[2] new mapping account   [writable]
+  // key[3] system program        [readable]
+  // key[4] permissions account   [readable]
+  e_cmd_add_mapping_pda,
+
+  // initialize and add new product reference data account, created at the PDA
+  // of the program with seeds ["product", seed of the instruction]
+  // key[0] funding account       [signer writable]
+  // key[1] mapping account       [writable]
+  // key[2] new product account   [writable]
+  // key[3] system program        [readable]
+  // key[4] permissions account   [readable]
+  e_cmd_add_product_pda,
+
+  // initialize and add new price account, created at the PDA of the program
+  // with seeds ["price", product account, price type as a u32]
+  // key[0] funding account       [signer writable]
+  // key[1] product account       [writable]
+  // key[2] new price account     [writable]
+  // key[3] system program        [readable]
+  // key[4] permissions account   [readable]
+  e_cmd_add_price_pda,
 } command_t;
 
 typedef struct cmd_hdr
@@ -302,6 +476,42 @@ typedef struct cmd_upd_product
 
 static_assert( sizeof( cmd_upd_product_t ) == 8, "" );
 
+typedef struct cmd_add_product_pda
+{
+  uint32_t     ver_;
+  int32_t      cmd_;
+  // seed of the product account (e.g. its symbol), 1 to 32 bytes
+} cmd_add_product_pda_t;
+
+static_assert( sizeof( cmd_add_product_pda_t ) == 8, "" );
+
+typedef struct cmd_resize_product_account
+{
+  uint32_t     ver_;
+  int32_t      cmd_;
+  uint32_t     size_;              // new size of the product account
+} cmd_resize_product_account_t;
+
+static_assert( sizeof( cmd_resize_product_account_t ) == 12, "" );
+
+typedef struct cmd_set_product_attr
+{
+  uint32_t     ver_;
+  int32_t      cmd_;
+  // key-value pair
+} cmd_set_product_attr_t;
+
+static_assert( sizeof( cmd_set_product_attr_t ) == 8, "" );
+
+typedef struct cmd_del_product_attr
+{
+  uint32_t     ver_;
+  int32_t      cmd_;
+  // key
+} cmd_del_product_attr_t;
+
+static_assert( sizeof( cmd_del_product_attr_t ) == 8, "" );
+
 typedef struct cmd_add_price
 {
   uint32_t     ver_;
@@ -331,6 +541,24 @@ typedef struct cmd_set_min_pub
 
 static_assert( sizeof( cmd_set_min_pub_t ) == 12, "" );
 
+typedef struct cmd_set_max_latency
+{
+  uint32_t     ver_;
+  int32_t      cmd_;
+  uint8_t      max_latency_;
+} cmd_set_max_latency_t;
+
+static_assert( sizeof( cmd_set_max_latency_t ) == 12, "" );
+
+typedef struct cmd_set_max_ci_divisor
+{
+  uint32_t     ver_;
+  int32_t      cmd_;
+  uint16_t     max_ci_divisor_;
+} cmd_set_max_ci_divisor_t;
+
+static_assert( sizeof( cmd_set_max_ci_divisor_t ) == 12, "" );
+
 typedef struct cmd_add_publisher
 {
   uint32_t     ver_;
@@ -354,7 +582,7 @@ typedef struct cmd_upd_price
   uint32_t     ver_;
   int32_t      cmd_;
   uint32_t     status_;
-  uint32_t     unused_;
+  uint32_t     pub_index_;           // index of the publisher's component, checked first
   int64_t      price_;
   uint64_t     conf_;
   uint64_t     pub_slot_;
@@ -362,6 +590,49 @@ typedef struct cmd_upd_price
 
 static_assert( sizeof( cmd_upd_price_t ) == 40, "" );
 
+typedef struct cmd_upd_price_batch
+{
+  uint32_t     ver_;
+  int32_t      cmd_;
+  uint32_t     no_fail_on_error_;    // skip the entries that fail instead of failing
+  uint32_t     num_;                 // number of entries
+  // followed by num_ cmd_upd_price_batch_entry_t
+} cmd_upd_price_batch_t;
+
+static_assert( sizeof( cmd_upd_price_batch_t ) == 16, "" );
+
+typedef struct cmd_upd_price_batch_entry
+{
+  uint32_t     status_;
+  uint32_t     pub_index_;           // same as in cmd_upd_price_t
+  int64_t      price_;
+  uint64_t     conf_;
+  uint64_t     pub_slot_;
+} cmd_upd_price_batch_entry_t;
+
+static_assert( sizeof( cmd_upd_price_batch_entry_t ) == 32, "" );
+
+typedef struct cmd_query_time_machine
+{
+  uint32_t     ver_;
+  int32_t      cmd_;
+  int64_t      window_;              // length of the window in seconds,
+                                     // a multiple of 3600
+} cmd_query_time_machine_t;
+
+static_assert( sizeof( cmd_query_time_machine_t ) == 16, "" );
+
+typedef struct cmd_upd_permissions
+{
+  uint32_t     ver_;
+  int32_t      cmd_;
+  pc_pub_key_t master_authority_;
+  pc_pub_key_t data_curation_authority_;
+  pc_pub_key_t security_authority_;
+} cmd_upd_permissions_t;
+
+static_assert( sizeof( cmd_upd_permissions_t ) == 104, "" );
+
 typedef struct cmd_upd_test
 {
   uint32_t     ver_;
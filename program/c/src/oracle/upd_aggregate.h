@@ -172,6 +172,7 @@ static inline bool upd_aggregate( pc_price_t *ptr, uint64_t slot, int64_t timest
   {
     uint32_t numv  = 0;
     uint32_t nprcs = (uint32_t)0;
+    int64_t  max_latency = ptr->max_latency_ ? ptr->max_latency_ : PC_MAX_SEND_LATENCY;
     int64_t  prcs[ PC_COMP_SIZE * 3 ]; // ~0.75KiB for current PC_COMP_SIZE (FIXME: DOUBLE CHECK THIS FITS INTO STACK FRAME LIMIT)
     for ( uint32_t i = 0; i != ptr->num_; ++i ) {
       pc_price_comp_t *iptr = &ptr->comp_[i];
@@ -183,7 +184,7 @@ static inline bool upd_aggregate( pc_price_t *ptr, uint64_t slot, int64_t timest
       int64_t conf      = ( int64_t )( iptr->agg_.conf_ );
       if ( iptr->agg_.status_ == PC_STATUS_TRADING &&
            (int64_t)0 < conf && conf < price && conf <= (INT64_MAX-price) && // No overflow for INT64_MAX-price as price>0
-           slot_diff >= 0 && slot_diff <= PC_MAX_SEND_LATENCY ) {
+           slot_diff >= 0 && slot_diff <= max_latency ) {
         numv += 1;
         prcs[ nprcs++ ] = price - conf; // No overflow as 0 < conf < price
         prcs[ nprcs++ ] = price;
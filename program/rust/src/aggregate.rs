@@ -0,0 +1,391 @@
+//! Rust implementation of the aggregation logic of `upd_aggregate.h`, `pd.h` and
+//! `model/price_model.c`.
+//! The results are identical to the C implementation, bit for bit. To achieve this, the integer
+//! arithmetic mirrors the C code operation by operation, using wrapping operations where the C
+//! code could overflow.
+use crate::c_oracle_header::{
+    pc_ema_t,
+    pc_price_comp_t,
+    pc_price_info_t,
+    pc_price_t,
+    PC_MAX_SEND_LATENCY,
+    PC_STATUS_TRADING,
+    PC_STATUS_UNKNOWN,
+};
+use crate::time_machine_types::{
+    num_components,
+    MAX_NUM_COMPONENTS,
+};
+
+const PD_SCALE9: i64 = 1_000_000_000;
+/// maximum slots before reset
+const PD_EMA_MAX_DIFF: i64 = 4145;
+/// exponent of temporary storage
+const PD_EMA_EXPO: i32 = -9;
+/// 1e9*-log(2)/5921
+const PD_EMA_DECAY: i64 = -117065;
+const PC_FACTOR_SIZE: i32 = 18;
+const EXP_BITS: u32 = 5;
+const EXP_MASK: i64 = (1 << EXP_BITS) - 1;
+
+/// powers of 10 for use in decimal arithmetic scaling
+const FACT: [i64; PC_FACTOR_SIZE as usize] = [
+    1,
+    10,
+    100,
+    1_000,
+    10_000,
+    100_000,
+    1_000_000,
+    10_000_000,
+    100_000_000,
+    1_000_000_000,
+    10_000_000_000,
+    100_000_000_000,
+    1_000_000_000_000,
+    10_000_000_000_000,
+    100_000_000_000_000,
+    1_000_000_000_000_000,
+    10_000_000_000_000_000,
+    100_000_000_000_000_000,
+];
+
+/// Decimal number `v * 10^e`, equivalent to `pd_t`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Pd {
+    e: i32,
+    v: i64,
+}
+
+impl Pd {
+    const fn new(v: i64, e: i32) -> Pd {
+        Pd { e, v }
+    }
+
+    fn new_scale(v: i64, e: i32) -> Pd {
+        Pd::new(v, e).scale()
+    }
+
+    /// Reduce the precision of the mantissa to 28 bits
+    fn scale(mut self) -> Pd {
+        let neg = self.v < 0;
+        let mut v = if neg { self.v.wrapping_neg() } else { self.v };
+        while v >= (1 << 28) {
+            v /= 10;
+            self.e += 1;
+        }
+        self.v = if neg { v.wrapping_neg() } else { v };
+        self
+    }
+
+    /// Pack the number in an `i64`, with 59 bits of mantissa and 5 bits of exponent.
+    /// Returns `None` if the number doesn't fit.
+    fn store(self) -> Option<i64> {
+        let mut v = self.v;
+        let mut e = self.e;
+        while v < -(1 << 58) {
+            v /= 10;
+            e += 1;
+        }
+        while v > (1 << 58) - 1 {
+            v /= 10;
+            e += 1;
+        }
+        while e < -(1 << (EXP_BITS - 1)) {
+            v /= 10;
+            e += 1;
+        }
+        while e > (1 << (EXP_BITS - 1)) - 1 {
+            v *= 10;
+            if !(-(1 << 58)..=(1 << 58) - 1).contains(&v) {
+                return None;
+            }
+            e -= 1;
+        }
+        Some((v << EXP_BITS) | (i64::from(e) & EXP_MASK))
+    }
+
+    /// Inverse of `store`
+    fn load(n: i64) -> Pd {
+        Pd::new(n >> EXP_BITS, (((n & EXP_MASK) << 59) >> 59) as i32).scale()
+    }
+
+    /// Change the exponent to `e`, truncating the mantissa if `e` is larger
+    fn adjust(self, e: i32) -> Pd {
+        let d = self.e - e;
+        let v = if d > 0 {
+            self.v.wrapping_mul(pow10(d))
+        } else if d < 0 {
+            self.v / pow10(-d)
+        } else {
+            self.v
+        };
+        Pd::new(v, e)
+    }
+
+    fn mul(self, other: Pd) -> Pd {
+        Pd::new(self.v.wrapping_mul(other.v), self.e + other.e).scale()
+    }
+
+    fn div(self, other: Pd) -> Pd {
+        if self.v == 0 {
+            return self;
+        }
+        let (mut v1, mut v2) = (self.v, other.v);
+        let (neg1, neg2) = (v1 < 0, v2 < 0);
+        let mut m = 0;
+        if neg1 {
+            v1 = v1.wrapping_neg();
+        }
+        if neg2 {
+            v2 = v2.wrapping_neg();
+        }
+        while (v1 as u64) & 0xfffffffff0000000 == 0 {
+            v1 = v1.wrapping_mul(10);
+            m += 1;
+        }
+        let mut v = v1.wrapping_mul(PD_SCALE9).wrapping_div(v2);
+        if neg1 {
+            v = v.wrapping_neg();
+        }
+        if neg2 {
+            v = v.wrapping_neg();
+        }
+        Pd::new(v, self.e - other.e - m - 9).scale()
+    }
+
+    fn add(self, other: Pd) -> Pd {
+        let d = self.e - other.e;
+        let r = if d == 0 {
+            Pd::new(self.v.wrapping_add(other.v), self.e)
+        } else if d > 0 {
+            if d < 9 {
+                Pd::new(self.v.wrapping_mul(pow10(d)).wrapping_add(other.v), other.e)
+            } else if d < PC_FACTOR_SIZE + 9 {
+                Pd::new(
+                    self.v
+                        .wrapping_mul(PD_SCALE9)
+                        .wrapping_add(other.v / pow10(d - 9)),
+                    self.e - 9,
+                )
+            } else {
+                self
+            }
+        } else {
+            let d = -d;
+            if d < 9 {
+                Pd::new(self.v.wrapping_add(other.v.wrapping_mul(pow10(d))), self.e)
+            } else if d < PC_FACTOR_SIZE + 9 {
+                Pd::new(
+                    (self.v / pow10(d - 9)).wrapping_add(other.v.wrapping_mul(PD_SCALE9)),
+                    other.e - 9,
+                )
+            } else {
+                other
+            }
+        };
+        r.scale()
+    }
+}
+
+/// `10^d`. The C code reads `10^d` from a table of `PC_FACTOR_SIZE` entries and the callers never
+/// go past its end for the exponents allowed in price accounts. Beyond the table, this wraps
+/// like the multiplication it is used for.
+fn pow10(d: i32) -> i64 {
+    match FACT.get(d as usize) {
+        Some(p) => *p,
+        None => 10i64.wrapping_pow(d as u32),
+    }
+}
+
+/// `floor((x + y) / 2)` without intermediate overflow
+fn avg_2_int64(x: i64, y: i64) -> i64 {
+    ((i128::from(x) + i128::from(y)) >> 1) as i64
+}
+
+/// Minimizes the loss model of the given quotes, equivalent to `price_model_core`.
+/// Sorts `quotes` in ascending order and returns the p25, p50 and p75 of the model.
+/// Assumes `quotes` is not empty.
+pub fn price_model_core(quotes: &mut [i64]) -> (i64, i64, i64) {
+    quotes.sort_unstable();
+    let cnt = quotes.len();
+
+    let p25_idx = cnt >> 2;
+    let p25 = quotes[p25_idx];
+
+    let p50 = if cnt & 1 == 1 {
+        quotes[cnt >> 1]
+    } else {
+        let p50_idx_right = cnt >> 1;
+        avg_2_int64(quotes[p50_idx_right - 1], quotes[p50_idx_right])
+    };
+
+    let p75_idx = cnt - 1 - p25_idx;
+    let p75 = quotes[p75_idx];
+
+    (p25, p50, p75)
+}
+
+/// Update a time-weighted exponential moving average with `val`, weighted by the inverse of
+/// `conf`
+fn upd_ema(ema: &mut pc_ema_t, mut val: Pd, conf: Pd, nslot: i64, expo: i32) {
+    let one = Pd::new(100_000_000, -8);
+    let cwgt = if conf.v != 0 { one.div(conf) } else { one };
+
+    let mut numer;
+    let mut denom;
+    if nslot > PD_EMA_MAX_DIFF {
+        // initial condition
+        numer = val.mul(cwgt);
+        denom = cwgt;
+    } else {
+        // compute decay factor
+        let diff = Pd::new(nslot, 0);
+        let decay = Pd::new(PD_EMA_DECAY, PD_EMA_EXPO).mul(diff).add(one);
+
+        // compute numer/denom and new value from decay factor
+        numer = Pd::load(ema.numer_);
+        denom = Pd::load(ema.denom_);
+        if numer.v < 0 || denom.v < 0 {
+            // temporary reset twap on negative value
+            numer = val;
+            denom = one;
+        } else {
+            numer = numer.mul(decay).add(val.mul(cwgt));
+            denom = denom.mul(decay).add(cwgt);
+            val = numer.div(denom);
+        }
+    }
+
+    // adjust and store results
+    ema.val_ = val.adjust(expo).v;
+    if let (Some(numer), Some(denom)) = (numer.store(), denom.store()) {
+        ema.numer_ = numer;
+        ema.denom_ = denom;
+    }
+}
+
+fn upd_twap(price_account: &mut pc_price_t, nslots: i64) {
+    let expo = price_account.expo_;
+    let price = Pd::new_scale(price_account.agg_.price_, expo);
+    let conf = Pd::new_scale(price_account.agg_.conf_ as i64, expo);
+    upd_ema(&mut price_account.twap_, price, conf, nslots, expo);
+    upd_ema(&mut price_account.twac_, conf, conf, nslots, expo);
+}
+
+/// Maximum number of slots between the publish slot of a component price and the aggregation
+/// slot, using the default latency if the account does not set one
+pub fn get_max_latency(price_account: &pc_price_t) -> i64 {
+    match price_account.max_latency_ {
+        0 => i64::from(PC_MAX_SEND_LATENCY),
+        max_latency => i64::from(max_latency),
+    }
+}
+
+/// Whether the component price `quote` is recent enough to be aggregated in slot `clock_slot`
+pub fn is_fresh_quote(quote: &pc_price_info_t, clock_slot: u64, max_latency: i64) -> bool {
+    let slot_diff = (clock_slot as i64).wrapping_sub(quote.pub_slot_ as i64);
+    slot_diff >= 0 && slot_diff <= max_latency
+}
+
+/// Whether the component price `quote` is included in the aggregate price of slot `clock_slot`
+pub fn is_valid_quote(quote: &pc_price_info_t, clock_slot: u64, max_latency: i64) -> bool {
+    let price = quote.price_;
+    let conf = quote.conf_ as i64;
+    quote.status_ == PC_STATUS_TRADING
+        && 0 < conf
+        && conf < price
+        && conf <= i64::MAX - price
+        && is_fresh_quote(quote, clock_slot, max_latency)
+}
+
+/// Compute the aggregate price of `price_account` in slot `clock_slot`, equivalent to
+/// `upd_aggregate`. The components of the account are its `comp_` followed by
+/// `extra_components`, the components stored in the extra publisher space of resized accounts.
+/// Returns `true` if a new aggregate price with status TRADING was computed.
+pub fn upd_aggregate(
+    price_account: &mut pc_price_t,
+    extra_components: &mut [pc_price_comp_t],
+    clock_slot: u64,
+    clock_timestamp: i64,
+) -> bool {
+    // only re-compute aggregate in next slot
+    if clock_slot <= price_account.agg_.pub_slot_ {
+        return false;
+    }
+
+    // get number of slots from last published valid price
+    let agg_diff = (clock_slot as i64).wrapping_sub(price_account.last_slot_ as i64);
+
+    // Update the value of the previous price, if it had TRADING status.
+    if price_account.agg_.status_ == PC_STATUS_TRADING {
+        price_account.prev_slot_ = price_account.agg_.pub_slot_;
+        price_account.prev_price_ = price_account.agg_.price_;
+        price_account.prev_conf_ = price_account.agg_.conf_;
+        price_account.prev_timestamp_ = price_account.timestamp_;
+    }
+
+    // update aggregate details ready for next slot
+    price_account.valid_slot_ = price_account.agg_.pub_slot_;
+    price_account.agg_.pub_slot_ = clock_slot;
+    price_account.timestamp_ = clock_timestamp;
+
+    // identify valid quotes
+    let max_latency = get_max_latency(price_account);
+    let mut numv: u32 = 0;
+    let mut nprcs: usize = 0;
+    let mut prcs = [0i64; MAX_NUM_COMPONENTS * 3];
+    let num_components = num_components(price_account);
+    for component in price_account
+        .comp_
+        .iter_mut()
+        .chain(extra_components.iter_mut())
+        .take(num_components)
+    {
+        // copy contributing price to aggregate snapshot
+        component.agg_ = component.latest_;
+        // add quote to sorted permutation array if it is valid
+        if is_valid_quote(&component.agg_, clock_slot, max_latency) {
+            let price = component.agg_.price_;
+            let conf = component.agg_.conf_ as i64;
+            numv += 1;
+            prcs[nprcs] = price - conf;
+            prcs[nprcs + 1] = price;
+            prcs[nprcs + 2] = price + conf;
+            nprcs += 3;
+        }
+    }
+
+    // too few valid quotes
+    price_account.num_qt_ = numv;
+    if numv == 0 || numv < u32::from(price_account.min_pub_) {
+        price_account.agg_.status_ = PC_STATUS_UNKNOWN;
+        return false;
+    }
+
+    // evaluate the model to get the p25/p50/p75 prices
+    let (agg_p25, agg_price, agg_p75) = price_model_core(&mut prcs[..nprcs]);
+
+    // get the left and right confidences, these can't overflow since the quotes are positive and
+    // agg_p25, agg_price, agg_p75 are ordered
+    let agg_conf_left = agg_price - agg_p25;
+    let agg_conf_right = agg_p75 - agg_price;
+
+    // use the larger of the left and right confidences
+    let agg_conf = agg_conf_left.max(agg_conf_right);
+
+    // if the confidences end up at zero, we abort
+    if agg_conf <= 0 {
+        price_account.agg_.status_ = PC_STATUS_UNKNOWN;
+        return false;
+    }
+
+    // update status and publish slot of last trading status price
+    price_account.agg_.status_ = PC_STATUS_TRADING;
+    price_account.last_slot_ = clock_slot;
+    price_account.agg_.price_ = agg_price;
+    price_account.agg_.conf_ = agg_conf as u64;
+
+    upd_twap(price_account, agg_diff);
+    true
+}
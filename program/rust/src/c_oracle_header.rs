@@ -52,6 +52,19 @@ impl PythAccount for pc_price_t {
     const INITIAL_SIZE: u32 = PC_PRICE_T_COMP_OFFSET as u32;
 }
 
+impl PythAccount for pc_permissions_t {
+    const ACCOUNT_TYPE: u32 = PC_ACCTYPE_PERMISSIONS;
+    const INITIAL_SIZE: u32 = size_of::<pc_permissions_t>() as u32;
+}
+
+/// The seed of the permissions account, a PDA of the program
+pub const PERMISSIONS_SEED: &str = "permissions";
+
+/// The first seed of the mapping, product and price accounts created at PDAs of the program
+pub const MAPPING_SEED: &str = "mapping";
+pub const PRODUCT_SEED: &str = "product";
+pub const PRICE_SEED: &str = "price";
+
 #[cfg(target_endian = "little")]
 unsafe impl Zeroable for pc_acc {
 }
@@ -155,6 +168,70 @@ unsafe impl Zeroable for cmd_set_min_pub_t {
 unsafe impl Pod for cmd_set_min_pub_t {
 }
 
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for cmd_set_max_latency_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for cmd_set_max_latency_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for cmd_set_max_ci_divisor_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for cmd_set_max_ci_divisor_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for cmd_resize_product_account_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for cmd_resize_product_account_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for cmd_upd_price_batch_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for cmd_upd_price_batch_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for cmd_upd_price_batch_entry_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for cmd_upd_price_batch_entry_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for cmd_query_time_machine_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for cmd_query_time_machine_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for cmd_upd_permissions_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for cmd_upd_permissions_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for pc_permissions_t {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for pc_permissions_t {
+}
+
 #[cfg(target_endian = "little")]
 unsafe impl Zeroable for pc_pub_key_t {
 }
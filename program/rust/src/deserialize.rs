@@ -1,15 +1,26 @@
 use std::mem::size_of;
 
 use bytemuck::{
+    try_cast_slice_mut,
     try_from_bytes,
     try_from_bytes_mut,
+    try_pod_read_unaligned,
     Pod,
 };
 
 use crate::c_oracle_header::{
     pc_acc,
+    pc_price_comp_t,
+    pc_price_t,
     PythAccount,
+    PC_EXTRA_COMP_SIZE,
     PC_MAGIC,
+    PC_MIN_VERSION,
+};
+use crate::error::OracleError;
+use crate::time_machine_types::{
+    TimeMachineWrapper,
+    TIME_MACHINE_OFFSET,
 };
 use crate::utils::{
     clear_account,
@@ -32,7 +43,6 @@ pub fn load<T: Pod>(data: &[u8]) -> Result<&T, ProgramError> {
 }
 
 /// Interpret the bytes in `data` as a mutable value of type `T`
-#[allow(unused)]
 pub fn load_mut<T: Pod>(data: &mut [u8]) -> Result<&mut T, ProgramError> {
     try_from_bytes_mut(
         data.get_mut(0..size_of::<T>())
@@ -66,19 +76,95 @@ pub fn load_checked<'a, T: PythAccount>(
     account: &'a AccountInfo,
     version: u32,
 ) -> Result<RefMut<'a, T>, ProgramError> {
-    {
-        let account_header = load_account_as::<pc_acc>(account)?;
-        pyth_assert(
-            account_header.magic_ == PC_MAGIC
-                && account_header.ver_ == version
-                && account_header.type_ == T::ACCOUNT_TYPE,
-            ProgramError::InvalidArgument,
-        )?;
-    }
+    check_header::<T>(&*load_account_as::<pc_acc>(account)?, version)?;
 
     load_account_as_mut::<T>(account)
 }
 
+/// Interpret the bytes in `data` as a value of type `T`, after checking the account header like
+/// `load_checked`
+pub fn load_checked_bytes<T: PythAccount>(data: &[u8], version: u32) -> Result<&T, ProgramError> {
+    check_header::<T>(load::<pc_acc>(data)?, version)?;
+
+    load::<T>(data)
+}
+
+/// Interpret the bytes in `data`, the data of a price account, as a `pc_price_t` and the components
+/// stored in its extra publisher space, after checking the account header like `load_checked`.
+/// Price accounts that were not resized have no extra components.
+pub fn load_price_components_checked(
+    data: &mut [u8],
+    version: u32,
+) -> Result<(&mut pc_price_t, &mut [pc_price_comp_t]), ProgramError> {
+    check_header::<pc_price_t>(load::<pc_acc>(data)?, version)?;
+
+    pyth_assert(
+        data.len() >= size_of::<pc_price_t>(),
+        ProgramError::InvalidArgument,
+    )?;
+    let (price_data, extra_publisher_space) = data.split_at_mut(size_of::<pc_price_t>());
+    let extra_components = match extra_publisher_space
+        .get_mut(..size_of::<[pc_price_comp_t; PC_EXTRA_COMP_SIZE as usize]>())
+    {
+        Some(extra_components) => {
+            try_cast_slice_mut(extra_components).map_err(|_| ProgramError::InvalidArgument)?
+        }
+        None => &mut [],
+    };
+    Ok((load_mut::<pc_price_t>(price_data)?, extra_components))
+}
+
+/// Interpret the bytes in `data`, the data of a resized price account, as a `pc_price_t` and its
+/// Time Machine, after checking the account header like `load_checked`. The accounts resized before
+/// the publisher statistics were added have a Time Machine as well.
+pub fn load_time_machine_checked(
+    data: &mut [u8],
+    version: u32,
+) -> Result<(&mut pc_price_t, &mut TimeMachineWrapper), ProgramError> {
+    check_header::<pc_price_t>(load::<pc_acc>(data)?, version)?;
+
+    pyth_assert(
+        data.len() >= TIME_MACHINE_OFFSET + size_of::<TimeMachineWrapper>(),
+        ProgramError::InvalidArgument,
+    )?;
+    let (price_data, time_machine) = data.split_at_mut(TIME_MACHINE_OFFSET);
+    Ok((
+        load_mut::<pc_price_t>(price_data)?,
+        load_mut::<TimeMachineWrapper>(time_machine)?,
+    ))
+}
+
+/// Copy the bytes in `data`, which don't need to be aligned, into a value of type `T`, after
+/// checking the account header like `load_checked`
+pub fn read_checked_bytes<T: PythAccount>(data: &[u8], version: u32) -> Result<T, ProgramError> {
+    check_header::<T>(&read_unaligned::<pc_acc>(data)?, version)?;
+
+    read_unaligned::<T>(data)
+}
+
+fn read_unaligned<T: Pod>(data: &[u8]) -> Result<T, ProgramError> {
+    try_pod_read_unaligned(
+        data.get(0..size_of::<T>())
+            .ok_or(ProgramError::InvalidArgument)?,
+    )
+    .map_err(|_| ProgramError::InvalidArgument)
+}
+
+fn check_header<T: PythAccount>(account_header: &pc_acc, version: u32) -> Result<(), ProgramError> {
+    pyth_assert(
+        account_header.magic_ == PC_MAGIC && account_header.type_ == T::ACCOUNT_TYPE,
+        OracleError::InvalidAccountHeader.into(),
+    )?;
+    match account_header.ver_ {
+        ver if ver == version => Ok(()),
+        // Older supported layouts are only accepted once migrated
+        ver if PC_MIN_VERSION <= ver && ver < version => {
+            Err(OracleError::AccountNeedsMigration.into())
+        }
+        _ => Err(OracleError::InvalidAccountVersion.into()),
+    }
+}
+
 pub fn initialize_pyth_account_checked<'a, T: PythAccount>(
     account: &'a AccountInfo,
     version: u32,
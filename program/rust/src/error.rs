@@ -6,30 +6,91 @@ use thiserror::Error;
 // similar to ProgramResult but allows for multiple success values
 pub type OracleResult = Result<u64, ProgramError>;
 
-/// Errors that may be returned by the oracle program
+/// Errors that may be returned by the oracle program. The numbers are part of the interface of the
+/// program and must not change.
 #[derive(Clone, Debug, Eq, Error, PartialEq)]
 pub enum OracleError {
     /// Generic catch all error
-    #[error("Generic")]
-    Generic                 = 600,
+    #[error("Generic error")]
+    Generic                   = 600,
     /// integer casting error
-    #[error("IntegerCastingError")]
-    IntegerCastingError     = 601,
+    #[error("An integer does not fit in the target type")]
+    IntegerCastingError       = 601,
     /// c_entrypoint returned an unexpected value
-    #[error("UnknownCError")]
-    UnknownCError           = 602,
-    #[error("UnrecognizedInstruction")]
-    UnrecognizedInstruction = 603,
-    #[error("InvalidFundingAccount")]
-    InvalidFundingAccount   = 604,
-    #[error("InvalidSignableAccount")]
-    InvalidSignableAccount  = 605,
-    #[error("InvalidSystemAccount")]
-    InvalidSystemAccount    = 606,
-    #[error("InvalidWritableAccount")]
-    InvalidWritableAccount  = 607,
-    #[error("InvalidFreshAccount")]
-    InvalidFreshAccount     = 608,
+    #[error("The C code returned an unexpected value")]
+    UnknownCError             = 602,
+    #[error("The instruction is not recognized")]
+    UnrecognizedInstruction   = 603,
+    #[error("The funding account must be a writable signer")]
+    InvalidFundingAccount     = 604,
+    #[error("The account must be a writable, rent exempt signer owned by the program")]
+    InvalidSignableAccount    = 605,
+    #[error("The account is not the system program")]
+    InvalidSystemAccount      = 606,
+    #[error("The account must be writable, rent exempt and owned by the program")]
+    InvalidWritableAccount    = 607,
+    #[error("The account is already initialized")]
+    InvalidFreshAccount       = 608,
+    #[error("The instruction version is outside of the versions supported by the program")]
+    InvalidInstructionVersion = 609,
+    #[error("The instruction has the wrong number of accounts")]
+    InvalidNumberOfAccounts   = 610,
+    #[error("The account is not an oracle account of the expected type")]
+    InvalidAccountHeader      = 611,
+    #[error("The account version is outside of the versions supported by the program")]
+    InvalidAccountVersion     = 612,
+    #[error("The publisher is not a publisher of the price account")]
+    UnknownPublisher          = 613,
+    #[error("The publisher already published a price for a more recent slot")]
+    StalePublisherSlot        = 614,
+    #[error("The price account has no room for another publisher")]
+    ComponentTableFull        = 615,
+    #[error("The publisher is already a publisher of the price account")]
+    DuplicatePublisher        = 616,
+    #[error("The publisher key is zero")]
+    InvalidPublisherKey       = 617,
+    #[error("The exponent is out of range")]
+    InvalidExponent           = 618,
+    #[error("The price type is unknown or does not match the price account")]
+    InvalidPriceType          = 619,
+    #[error("The mapping account has no room for another product")]
+    MappingAccountFull        = 620,
+    #[error("The mapping account is not a full account at the end of the mapping list")]
+    InvalidMappingTail        = 621,
+    #[error("The account does not have the expected size")]
+    InvalidAccountSize        = 622,
+    #[error("Accounts can only grow, up to the maximum size of their type")]
+    InvalidResize             = 623,
+    #[error("The product metadata does not fit in the product account")]
+    MetadataTooLarge          = 624,
+    #[error("The product has no attribute with this key")]
+    AttributeNotFound         = 625,
+    #[error("The maximum confidence interval divisor can't be 1")]
+    InvalidMaxCiDivisor       = 626,
+    #[error("The price account still has publishers")]
+    PriceAccountHasPublishers = 627,
+    #[error("The price account is not linked from the given product or price account")]
+    PriceAccountNotLinked     = 628,
+    #[error("The product account still has price accounts")]
+    ProductAccountHasPrices   = 629,
+    #[error("The product account is not listed in the mapping account")]
+    ProductNotInMapping       = 630,
+    #[error("The time machine has no moving average for this window")]
+    SmaUnavailable            = 631,
+    #[error("The account has an older layout, upgrade it with migrate_account")]
+    AccountNeedsMigration     = 632,
+    #[error("The account is not the permissions account of the program")]
+    InvalidPermissionsAccount = 633,
+    #[error("The funding account is not allowed to run the instruction")]
+    PermissionDenied          = 634,
+    #[error("The account is not the programdata account of the program")]
+    InvalidProgramDataAccount = 635,
+    #[error("The account is not the program derived address expected by the instruction")]
+    InvalidPdaAccount         = 636,
+    #[error("The account is not owned by the program")]
+    InvalidAccountOwner       = 637,
+    #[error("The window is not a multiple of the SMA granularity that fits in the time machine")]
+    InvalidSmaWindow          = 638,
 }
 
 impl From<OracleError> for ProgramError {
@@ -0,0 +1,302 @@
+//! Structured events, logged with `sol_log_data` so that indexers don't have to parse the `msg!`
+//! logs of `log.rs`. Each record is an `EventHeader` followed by the payload of the event, both
+//! encoded with bytemuck. The runtime logs the record in base64 as `Program data: <record>`.
+//! `EVENT_SCHEMA_VERSION` changes whenever the layout of a record changes.
+//! The handlers emit their events once the state of the accounts has changed, so that an
+//! instruction that fails, or an update that `upd_price_no_fail_on_error` skips, logs nothing.
+use std::mem::size_of;
+
+use bytemuck::{
+    bytes_of,
+    try_pod_read_unaligned,
+    Pod,
+    Zeroable,
+};
+use solana_program::log::sol_log_data;
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;
+
+use crate::c_oracle_header::{
+    pc_price_info_t,
+    pc_price_t,
+};
+
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EventHeader {
+    pub schema_version: u32,
+    pub kind:           u32,
+}
+
+/// The price of a publisher as stored in the price account, after an update. The status is
+/// UNKNOWN if the confidence interval was too wide. `upd_price_batch` logs one event per entry
+/// that was applied.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UpdatePriceEvent {
+    pub publisher:       [u8; 32],
+    pub price_account:   [u8; 32],
+    pub price:           i64,
+    pub conf:            u64,
+    pub pub_slot:        u64,
+    pub status:          u32,
+    pub corp_act_status: u32,
+}
+
+/// A new aggregate price with TRADING status
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UpdateAggregateEvent {
+    pub price_account: [u8; 32],
+    pub price:         i64,
+    pub conf:          u64,
+    pub twap:          i64,
+    pub twac:          i64,
+    pub pub_slot:      u64,
+    pub solana_time:   i64,
+    pub expo:          i32,
+    pub status:        u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PublisherEvent {
+    pub price_account: [u8; 32],
+    pub publisher:     [u8; 32],
+}
+
+/// A change of one of the parameters of a price account
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PriceParameterEvent {
+    pub price_account: [u8; 32],
+    pub value:         u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InitPriceEvent {
+    pub price_account: [u8; 32],
+    pub expo:          i32,
+    pub ptype:         u32,
+}
+
+/// An account that was linked to or unlinked from `parent`, like a product of a mapping account
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LinkEvent {
+    pub parent: [u8; 32],
+    pub child:  [u8; 32],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AccountEvent {
+    pub account: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PermissionsEvent {
+    pub master_authority:        [u8; 32],
+    pub data_curation_authority: [u8; 32],
+    pub security_authority:      [u8; 32],
+}
+
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(
+            #[cfg(target_endian = "little")]
+            unsafe impl Zeroable for $t {
+            }
+
+            #[cfg(target_endian = "little")]
+            unsafe impl Pod for $t {
+            }
+        )*
+    };
+}
+
+impl_pod!(
+    EventHeader,
+    UpdatePriceEvent,
+    UpdateAggregateEvent,
+    PublisherEvent,
+    PriceParameterEvent,
+    InitPriceEvent,
+    LinkEvent,
+    AccountEvent,
+    PermissionsEvent
+);
+
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum EventError {
+    #[error("The record has schema version {0}")]
+    UnsupportedVersion(u32),
+    #[error("The record has the unknown kind {0}")]
+    UnknownKind(u32),
+    #[error("The record does not have the length of its kind")]
+    InvalidLength,
+    #[error("The log is not a valid record")]
+    InvalidLog,
+}
+
+// Declare the events with the kind that identifies them in the header. Kinds must not be reused.
+macro_rules! events {
+    ($($variant:ident($payload:ty) = $kind:literal,)*) => {
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub enum Event {
+            $($variant($payload),)*
+        }
+
+        impl Event {
+            pub fn kind(&self) -> u32 {
+                match self {
+                    $(Event::$variant(_) => $kind,)*
+                }
+            }
+
+            /// The record of this event, as logged by `emit`
+            pub fn encode(&self) -> Vec<u8> {
+                let header = EventHeader {
+                    schema_version: EVENT_SCHEMA_VERSION,
+                    kind:           self.kind(),
+                };
+                let mut record = bytes_of(&header).to_vec();
+                match self {
+                    $(Event::$variant(payload) => record.extend_from_slice(bytes_of(payload)),)*
+                }
+                record
+            }
+
+            /// Decode a record produced by `encode`
+            pub fn decode(record: &[u8]) -> Result<Event, EventError> {
+                let header: EventHeader = read(record.get(..size_of::<EventHeader>()))?;
+                if header.schema_version != EVENT_SCHEMA_VERSION {
+                    return Err(EventError::UnsupportedVersion(header.schema_version));
+                }
+                let payload = &record[size_of::<EventHeader>()..];
+                match header.kind {
+                    $($kind => Ok(Event::$variant(read(Some(payload))?)),)*
+                    kind => Err(EventError::UnknownKind(kind)),
+                }
+            }
+        }
+    };
+}
+
+events!(
+    UpdatePrice(UpdatePriceEvent) = 1,
+    UpdateAggregate(UpdateAggregateEvent) = 2,
+    AddPublisher(PublisherEvent) = 3,
+    DelPublisher(PublisherEvent) = 4,
+    SetMinPub(PriceParameterEvent) = 5,
+    SetMaxLatency(PriceParameterEvent) = 6,
+    SetMaxCiDivisor(PriceParameterEvent) = 7,
+    ResizePriceAccount(AccountEvent) = 8,
+    InitMapping(AccountEvent) = 9,
+    AddMapping(LinkEvent) = 10,
+    AddProduct(LinkEvent) = 11,
+    UpdProduct(AccountEvent) = 12,
+    SetProductAttr(AccountEvent) = 13,
+    DelProductAttr(AccountEvent) = 14,
+    ResizeProductAccount(AccountEvent) = 15,
+    DelProduct(LinkEvent) = 16,
+    AddPrice(LinkEvent) = 17,
+    InitPrice(InitPriceEvent) = 18,
+    DelPrice(LinkEvent) = 19,
+    MigrateAccount(AccountEvent) = 20,
+    UpdPermissions(PermissionsEvent) = 21,
+);
+
+impl Event {
+    /// Decode the record logged in `log`, a `Program data: <base64>` line of the logs of a
+    /// transaction
+    #[cfg(feature = "client")]
+    pub fn decode_log(log: &str) -> Result<Event, EventError> {
+        let record = log
+            .strip_prefix("Program data: ")
+            .and_then(|data| base64::decode(data).ok())
+            .ok_or(EventError::InvalidLog)?;
+        Event::decode(&record)
+    }
+}
+
+fn read<T: Pod>(data: Option<&[u8]>) -> Result<T, EventError> {
+    data.and_then(|data| try_pod_read_unaligned(data).ok())
+        .ok_or(EventError::InvalidLength)
+}
+
+/// Log `event` as a structured record
+pub fn emit(event: &Event) {
+    sol_log_data(&[&event.encode()]);
+}
+
+/// The event of the new aggregate price of `price_data`, stored in `price_account`
+pub fn aggregate_event(price_account: &Pubkey, price_data: &pc_price_t) -> Event {
+    Event::UpdateAggregate(UpdateAggregateEvent {
+        price_account: price_account.to_bytes(),
+        price:         price_data.agg_.price_,
+        conf:          price_data.agg_.conf_,
+        twap:          price_data.twap_.val_,
+        twac:          price_data.twac_.val_,
+        pub_slot:      price_data.agg_.pub_slot_,
+        solana_time:   price_data.timestamp_,
+        expo:          price_data.expo_,
+        status:        price_data.agg_.status_,
+    })
+}
+
+/// The event of the price of `publisher` in `price_account`, as stored by an update
+pub fn price_event(
+    publisher: &Pubkey,
+    price_account: &Pubkey,
+    price_info: &pc_price_info_t,
+) -> Event {
+    Event::UpdatePrice(UpdatePriceEvent {
+        publisher:       publisher.to_bytes(),
+        price_account:   price_account.to_bytes(),
+        price:           price_info.price_,
+        conf:            price_info.conf_,
+        pub_slot:        price_info.pub_slot_,
+        status:          price_info.status_,
+        corp_act_status: price_info.corp_act_status_,
+    })
+}
+
+impl PublisherEvent {
+    pub fn new(price_account: &Pubkey, publisher: &Pubkey) -> Self {
+        PublisherEvent {
+            price_account: price_account.to_bytes(),
+            publisher:     publisher.to_bytes(),
+        }
+    }
+}
+
+impl PriceParameterEvent {
+    pub fn new(price_account: &Pubkey, value: u64) -> Self {
+        PriceParameterEvent {
+            price_account: price_account.to_bytes(),
+            value,
+        }
+    }
+}
+
+impl LinkEvent {
+    pub fn new(parent: &Pubkey, child: &Pubkey) -> Self {
+        LinkEvent {
+            parent: parent.to_bytes(),
+            child:  child.to_bytes(),
+        }
+    }
+}
+
+impl AccountEvent {
+    pub fn new(account: &Pubkey) -> Self {
+        AccountEvent {
+            account: account.to_bytes(),
+        }
+    }
+}
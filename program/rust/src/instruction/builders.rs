@@ -0,0 +1,848 @@
+//! Builders for the instructions of the oracle program, to be used off-chain with the `client`
+//! feature. Each builder lists the accounts in the order and with the signer and writable flags
+//! that the matching handler of `rust_oracle.rs` expects.
+use bytemuck::bytes_of;
+use solana_program::instruction::{
+    AccountMeta,
+    Instruction,
+};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::{
+    Pubkey,
+    MAX_SEED_LEN,
+};
+use solana_program::sysvar::clock;
+use solana_program::{
+    bpf_loader_upgradeable,
+    system_program,
+};
+
+use crate::c_oracle_header::{
+    cmd_add_price_t,
+    cmd_add_publisher_t,
+    cmd_del_publisher_t,
+    cmd_hdr_t,
+    cmd_init_price_t,
+    cmd_query_time_machine_t,
+    cmd_resize_product_account_t,
+    cmd_set_max_ci_divisor_t,
+    cmd_set_max_latency_t,
+    cmd_set_min_pub_t,
+    cmd_upd_permissions_t,
+    cmd_upd_price_batch_entry_t,
+    cmd_upd_price_batch_t,
+    cmd_upd_price_t,
+    command_t_e_cmd_add_mapping,
+    command_t_e_cmd_add_mapping_pda,
+    command_t_e_cmd_add_price,
+    command_t_e_cmd_add_price_pda,
+    command_t_e_cmd_add_product,
+    command_t_e_cmd_add_product_pda,
+    command_t_e_cmd_add_publisher,
+    command_t_e_cmd_agg_price,
+    command_t_e_cmd_del_price,
+    command_t_e_cmd_del_product,
+    command_t_e_cmd_del_product_attr,
+    command_t_e_cmd_del_publisher,
+    command_t_e_cmd_init_mapping,
+    command_t_e_cmd_init_mapping_pda,
+    command_t_e_cmd_init_price,
+    command_t_e_cmd_migrate_account,
+    command_t_e_cmd_query_time_machine,
+    command_t_e_cmd_resize_price_account,
+    command_t_e_cmd_resize_product_account,
+    command_t_e_cmd_set_max_ci_divisor,
+    command_t_e_cmd_set_max_latency,
+    command_t_e_cmd_set_min_pub,
+    command_t_e_cmd_set_product_attr,
+    command_t_e_cmd_upd_permissions,
+    command_t_e_cmd_upd_price,
+    command_t_e_cmd_upd_price_batch,
+    command_t_e_cmd_upd_price_no_fail_on_error,
+    command_t_e_cmd_upd_product,
+    pc_pub_key_t,
+    PC_STATUS_UNKNOWN,
+    PC_VERSION,
+};
+use crate::instruction::{
+    OracleInstruction,
+    ProductAttribute,
+};
+pub use crate::utils::{
+    get_mapping_address,
+    get_permissions_address,
+    get_price_address,
+    get_product_address,
+};
+
+/// Build an `init_mapping` instruction
+pub fn init_mapping(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    mapping_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*mapping_account, true),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       bytes_of(&hdr(command_t_e_cmd_init_mapping)).to_vec(),
+    }
+}
+
+/// Build an `add_mapping` instruction that links `next_mapping_account` after the full
+/// `cur_mapping_account`
+pub fn add_mapping(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    cur_mapping_account: &Pubkey,
+    next_mapping_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*cur_mapping_account, false),
+            AccountMeta::new(*next_mapping_account, true),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       bytes_of(&hdr(command_t_e_cmd_add_mapping)).to_vec(),
+    }
+}
+
+/// Build an `add_product` instruction
+pub fn add_product(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    mapping_account: &Pubkey,
+    product_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*mapping_account, false),
+            AccountMeta::new(*product_account, true),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       bytes_of(&hdr(command_t_e_cmd_add_product)).to_vec(),
+    }
+}
+
+/// Build an `init_mapping` instruction that also creates `mapping_account`, funded by
+/// `funding_account`
+pub fn create_and_init_mapping(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    mapping_account: &Pubkey,
+) -> Instruction {
+    with_system_program(init_mapping(program_id, funding_account, mapping_account))
+}
+
+/// Build an `add_mapping` instruction that also creates `next_mapping_account`, funded by
+/// `funding_account`
+pub fn create_and_add_mapping(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    cur_mapping_account: &Pubkey,
+    next_mapping_account: &Pubkey,
+) -> Instruction {
+    with_system_program(add_mapping(
+        program_id,
+        funding_account,
+        cur_mapping_account,
+        next_mapping_account,
+    ))
+}
+
+/// Build an `add_product` instruction that also creates `product_account`, funded by
+/// `funding_account`
+pub fn create_and_add_product(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    mapping_account: &Pubkey,
+    product_account: &Pubkey,
+) -> Instruction {
+    with_system_program(add_product(
+        program_id,
+        funding_account,
+        mapping_account,
+        product_account,
+    ))
+}
+
+/// Build an `upd_product` instruction that replaces the metadata of the product with
+/// `attributes`. Fails if a key or a value is longer than 255 bytes.
+pub fn upd_product(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    attributes: &[ProductAttribute],
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts:   product_accounts(program_id, funding_account, product_account),
+        data:       OracleInstruction::UpdProduct {
+            hdr:        hdr(command_t_e_cmd_upd_product),
+            attributes: attributes.to_vec(),
+        }
+        .pack()?,
+    })
+}
+
+/// Build a `set_product_attr` instruction. Fails if the key or the value is longer than 255
+/// bytes.
+pub fn set_product_attr(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    key: &[u8],
+    value: &[u8],
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts:   product_accounts(program_id, funding_account, product_account),
+        data:       OracleInstruction::SetProductAttr {
+            hdr:   hdr(command_t_e_cmd_set_product_attr),
+            key:   key.to_vec(),
+            value: value.to_vec(),
+        }
+        .pack()?,
+    })
+}
+
+/// Build a `del_product_attr` instruction. Fails if the key is longer than 255 bytes.
+pub fn del_product_attr(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    key: &[u8],
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts:   product_accounts(program_id, funding_account, product_account),
+        data:       OracleInstruction::DelProductAttr {
+            hdr: hdr(command_t_e_cmd_del_product_attr),
+            key: key.to_vec(),
+        }
+        .pack()?,
+    })
+}
+
+/// Build a `resize_product_account` instruction that grows the product account to `size` bytes
+pub fn resize_product_account(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    size: u32,
+) -> Instruction {
+    let cmd = cmd_resize_product_account_t {
+        ver_:  PC_VERSION,
+        cmd_:  command_t_e_cmd_resize_product_account as i32,
+        size_: size,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*product_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+/// Build a `del_product` instruction
+pub fn del_product(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    mapping_account: &Pubkey,
+    product_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*mapping_account, false),
+            AccountMeta::new(*product_account, false),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       bytes_of(&hdr(command_t_e_cmd_del_product)).to_vec(),
+    }
+}
+
+/// Build an `add_price` instruction
+pub fn add_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    price_account: &Pubkey,
+    expo: i32,
+    ptype: u32,
+) -> Instruction {
+    let cmd = cmd_add_price_t {
+        ver_:   PC_VERSION,
+        cmd_:   command_t_e_cmd_add_price as i32,
+        expo_:  expo,
+        ptype_: ptype,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*product_account, false),
+            AccountMeta::new(*price_account, true),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+/// Build an `add_price` instruction that also creates `price_account`, funded by
+/// `funding_account`
+pub fn create_and_add_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    price_account: &Pubkey,
+    expo: i32,
+    ptype: u32,
+) -> Instruction {
+    with_system_program(add_price(
+        program_id,
+        funding_account,
+        product_account,
+        price_account,
+        expo,
+        ptype,
+    ))
+}
+
+/// Build an `init_price` instruction
+pub fn init_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    expo: i32,
+    ptype: u32,
+) -> Instruction {
+    let cmd = cmd_init_price_t {
+        ver_:   PC_VERSION,
+        cmd_:   command_t_e_cmd_init_price as i32,
+        expo_:  expo,
+        ptype_: ptype,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts:   price_accounts(program_id, funding_account, price_account),
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+/// Build a `resize_price_account` instruction that makes room for the time machine
+pub fn resize_price_account(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       bytes_of(&hdr(command_t_e_cmd_resize_price_account)).to_vec(),
+    }
+}
+
+/// Build a `del_price` instruction. `previous_price_account` is the price account that precedes
+/// `price_account` in the list of the product, if `price_account` isn't the first one.
+pub fn del_price(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    price_account: &Pubkey,
+    previous_price_account: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new(*product_account, false),
+        AccountMeta::new(*price_account, false),
+    ];
+    if let Some(previous_price_account) = previous_price_account {
+        accounts.push(AccountMeta::new(*previous_price_account, false));
+    }
+    accounts.push(AccountMeta::new_readonly(
+        permissions_address(program_id),
+        false,
+    ));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: bytes_of(&hdr(command_t_e_cmd_del_price)).to_vec(),
+    }
+}
+
+/// Build an `add_publisher` instruction
+pub fn add_publisher(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    publisher: &Pubkey,
+) -> Instruction {
+    let cmd = cmd_add_publisher_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_add_publisher as i32,
+        pub_: pc_pub_key_t {
+            k1_: publisher.to_bytes(),
+        },
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts:   price_accounts(program_id, funding_account, price_account),
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+/// Build a `del_publisher` instruction
+pub fn del_publisher(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    publisher: &Pubkey,
+) -> Instruction {
+    let cmd = cmd_del_publisher_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_del_publisher as i32,
+        pub_: pc_pub_key_t {
+            k1_: publisher.to_bytes(),
+        },
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts:   price_accounts(program_id, funding_account, price_account),
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+/// Build a `set_min_pub` instruction
+pub fn set_min_pub(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    min_pub: u8,
+) -> Instruction {
+    let cmd = cmd_set_min_pub_t {
+        ver_:     PC_VERSION,
+        cmd_:     command_t_e_cmd_set_min_pub as i32,
+        min_pub_: min_pub,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts:   price_accounts(program_id, funding_account, price_account),
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+/// Build a `set_max_latency` instruction, 0 restores the default latency
+pub fn set_max_latency(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    max_latency: u8,
+) -> Instruction {
+    let cmd = cmd_set_max_latency_t {
+        ver_:         PC_VERSION,
+        cmd_:         command_t_e_cmd_set_max_latency as i32,
+        max_latency_: max_latency,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts:   price_accounts(program_id, funding_account, price_account),
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+/// Build a `set_max_ci_divisor` instruction, 0 restores the default divisor
+pub fn set_max_ci_divisor(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+    max_ci_divisor: u16,
+) -> Instruction {
+    let cmd = cmd_set_max_ci_divisor_t {
+        ver_:            PC_VERSION,
+        cmd_:            command_t_e_cmd_set_max_ci_divisor as i32,
+        max_ci_divisor_: max_ci_divisor,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts:   price_accounts(program_id, funding_account, price_account),
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+/// Build an `upd_price` instruction publishing a price of `publisher_account`. `pub_index` is the
+/// index of the component of the publisher, as returned by `sdk::find_publisher_index`, which
+/// saves the program from looking the publisher up among all the components. `u32::MAX` means
+/// that the index is unknown.
+#[allow(clippy::too_many_arguments)]
+pub fn upd_price(
+    program_id: &Pubkey,
+    publisher_account: &Pubkey,
+    price_account: &Pubkey,
+    pub_index: u32,
+    status: u32,
+    price: i64,
+    conf: u64,
+    pub_slot: u64,
+) -> Instruction {
+    upd_price_with_command(
+        program_id,
+        publisher_account,
+        price_account,
+        command_t_e_cmd_upd_price,
+        pub_index,
+        status,
+        price,
+        conf,
+        pub_slot,
+    )
+}
+
+/// Build an `upd_price_no_fail_on_error` instruction, which succeeds even if the price can't be
+/// updated. `pub_index` is the same as in `upd_price`.
+#[allow(clippy::too_many_arguments)]
+pub fn upd_price_no_fail_on_error(
+    program_id: &Pubkey,
+    publisher_account: &Pubkey,
+    price_account: &Pubkey,
+    pub_index: u32,
+    status: u32,
+    price: i64,
+    conf: u64,
+    pub_slot: u64,
+) -> Instruction {
+    upd_price_with_command(
+        program_id,
+        publisher_account,
+        price_account,
+        command_t_e_cmd_upd_price_no_fail_on_error,
+        pub_index,
+        status,
+        price,
+        conf,
+        pub_slot,
+    )
+}
+
+/// Build an `agg_price` instruction, which only refreshes the aggregate price
+pub fn agg_price(
+    program_id: &Pubkey,
+    publisher_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Instruction {
+    upd_price_with_command(
+        program_id,
+        publisher_account,
+        price_account,
+        command_t_e_cmd_agg_price,
+        u32::MAX,
+        PC_STATUS_UNKNOWN,
+        0,
+        0,
+        0,
+    )
+}
+
+/// Build an `upd_price_batch` instruction with one update per price account. Each update is a
+/// price account with its `(pub_index, status, price, conf, pub_slot)`, `pub_index` being the same
+/// as in `upd_price`.
+pub fn upd_price_batch(
+    program_id: &Pubkey,
+    publisher_account: &Pubkey,
+    updates: &[(Pubkey, u32, u32, i64, u64, u64)],
+    no_fail_on_error: bool,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new(*publisher_account, true),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+    let mut entries = vec![];
+    for (price_account, pub_index, status, price, conf, pub_slot) in updates {
+        accounts.push(AccountMeta::new(*price_account, false));
+        entries.push(cmd_upd_price_batch_entry_t {
+            status_:    *status,
+            pub_index_: *pub_index,
+            price_:     *price,
+            conf_:      *conf,
+            pub_slot_:  *pub_slot,
+        });
+    }
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: OracleInstruction::UpdPriceBatch {
+            cmd: cmd_upd_price_batch_t {
+                ver_:              PC_VERSION,
+                cmd_:              command_t_e_cmd_upd_price_batch as i32,
+                no_fail_on_error_: no_fail_on_error.into(),
+                num_:              0,
+            },
+            entries,
+        }
+        .pack()?,
+    })
+}
+
+/// Build a `query_time_machine` instruction, the result is in the return data of the transaction
+pub fn query_time_machine(program_id: &Pubkey, price_account: &Pubkey, window: i64) -> Instruction {
+    let cmd = cmd_query_time_machine_t {
+        ver_:    PC_VERSION,
+        cmd_:    command_t_e_cmd_query_time_machine as i32,
+        window_: window,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new_readonly(*price_account, false),
+            AccountMeta::new_readonly(clock::id(), false),
+        ],
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+/// Build an `upd_permissions` instruction, signed by `upgrade_authority`, the upgrade authority of
+/// the program
+pub fn upd_permissions(
+    program_id: &Pubkey,
+    upgrade_authority: &Pubkey,
+    master_authority: &Pubkey,
+    data_curation_authority: &Pubkey,
+    security_authority: &Pubkey,
+) -> Instruction {
+    let cmd = cmd_upd_permissions_t {
+        ver_:                     PC_VERSION,
+        cmd_:                     command_t_e_cmd_upd_permissions as i32,
+        master_authority_:        pc_pub_key_t {
+            k1_: master_authority.to_bytes(),
+        },
+        data_curation_authority_: pc_pub_key_t {
+            k1_: data_curation_authority.to_bytes(),
+        },
+        security_authority_:      pc_pub_key_t {
+            k1_: security_authority.to_bytes(),
+        },
+    };
+    let (programdata_account, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*upgrade_authority, true),
+            AccountMeta::new_readonly(programdata_account, false),
+            AccountMeta::new(permissions_address(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+/// Build a `migrate_account` instruction that upgrades `account` to the layout of `PC_VERSION`
+pub fn migrate_account(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       bytes_of(&hdr(command_t_e_cmd_migrate_account)).to_vec(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn upd_price_with_command(
+    program_id: &Pubkey,
+    publisher_account: &Pubkey,
+    price_account: &Pubkey,
+    command: u32,
+    pub_index: u32,
+    status: u32,
+    price: i64,
+    conf: u64,
+    pub_slot: u64,
+) -> Instruction {
+    let cmd = cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command as i32,
+        status_:    status,
+        pub_index_: pub_index,
+        price_:     price,
+        conf_:      conf,
+        pub_slot_:  pub_slot,
+    };
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*publisher_account, true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(clock::id(), false),
+        ],
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+fn hdr(command: u32) -> cmd_hdr_t {
+    cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: command as i32,
+    }
+}
+
+/// Build an `init_mapping_pda` instruction, the mapping account is the PDA returned by
+/// `get_mapping_address(program_id, None)`
+pub fn init_mapping_pda(program_id: &Pubkey, funding_account: &Pubkey) -> Instruction {
+    let (mapping_account, _) = get_mapping_address(program_id, None);
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(mapping_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       bytes_of(&hdr(command_t_e_cmd_init_mapping_pda)).to_vec(),
+    }
+}
+
+/// Build an `add_mapping_pda` instruction that links the PDA returned by
+/// `get_mapping_address(program_id, Some(cur_mapping_account))` after the full
+/// `cur_mapping_account`
+pub fn add_mapping_pda(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    cur_mapping_account: &Pubkey,
+) -> Instruction {
+    let (next_mapping_account, _) = get_mapping_address(program_id, Some(cur_mapping_account));
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*cur_mapping_account, false),
+            AccountMeta::new(next_mapping_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       bytes_of(&hdr(command_t_e_cmd_add_mapping_pda)).to_vec(),
+    }
+}
+
+/// Build an `add_product_pda` instruction, the product account is the PDA returned by
+/// `get_product_address(program_id, seed)`. Fails if the seed is empty or longer than
+/// `MAX_SEED_LEN` bytes.
+pub fn add_product_pda(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    mapping_account: &Pubkey,
+    seed: &[u8],
+) -> Result<Instruction, ProgramError> {
+    if seed.is_empty() || seed.len() > MAX_SEED_LEN {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (product_account, _) = get_product_address(program_id, seed);
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*mapping_account, false),
+            AccountMeta::new(product_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       OracleInstruction::AddProductPda {
+            hdr:  hdr(command_t_e_cmd_add_product_pda),
+            seed: seed.to_vec(),
+        }
+        .pack()?,
+    })
+}
+
+/// Build an `add_price_pda` instruction, the price account is the PDA returned by
+/// `get_price_address(program_id, product_account, ptype)`
+pub fn add_price_pda(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+    expo: i32,
+    ptype: u32,
+) -> Instruction {
+    let cmd = cmd_add_price_t {
+        ver_:   PC_VERSION,
+        cmd_:   command_t_e_cmd_add_price_pda as i32,
+        expo_:  expo,
+        ptype_: ptype,
+    };
+    let (price_account, _) = get_price_address(program_id, product_account, ptype);
+    Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(*funding_account, true),
+            AccountMeta::new(*product_account, false),
+            AccountMeta::new(price_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(permissions_address(program_id), false),
+        ],
+        data:       bytes_of(&cmd).to_vec(),
+    }
+}
+
+/// The accounts of the instructions that only modify a product account
+fn product_accounts(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    product_account: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new(*product_account, false),
+        AccountMeta::new_readonly(permissions_address(program_id), false),
+    ]
+}
+
+/// The accounts of the instructions that only modify a price account
+fn price_accounts(
+    program_id: &Pubkey,
+    funding_account: &Pubkey,
+    price_account: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new(*price_account, false),
+        AccountMeta::new_readonly(permissions_address(program_id), false),
+    ]
+}
+
+/// Insert the system program before the permissions account, the last account of `instruction`
+fn with_system_program(mut instruction: Instruction) -> Instruction {
+    let permissions_index = instruction.accounts.len() - 1;
+    instruction.accounts.insert(
+        permissions_index,
+        AccountMeta::new_readonly(system_program::id(), false),
+    );
+    instruction
+}
+
+fn permissions_address(program_id: &Pubkey) -> Pubkey {
+    get_permissions_address(program_id).0
+}
@@ -0,0 +1,407 @@
+//! Typed representation of the instructions of the oracle program.
+//! `OracleInstruction::unpack` parses the instruction data once, the processor, the logs and the
+//! handlers then work on the parsed arguments. `OracleInstruction::pack` does the reverse to let
+//! clients build instructions, the builders of the `client` feature also provide the accounts.
+use std::mem::size_of;
+
+use bytemuck::{
+    bytes_of,
+    try_pod_read_unaligned,
+    Pod,
+};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::MAX_SEED_LEN;
+
+use crate::c_oracle_header::{
+    cmd_add_price_t,
+    cmd_add_product_pda_t,
+    cmd_add_publisher_t,
+    cmd_del_product_attr_t,
+    cmd_del_publisher_t,
+    cmd_hdr_t,
+    cmd_init_price_t,
+    cmd_query_time_machine_t,
+    cmd_resize_product_account_t,
+    cmd_set_max_ci_divisor_t,
+    cmd_set_max_latency_t,
+    cmd_set_min_pub_t,
+    cmd_set_product_attr_t,
+    cmd_upd_permissions_t,
+    cmd_upd_price_batch_entry_t,
+    cmd_upd_price_batch_t,
+    cmd_upd_price_t,
+    cmd_upd_product_t,
+    command_t_e_cmd_add_mapping,
+    command_t_e_cmd_add_mapping_pda,
+    command_t_e_cmd_add_price,
+    command_t_e_cmd_add_price_pda,
+    command_t_e_cmd_add_product,
+    command_t_e_cmd_add_product_pda,
+    command_t_e_cmd_add_publisher,
+    command_t_e_cmd_agg_price,
+    command_t_e_cmd_del_price,
+    command_t_e_cmd_del_product,
+    command_t_e_cmd_del_product_attr,
+    command_t_e_cmd_del_publisher,
+    command_t_e_cmd_init_mapping,
+    command_t_e_cmd_init_mapping_pda,
+    command_t_e_cmd_init_price,
+    command_t_e_cmd_migrate_account,
+    command_t_e_cmd_query_time_machine,
+    command_t_e_cmd_resize_price_account,
+    command_t_e_cmd_resize_product_account,
+    command_t_e_cmd_set_max_ci_divisor,
+    command_t_e_cmd_set_max_latency,
+    command_t_e_cmd_set_min_pub,
+    command_t_e_cmd_set_product_attr,
+    command_t_e_cmd_upd_permissions,
+    command_t_e_cmd_upd_price,
+    command_t_e_cmd_upd_price_batch,
+    command_t_e_cmd_upd_price_no_fail_on_error,
+    command_t_e_cmd_upd_product,
+};
+use crate::error::OracleError;
+use crate::utils::{
+    pyth_assert,
+    read_pc_str_t,
+    try_convert,
+    write_pc_str_t,
+};
+
+#[cfg(any(test, feature = "client"))]
+mod builders;
+#[cfg(any(test, feature = "client"))]
+pub use builders::*;
+
+/// A key-value pair of product metadata, without the length prefixes of `pc_str_t`
+pub type ProductAttribute = (Vec<u8>, Vec<u8>);
+
+/// An instruction of the oracle program with its parsed arguments.
+/// Fixed size arguments are kept as the command structs of `oracle.h`, the variable length data
+/// that follows some of them is parsed into owned values.
+#[derive(Clone)]
+pub enum OracleInstruction {
+    InitMapping(cmd_hdr_t),
+    AddMapping(cmd_hdr_t),
+    AddProduct(cmd_hdr_t),
+    UpdProduct {
+        hdr:        cmd_hdr_t,
+        attributes: Vec<ProductAttribute>,
+    },
+    AddPrice(cmd_add_price_t),
+    AddPublisher(cmd_add_publisher_t),
+    DelPublisher(cmd_del_publisher_t),
+    UpdPrice(cmd_upd_price_t),
+    AggPrice(cmd_upd_price_t),
+    InitPrice(cmd_init_price_t),
+    SetMinPub(cmd_set_min_pub_t),
+    UpdPriceNoFailOnError(cmd_upd_price_t),
+    ResizePriceAccount(cmd_hdr_t),
+    QueryTimeMachine(cmd_query_time_machine_t),
+    SetMaxLatency(cmd_set_max_latency_t),
+    SetMaxCiDivisor(cmd_set_max_ci_divisor_t),
+    DelPrice(cmd_hdr_t),
+    DelProduct(cmd_hdr_t),
+    UpdPriceBatch {
+        cmd:     cmd_upd_price_batch_t,
+        entries: Vec<cmd_upd_price_batch_entry_t>,
+    },
+    SetProductAttr {
+        hdr:   cmd_hdr_t,
+        key:   Vec<u8>,
+        value: Vec<u8>,
+    },
+    DelProductAttr {
+        hdr: cmd_hdr_t,
+        key: Vec<u8>,
+    },
+    ResizeProductAccount(cmd_resize_product_account_t),
+    MigrateAccount(cmd_hdr_t),
+    UpdPermissions(cmd_upd_permissions_t),
+    InitMappingPda(cmd_hdr_t),
+    AddMappingPda(cmd_hdr_t),
+    AddProductPda {
+        hdr:  cmd_hdr_t,
+        seed: Vec<u8>,
+    },
+    AddPricePda(cmd_add_price_t),
+}
+
+impl OracleInstruction {
+    /// Parse `instruction_data`. The command struct must be followed by exactly the data the
+    /// command expects, except for the commands that historically ignored trailing bytes.
+    pub fn unpack(instruction_data: &[u8]) -> Result<Self, ProgramError> {
+        let hdr = read::<cmd_hdr_t>(instruction_data)?;
+        let command: u32 = hdr
+            .cmd_
+            .try_into()
+            .map_err(|_| OracleError::IntegerCastingError)?;
+
+        match command {
+            command_t_e_cmd_init_mapping => Ok(Self::InitMapping(hdr)),
+            command_t_e_cmd_add_mapping => Ok(Self::AddMapping(hdr)),
+            command_t_e_cmd_add_product => Ok(Self::AddProduct(hdr)),
+            command_t_e_cmd_upd_product => {
+                pyth_assert(
+                    instruction_data.len() >= size_of::<cmd_upd_product_t>(),
+                    ProgramError::InvalidInstructionData,
+                )?;
+                // The data must be a list of key-value pairs, both of which are pc_str_t
+                let data = &instruction_data[size_of::<cmd_upd_product_t>()..];
+                let mut attributes = vec![];
+                let mut idx = 0;
+                while idx < data.len() {
+                    let key = read_pc_str_t(&data[idx..])?;
+                    idx += key.len();
+                    let value = read_pc_str_t(&data[idx..])?;
+                    idx += value.len();
+                    attributes.push((key[1..].to_vec(), value[1..].to_vec()));
+                }
+                Ok(Self::UpdProduct { hdr, attributes })
+            }
+            command_t_e_cmd_add_price => Ok(Self::AddPrice(read(instruction_data)?)),
+            command_t_e_cmd_add_publisher => Ok(Self::AddPublisher(read_exact(instruction_data)?)),
+            command_t_e_cmd_del_publisher => Ok(Self::DelPublisher(read_exact(instruction_data)?)),
+            command_t_e_cmd_upd_price => Ok(Self::UpdPrice(read(instruction_data)?)),
+            command_t_e_cmd_agg_price => Ok(Self::AggPrice(read(instruction_data)?)),
+            command_t_e_cmd_init_price => Ok(Self::InitPrice(read(instruction_data)?)),
+            command_t_e_cmd_set_min_pub => Ok(Self::SetMinPub(read_exact(instruction_data)?)),
+            command_t_e_cmd_upd_price_no_fail_on_error => {
+                Ok(Self::UpdPriceNoFailOnError(read(instruction_data)?))
+            }
+            command_t_e_cmd_resize_price_account => Ok(Self::ResizePriceAccount(hdr)),
+            command_t_e_cmd_query_time_machine => {
+                Ok(Self::QueryTimeMachine(read(instruction_data)?))
+            }
+            command_t_e_cmd_set_max_latency => {
+                Ok(Self::SetMaxLatency(read_exact(instruction_data)?))
+            }
+            command_t_e_cmd_set_max_ci_divisor => {
+                Ok(Self::SetMaxCiDivisor(read_exact(instruction_data)?))
+            }
+            command_t_e_cmd_del_price => Ok(Self::DelPrice(hdr)),
+            command_t_e_cmd_del_product => Ok(Self::DelProduct(hdr)),
+            command_t_e_cmd_upd_price_batch => {
+                let cmd = read::<cmd_upd_price_batch_t>(instruction_data)?;
+                let num_entries: usize = try_convert(cmd.num_)?;
+                pyth_assert(
+                    instruction_data.len()
+                        == size_of::<cmd_upd_price_batch_t>()
+                            + num_entries * size_of::<cmd_upd_price_batch_entry_t>(),
+                    ProgramError::InvalidArgument,
+                )?;
+                let entries = instruction_data[size_of::<cmd_upd_price_batch_t>()..]
+                    .chunks_exact(size_of::<cmd_upd_price_batch_entry_t>())
+                    .map(read::<cmd_upd_price_batch_entry_t>)
+                    .collect::<Result<_, _>>()?;
+                Ok(Self::UpdPriceBatch { cmd, entries })
+            }
+            command_t_e_cmd_set_product_attr => {
+                pyth_assert(
+                    instruction_data.len() >= size_of::<cmd_set_product_attr_t>(),
+                    ProgramError::InvalidInstructionData,
+                )?;
+                // The data must be exactly one key-value pair
+                let data = &instruction_data[size_of::<cmd_set_product_attr_t>()..];
+                let key = read_pc_str_t(data)?;
+                let value = read_pc_str_t(&data[key.len()..])?;
+                pyth_assert(
+                    key.len() + value.len() == data.len(),
+                    ProgramError::InvalidArgument,
+                )?;
+                Ok(Self::SetProductAttr {
+                    hdr,
+                    key: key[1..].to_vec(),
+                    value: value[1..].to_vec(),
+                })
+            }
+            command_t_e_cmd_del_product_attr => {
+                pyth_assert(
+                    instruction_data.len() >= size_of::<cmd_del_product_attr_t>(),
+                    ProgramError::InvalidInstructionData,
+                )?;
+                // The data must be exactly one key
+                let data = &instruction_data[size_of::<cmd_del_product_attr_t>()..];
+                let key = read_pc_str_t(data)?;
+                pyth_assert(key.len() == data.len(), ProgramError::InvalidArgument)?;
+                Ok(Self::DelProductAttr {
+                    hdr,
+                    key: key[1..].to_vec(),
+                })
+            }
+            command_t_e_cmd_resize_product_account => {
+                Ok(Self::ResizeProductAccount(read(instruction_data)?))
+            }
+            command_t_e_cmd_migrate_account => Ok(Self::MigrateAccount(hdr)),
+            command_t_e_cmd_upd_permissions => {
+                Ok(Self::UpdPermissions(read_exact(instruction_data)?))
+            }
+            command_t_e_cmd_init_mapping_pda => Ok(Self::InitMappingPda(hdr)),
+            command_t_e_cmd_add_mapping_pda => Ok(Self::AddMappingPda(hdr)),
+            command_t_e_cmd_add_product_pda => {
+                // The rest of the data is the seed of the product account
+                let seed = &instruction_data[size_of::<cmd_add_product_pda_t>()..];
+                pyth_assert(
+                    !seed.is_empty() && seed.len() <= MAX_SEED_LEN,
+                    ProgramError::InvalidArgument,
+                )?;
+                Ok(Self::AddProductPda {
+                    hdr,
+                    seed: seed.to_vec(),
+                })
+            }
+            command_t_e_cmd_add_price_pda => Ok(Self::AddPricePda(read_exact(instruction_data)?)),
+            _ => Err(OracleError::UnrecognizedInstruction.into()),
+        }
+    }
+
+    /// Serialize the instruction. The command of the header is the one of the variant, whatever
+    /// the `cmd_` field of the arguments is.
+    pub fn pack(&self) -> Result<Vec<u8>, ProgramError> {
+        let mut instruction_data = match self {
+            Self::InitMapping(hdr)
+            | Self::AddMapping(hdr)
+            | Self::AddProduct(hdr)
+            | Self::ResizePriceAccount(hdr)
+            | Self::DelPrice(hdr)
+            | Self::DelProduct(hdr)
+            | Self::MigrateAccount(hdr)
+            | Self::InitMappingPda(hdr)
+            | Self::AddMappingPda(hdr) => bytes_of(hdr).to_vec(),
+            Self::UpdProduct { hdr, attributes } => {
+                let mut instruction_data = bytes_of(hdr).to_vec();
+                for (key, value) in attributes {
+                    write_pc_str_t(&mut instruction_data, key)?;
+                    write_pc_str_t(&mut instruction_data, value)?;
+                }
+                instruction_data
+            }
+            Self::AddPrice(cmd) => bytes_of(cmd).to_vec(),
+            Self::AddPublisher(cmd) => bytes_of(cmd).to_vec(),
+            Self::DelPublisher(cmd) => bytes_of(cmd).to_vec(),
+            Self::UpdPrice(cmd) | Self::AggPrice(cmd) | Self::UpdPriceNoFailOnError(cmd) => {
+                bytes_of(cmd).to_vec()
+            }
+            Self::InitPrice(cmd) => bytes_of(cmd).to_vec(),
+            Self::SetMinPub(cmd) => bytes_of(cmd).to_vec(),
+            Self::QueryTimeMachine(cmd) => bytes_of(cmd).to_vec(),
+            Self::SetMaxLatency(cmd) => bytes_of(cmd).to_vec(),
+            Self::SetMaxCiDivisor(cmd) => bytes_of(cmd).to_vec(),
+            Self::UpdPriceBatch { cmd, entries } => {
+                let mut cmd = *cmd;
+                cmd.num_ = try_convert(entries.len())?;
+                let mut instruction_data = bytes_of(&cmd).to_vec();
+                for entry in entries {
+                    instruction_data.extend_from_slice(bytes_of(entry));
+                }
+                instruction_data
+            }
+            Self::SetProductAttr { hdr, key, value } => {
+                let mut instruction_data = bytes_of(hdr).to_vec();
+                write_pc_str_t(&mut instruction_data, key)?;
+                write_pc_str_t(&mut instruction_data, value)?;
+                instruction_data
+            }
+            Self::DelProductAttr { hdr, key } => {
+                let mut instruction_data = bytes_of(hdr).to_vec();
+                write_pc_str_t(&mut instruction_data, key)?;
+                instruction_data
+            }
+            Self::ResizeProductAccount(cmd) => bytes_of(cmd).to_vec(),
+            Self::UpdPermissions(cmd) => bytes_of(cmd).to_vec(),
+            Self::AddProductPda { hdr, seed } => {
+                let mut instruction_data = bytes_of(hdr).to_vec();
+                instruction_data.extend_from_slice(seed);
+                instruction_data
+            }
+            Self::AddPricePda(cmd) => bytes_of(cmd).to_vec(),
+        };
+
+        let command: i32 = try_convert(self.command())?;
+        instruction_data[4..8].copy_from_slice(&command.to_le_bytes());
+        Ok(instruction_data)
+    }
+
+    /// The `command_t` of the instruction
+    pub fn command(&self) -> u32 {
+        match self {
+            Self::InitMapping(_) => command_t_e_cmd_init_mapping,
+            Self::AddMapping(_) => command_t_e_cmd_add_mapping,
+            Self::AddProduct(_) => command_t_e_cmd_add_product,
+            Self::UpdProduct { .. } => command_t_e_cmd_upd_product,
+            Self::AddPrice(_) => command_t_e_cmd_add_price,
+            Self::AddPublisher(_) => command_t_e_cmd_add_publisher,
+            Self::DelPublisher(_) => command_t_e_cmd_del_publisher,
+            Self::UpdPrice(_) => command_t_e_cmd_upd_price,
+            Self::AggPrice(_) => command_t_e_cmd_agg_price,
+            Self::InitPrice(_) => command_t_e_cmd_init_price,
+            Self::SetMinPub(_) => command_t_e_cmd_set_min_pub,
+            Self::UpdPriceNoFailOnError(_) => command_t_e_cmd_upd_price_no_fail_on_error,
+            Self::ResizePriceAccount(_) => command_t_e_cmd_resize_price_account,
+            Self::QueryTimeMachine(_) => command_t_e_cmd_query_time_machine,
+            Self::SetMaxLatency(_) => command_t_e_cmd_set_max_latency,
+            Self::SetMaxCiDivisor(_) => command_t_e_cmd_set_max_ci_divisor,
+            Self::DelPrice(_) => command_t_e_cmd_del_price,
+            Self::DelProduct(_) => command_t_e_cmd_del_product,
+            Self::UpdPriceBatch { .. } => command_t_e_cmd_upd_price_batch,
+            Self::SetProductAttr { .. } => command_t_e_cmd_set_product_attr,
+            Self::DelProductAttr { .. } => command_t_e_cmd_del_product_attr,
+            Self::ResizeProductAccount(_) => command_t_e_cmd_resize_product_account,
+            Self::MigrateAccount(_) => command_t_e_cmd_migrate_account,
+            Self::UpdPermissions(_) => command_t_e_cmd_upd_permissions,
+            Self::InitMappingPda(_) => command_t_e_cmd_init_mapping_pda,
+            Self::AddMappingPda(_) => command_t_e_cmd_add_mapping_pda,
+            Self::AddProductPda { .. } => command_t_e_cmd_add_product_pda,
+            Self::AddPricePda(_) => command_t_e_cmd_add_price_pda,
+        }
+    }
+
+    /// The version of the instruction
+    pub fn version(&self) -> u32 {
+        match self {
+            Self::InitMapping(hdr)
+            | Self::AddMapping(hdr)
+            | Self::AddProduct(hdr)
+            | Self::UpdProduct { hdr, .. }
+            | Self::ResizePriceAccount(hdr)
+            | Self::DelPrice(hdr)
+            | Self::DelProduct(hdr)
+            | Self::SetProductAttr { hdr, .. }
+            | Self::DelProductAttr { hdr, .. }
+            | Self::MigrateAccount(hdr)
+            | Self::InitMappingPda(hdr)
+            | Self::AddMappingPda(hdr)
+            | Self::AddProductPda { hdr, .. } => hdr.ver_,
+            Self::AddPrice(cmd) => cmd.ver_,
+            Self::AddPublisher(cmd) => cmd.ver_,
+            Self::DelPublisher(cmd) => cmd.ver_,
+            Self::UpdPrice(cmd) | Self::AggPrice(cmd) | Self::UpdPriceNoFailOnError(cmd) => {
+                cmd.ver_
+            }
+            Self::InitPrice(cmd) => cmd.ver_,
+            Self::SetMinPub(cmd) => cmd.ver_,
+            Self::QueryTimeMachine(cmd) => cmd.ver_,
+            Self::SetMaxLatency(cmd) => cmd.ver_,
+            Self::SetMaxCiDivisor(cmd) => cmd.ver_,
+            Self::UpdPriceBatch { cmd, .. } => cmd.ver_,
+            Self::ResizeProductAccount(cmd) => cmd.ver_,
+            Self::UpdPermissions(cmd) => cmd.ver_,
+            Self::AddPricePda(cmd) => cmd.ver_,
+        }
+    }
+}
+
+/// Read a `T` from the beginning of `data`, which doesn't need to be aligned
+fn read<T: Pod>(data: &[u8]) -> Result<T, ProgramError> {
+    try_pod_read_unaligned(
+        data.get(0..size_of::<T>())
+            .ok_or(ProgramError::InvalidArgument)?,
+    )
+    .map_err(|_| ProgramError::InvalidArgument)
+}
+
+/// Read a `T` from `data`, which must have the exact size of `T`
+fn read_exact<T: Pod>(data: &[u8]) -> Result<T, ProgramError> {
+    pyth_assert(data.len() == size_of::<T>(), ProgramError::InvalidArgument)?;
+    read(data)
+}
@@ -4,12 +4,16 @@
 // Allow using the solana_program::entrypoint::deserialize function
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 
-mod c_oracle_header;
+mod aggregate;
+pub mod c_oracle_header;
 mod deserialize;
 mod error;
+pub mod events;
+pub mod instruction;
 mod log;
 mod processor;
 mod rust_oracle;
+pub mod sdk;
 mod time_machine_types;
 mod utils;
 
@@ -18,6 +22,7 @@ mod tests;
 
 use crate::c_oracle_header::SUCCESSFULLY_UPDATED_AGGREGATE;
 use crate::error::OracleError;
+use crate::instruction::OracleInstruction;
 
 use crate::log::{
     post_log,
@@ -36,8 +41,10 @@ use solana_program::{
 //As we migrate from C to Rust, our Rust code needs to be able to interact with C
 //build-bpf.sh is set up to compile the C code into a two archive files
 //contained in `./program/c/target/`
-// - `libcpyth-bpf.a` contains the bpf version for production code
-// - `libcpyth-native.a` contains the systems architecture version for tests
+// - `libcpyth-bpf.a` contains the bpf version, the program no longer links it since the aggregation
+//   has been ported to Rust in `aggregate.rs`
+// - `libcpyth-native.a` contains the systems architecture version for tests, the tests check that
+//   the Rust aggregation matches the C one when the `test-c-aggregate` feature is enabled
 
 //We also generate bindings for the types and constants in oracle.h (as well as other things
 //included in bindings.h), these bindings can be accessed through c_oracle_header.rs
@@ -51,11 +58,16 @@ use solana_program::{
 pub extern "C" fn entrypoint(input: *mut u8) -> u64 {
     let (program_id, accounts, instruction_data) = unsafe { deserialize(input) };
 
-    if let Err(error) = pre_log(&accounts, instruction_data) {
+    let instruction = match OracleInstruction::unpack(instruction_data) {
+        Err(error) => return error.into(),
+        Ok(instruction) => instruction,
+    };
+
+    if let Err(error) = pre_log(&accounts, &instruction) {
         return error.into();
     }
 
-    let c_ret_val = match process_instruction(program_id, &accounts, instruction_data) {
+    let c_ret_val = match process_instruction(program_id, &accounts, &instruction) {
         Err(error) => error.into(),
         Ok(success_status) => success_status,
     };
@@ -1,9 +1,6 @@
 use crate::c_oracle_header::*;
-use crate::deserialize::{
-    load,
-    load_account_as,
-};
-use crate::error::OracleError;
+use crate::deserialize::load_account_as;
+use crate::instruction::OracleInstruction;
 use solana_program::account_info::AccountInfo;
 use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
@@ -11,18 +8,11 @@ use solana_program::msg;
 use solana_program::program_error::ProgramError;
 use solana_program::sysvar::Sysvar;
 
-pub fn pre_log(accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+pub fn pre_log(accounts: &[AccountInfo], instruction: &OracleInstruction) -> ProgramResult {
     msg!("Pyth oracle contract");
 
-    let instruction_header: &cmd_hdr = load::<cmd_hdr>(instruction_data)?;
-    let instruction_id: u32 = instruction_header
-        .cmd_
-        .try_into()
-        .map_err(|_| OracleError::IntegerCastingError)?;
-
-    match instruction_id {
-        command_t_e_cmd_upd_price | command_t_e_cmd_agg_price => {
-            let instruction: &cmd_upd_price = load::<cmd_upd_price>(instruction_data)?;
+    match instruction {
+        OracleInstruction::UpdPrice(instruction) | OracleInstruction::AggPrice(instruction) => {
             // Account 1 is price_info in this instruction
             let price_account = load_account_as::<pc_price_t>(&accounts[1])?;
             msg!(
@@ -39,8 +29,7 @@ pub fn pre_log(accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResu
                 Clock::get()?.unix_timestamp
             );
         }
-        command_t_e_cmd_upd_price_no_fail_on_error => {
-            let instruction: &cmd_upd_price = load::<cmd_upd_price>(instruction_data)?;
+        OracleInstruction::UpdPriceNoFailOnError(instruction) => {
             // Account 1 is price_info in this instruction
             let price_account = load_account_as::<pc_price_t>(&accounts[1])?;
             msg!(
@@ -57,42 +46,119 @@ pub fn pre_log(accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResu
                 Clock::get()?.unix_timestamp
             );
         }
-        command_t_e_cmd_add_mapping => {
+        OracleInstruction::UpdPriceBatch {
+            cmd: instruction, ..
+        } => {
+            msg!(
+                "UpdatePriceBatch: publisher={:}, num_prices={:}, no_fail_on_error={:}, solana_time={:}",
+                accounts
+                    .first()
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?
+                    .key,
+                instruction.num_,
+                instruction.no_fail_on_error_,
+                Clock::get()?.unix_timestamp
+            );
+        }
+        OracleInstruction::AddMapping(_) => {
             msg!("AddMapping");
         }
-        command_t_e_cmd_add_price => {
+        OracleInstruction::AddPrice(_) => {
             msg!("AddPrice");
         }
-        command_t_e_cmd_add_product => {
+        OracleInstruction::AddProduct(_) => {
             msg!("AddProduct")
         }
-        command_t_e_cmd_add_publisher => {
+        OracleInstruction::AddPublisher(_) => {
             msg!("AddPublisher")
         }
-        command_t_e_cmd_del_publisher => {
+        OracleInstruction::DelPublisher(_) => {
             msg!("DeletePublisher")
         }
-        command_t_e_cmd_init_price => {
+        OracleInstruction::DelProduct(_) => {
+            msg!(
+                "DeleteProduct: {}",
+                accounts
+                    .get(2)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?
+                    .key
+            );
+        }
+        OracleInstruction::DelPrice(_) => {
+            msg!(
+                "DeletePrice: {}",
+                accounts
+                    .get(2)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?
+                    .key
+            );
+        }
+        OracleInstruction::InitPrice(_) => {
             msg!("InitializePrice")
         }
-        command_t_e_cmd_init_mapping => {
+        OracleInstruction::InitMapping(_) => {
             msg!("InitializeMapping");
         }
 
-        command_t_e_cmd_set_min_pub => {
+        OracleInstruction::SetMinPub(_) => {
             msg!("SetMinimumPublishers");
         }
-        command_t_e_cmd_upd_product => {
+        OracleInstruction::SetMaxLatency(_) => {
+            msg!("SetMaximumLatency");
+        }
+        OracleInstruction::SetMaxCiDivisor(_) => {
+            msg!("SetMaximumConfidenceDivisor");
+        }
+        OracleInstruction::UpdProduct { .. } => {
             msg!("UpdateProduct");
         }
+        OracleInstruction::SetProductAttr { .. } => {
+            msg!("SetProductAttribute");
+        }
+        OracleInstruction::DelProductAttr { .. } => {
+            msg!("DeleteProductAttribute");
+        }
 
-        command_t_e_cmd_resize_price_account => {
+        OracleInstruction::ResizePriceAccount(_) => {
             //accounts[1] is the updated account
             msg!("ResizePriceAccount: {}", accounts[1].key);
         }
-        _ => {
-            msg!("UnrecognizedInstruction");
-            return Err(OracleError::UnrecognizedInstruction.into());
+        OracleInstruction::ResizeProductAccount(_) => {
+            //accounts[1] is the updated account
+            msg!(
+                "ResizeProductAccount: {}",
+                accounts
+                    .get(1)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?
+                    .key
+            );
+        }
+        OracleInstruction::QueryTimeMachine(_) => {
+            msg!("QueryTimeMachine");
+        }
+        OracleInstruction::UpdPermissions(_) => {
+            msg!("UpdatePermissions");
+        }
+        OracleInstruction::InitMappingPda(_) => {
+            msg!("InitializeMappingPda");
+        }
+        OracleInstruction::AddMappingPda(_) => {
+            msg!("AddMappingPda");
+        }
+        OracleInstruction::AddProductPda { .. } => {
+            msg!("AddProductPda");
+        }
+        OracleInstruction::AddPricePda(_) => {
+            msg!("AddPricePda");
+        }
+        OracleInstruction::MigrateAccount(_) => {
+            msg!(
+                "MigrateAccount: {}",
+                accounts
+                    .get(1)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?
+                    .key
+            );
         }
     }
     Ok(())
@@ -1,82 +1,114 @@
-use solana_program::program_error::ProgramError;
+use solana_program::msg;
 use solana_program::pubkey::Pubkey;
 use solana_program::sysvar::slot_history::AccountInfo;
 
 use crate::c_oracle_header::{
-    cmd_hdr,
-    command_t_e_cmd_add_mapping,
-    command_t_e_cmd_add_price,
-    command_t_e_cmd_add_product,
-    command_t_e_cmd_add_publisher,
-    command_t_e_cmd_agg_price,
-    command_t_e_cmd_del_publisher,
-    command_t_e_cmd_init_mapping,
-    command_t_e_cmd_init_price,
-    command_t_e_cmd_resize_price_account,
-    command_t_e_cmd_set_min_pub,
-    command_t_e_cmd_upd_price,
-    command_t_e_cmd_upd_price_no_fail_on_error,
-    command_t_e_cmd_upd_product,
+    PC_MIN_VERSION,
     PC_VERSION,
 };
-use crate::deserialize::load;
 use crate::error::{
     OracleError,
     OracleResult,
 };
+use crate::instruction::OracleInstruction;
 use crate::rust_oracle::{
     add_mapping,
+    add_mapping_pda,
     add_price,
+    add_price_pda,
     add_product,
+    add_product_pda,
     add_publisher,
+    del_price,
+    del_product,
+    del_product_attr,
     del_publisher,
     init_mapping,
+    init_mapping_pda,
     init_price,
+    migrate_account,
+    query_time_machine,
     resize_price_account,
+    resize_product_account,
+    set_max_ci_divisor,
+    set_max_latency,
     set_min_pub,
+    set_product_attr,
+    upd_permissions,
     upd_price,
+    upd_price_batch,
     upd_price_no_fail_on_error,
     upd_product,
 };
+use crate::utils::is_supported_version;
 
 ///dispatch to the right instruction in the oracle
 pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    instruction: &OracleInstruction,
 ) -> OracleResult {
-    let cmd_data = load::<cmd_hdr>(instruction_data)?;
-
-    if cmd_data.ver_ != PC_VERSION {
-        //FIXME: I am not sure what's best to do here (this is copied from C)
-        // it seems to me like we should not break when version numbers change
-        //instead we should log a message that asks users to call update_version
-        return Err(ProgramError::InvalidArgument);
+    // Clients can keep sending older supported versions after PC_VERSION is bumped, the
+    // handlers always work with the accounts in the layout of PC_VERSION
+    if !is_supported_version(instruction.version()) {
+        msg!(
+            "Unsupported instruction version {}, supported versions are {} to {}",
+            instruction.version(),
+            PC_MIN_VERSION,
+            PC_VERSION
+        );
+        return Err(OracleError::InvalidInstructionVersion.into());
     }
 
-    match cmd_data
-        .cmd_
-        .try_into()
-        .map_err(|_| OracleError::IntegerCastingError)?
-    {
-        command_t_e_cmd_upd_price | command_t_e_cmd_agg_price => {
-            upd_price(program_id, accounts, instruction_data)
+    match instruction {
+        OracleInstruction::UpdPrice(cmd_args) | OracleInstruction::AggPrice(cmd_args) => {
+            upd_price(program_id, accounts, cmd_args)
+        }
+        OracleInstruction::UpdPriceNoFailOnError(cmd_args) => {
+            upd_price_no_fail_on_error(program_id, accounts, cmd_args)
+        }
+        OracleInstruction::UpdPriceBatch { cmd, entries } => {
+            upd_price_batch(program_id, accounts, cmd, entries)
+        }
+        OracleInstruction::ResizePriceAccount(hdr) => {
+            resize_price_account(program_id, accounts, hdr)
+        }
+        OracleInstruction::ResizeProductAccount(cmd_args) => {
+            resize_product_account(program_id, accounts, cmd_args)
+        }
+        OracleInstruction::AddPrice(cmd_args) => add_price(program_id, accounts, cmd_args),
+        OracleInstruction::InitMapping(hdr) => init_mapping(program_id, accounts, hdr),
+        OracleInstruction::InitPrice(cmd_args) => init_price(program_id, accounts, cmd_args),
+        OracleInstruction::AddMapping(hdr) => add_mapping(program_id, accounts, hdr),
+        OracleInstruction::AddPublisher(cmd_args) => add_publisher(program_id, accounts, cmd_args),
+        OracleInstruction::DelPublisher(cmd_args) => del_publisher(program_id, accounts, cmd_args),
+        OracleInstruction::DelPrice(hdr) => del_price(program_id, accounts, hdr),
+        OracleInstruction::DelProduct(hdr) => del_product(program_id, accounts, hdr),
+        OracleInstruction::AddProduct(hdr) => add_product(program_id, accounts, hdr),
+        OracleInstruction::UpdProduct { hdr, attributes } => {
+            upd_product(program_id, accounts, hdr, attributes)
+        }
+        OracleInstruction::SetProductAttr { hdr, key, value } => {
+            set_product_attr(program_id, accounts, hdr, key, value)
+        }
+        OracleInstruction::DelProductAttr { hdr, key } => {
+            del_product_attr(program_id, accounts, hdr, key)
+        }
+        OracleInstruction::SetMinPub(cmd) => set_min_pub(program_id, accounts, cmd),
+        OracleInstruction::QueryTimeMachine(cmd_args) => {
+            query_time_machine(program_id, accounts, cmd_args)
         }
-        command_t_e_cmd_upd_price_no_fail_on_error => {
-            upd_price_no_fail_on_error(program_id, accounts, instruction_data)
+        OracleInstruction::SetMaxLatency(cmd) => set_max_latency(program_id, accounts, cmd),
+        OracleInstruction::SetMaxCiDivisor(cmd) => set_max_ci_divisor(program_id, accounts, cmd),
+        OracleInstruction::MigrateAccount(hdr) => migrate_account(program_id, accounts, hdr),
+        OracleInstruction::UpdPermissions(cmd_args) => {
+            upd_permissions(program_id, accounts, cmd_args)
         }
-        command_t_e_cmd_resize_price_account => {
-            resize_price_account(program_id, accounts, instruction_data)
+        OracleInstruction::InitMappingPda(hdr) => init_mapping_pda(program_id, accounts, hdr),
+        OracleInstruction::AddMappingPda(hdr) => add_mapping_pda(program_id, accounts, hdr),
+        OracleInstruction::AddProductPda { hdr, seed } => {
+            add_product_pda(program_id, accounts, hdr, seed)
         }
-        command_t_e_cmd_add_price => add_price(program_id, accounts, instruction_data),
-        command_t_e_cmd_init_mapping => init_mapping(program_id, accounts, instruction_data),
-        command_t_e_cmd_init_price => init_price(program_id, accounts, instruction_data),
-        command_t_e_cmd_add_mapping => add_mapping(program_id, accounts, instruction_data),
-        command_t_e_cmd_add_publisher => add_publisher(program_id, accounts, instruction_data),
-        command_t_e_cmd_del_publisher => del_publisher(program_id, accounts, instruction_data),
-        command_t_e_cmd_add_product => add_product(program_id, accounts, instruction_data),
-        command_t_e_cmd_upd_product => upd_product(program_id, accounts, instruction_data),
-        command_t_e_cmd_set_min_pub => set_min_pub(program_id, accounts, instruction_data),
-        _ => Err(OracleError::UnrecognizedInstruction.into()),
+        OracleInstruction::AddPricePda(cmd_args) => add_price_pda(program_id, accounts, cmd_args),
     }
 }
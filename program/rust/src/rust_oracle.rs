@@ -20,9 +20,26 @@ use solana_program::rent::Rent;
 use solana_program::sysvar::Sysvar;
 
 
-use crate::time_machine_types::PriceAccountWrapper;
-use solana_program::program::invoke;
-use solana_program::system_instruction::transfer;
+use crate::aggregate::upd_aggregate;
+use crate::time_machine_types::{
+    find_publisher,
+    find_publisher_with_hint,
+    get_component,
+    get_component_mut,
+    num_components,
+    PriceAccountWrapper,
+};
+use solana_program::program::{
+    invoke,
+    invoke_signed,
+    set_return_data,
+};
+use solana_program::system_instruction::{
+    allocate,
+    assign,
+    create_account,
+    transfer,
+};
 use solana_program::system_program::check_id;
 
 
@@ -32,60 +49,129 @@ use crate::c_oracle_header::{
     cmd_del_publisher_t,
     cmd_hdr_t,
     cmd_init_price_t,
+    cmd_query_time_machine_t,
+    cmd_resize_product_account_t,
+    cmd_set_max_ci_divisor_t,
+    cmd_set_max_latency_t,
     cmd_set_min_pub_t,
+    cmd_upd_permissions_t,
+    cmd_upd_price_batch_entry_t,
+    cmd_upd_price_batch_t,
     cmd_upd_price_t,
-    cmd_upd_product_t,
+    command_t_e_cmd_add_mapping,
+    command_t_e_cmd_add_mapping_pda,
+    command_t_e_cmd_add_price,
+    command_t_e_cmd_add_price_pda,
+    command_t_e_cmd_add_product,
+    command_t_e_cmd_add_product_pda,
+    command_t_e_cmd_add_publisher,
+    command_t_e_cmd_del_price,
+    command_t_e_cmd_del_product,
+    command_t_e_cmd_del_product_attr,
+    command_t_e_cmd_del_publisher,
+    command_t_e_cmd_init_mapping,
+    command_t_e_cmd_init_mapping_pda,
+    command_t_e_cmd_init_price,
+    command_t_e_cmd_migrate_account,
+    command_t_e_cmd_resize_price_account,
+    command_t_e_cmd_resize_product_account,
+    command_t_e_cmd_set_max_ci_divisor,
+    command_t_e_cmd_set_max_latency,
+    command_t_e_cmd_set_min_pub,
+    command_t_e_cmd_set_product_attr,
+    command_t_e_cmd_upd_product,
+    pc_acc,
     pc_ema_t,
     pc_map_table_t,
+    pc_permissions_t,
     pc_price_comp,
     pc_price_info_t,
     pc_price_t,
     pc_prod_t,
     pc_pub_key_t,
+    PythAccount,
+    MAPPING_SEED,
+    PC_ACCTYPE_MAPPING,
+    PC_ACCTYPE_PRICE,
+    PC_ACCTYPE_PRODUCT,
     PC_COMP_SIZE,
+    PC_MAGIC,
     PC_MAP_TABLE_SIZE,
     PC_MAX_CI_DIVISOR,
+    PC_MAX_PROD_ACC_SIZE,
     PC_PROD_ACC_SIZE,
     PC_PTYPE_UNKNOWN,
     PC_STATUS_UNKNOWN,
     PC_VERSION,
+    PERMISSIONS_SEED,
+    PRICE_SEED,
+    PRODUCT_SEED,
+    PUBLISHER_STATS_SIZE,
 };
 use crate::deserialize::{
     initialize_pyth_account_checked, /* TODO: This has a confusingly similar name to a Solana
                                       * sdk function */
-    load,
+    load_account_as,
     load_account_as_mut,
     load_checked,
+    load_price_components_checked,
+    load_time_machine_checked,
 };
 use crate::error::OracleResult;
+use crate::events::{
+    aggregate_event,
+    emit,
+    price_event,
+    AccountEvent,
+    Event,
+    InitPriceEvent,
+    LinkEvent,
+    PermissionsEvent,
+    PriceParameterEvent,
+    PublisherEvent,
+};
+use crate::instruction::ProductAttribute;
 use crate::OracleError;
 
 use crate::utils::{
     check_exponent_range,
+    check_permissioned_funding_account,
     check_valid_fresh_account,
     check_valid_funding_account,
     check_valid_signable_account,
     check_valid_writable_account,
+    clear_account,
+    get_mapping_address,
+    get_permissions_address,
+    get_price_address,
+    get_product_address,
+    get_upgrade_authority,
     is_component_update,
+    is_supported_version,
     pubkey_assign,
     pubkey_equal,
     pubkey_is_zero,
+    pubkey_to_solana,
     pyth_assert,
     read_pc_str_t,
     try_convert,
+    valid_fresh_account,
+    write_pc_str_t,
 };
 
 const PRICE_T_SIZE: usize = size_of::<pc_price_t>();
 const PRICE_ACCOUNT_SIZE: usize = size_of::<PriceAccountWrapper>();
+// Size of the price accounts resized before the publisher statistics were added
+const PRICE_ACCOUNT_SIZE_WITHOUT_STATS: usize = PRICE_ACCOUNT_SIZE - PUBLISHER_STATS_SIZE as usize;
 
-
-#[cfg(target_arch = "bpf")]
-#[link(name = "cpyth-bpf")]
-extern "C" {
-    pub fn c_upd_aggregate(_input: *mut u8, clock_slot: u64, clock_timestamp: i64) -> bool;
+/// Whether a price account of `account_len` bytes was resized to hold the Time Machine
+fn has_time_machine(account_len: usize) -> bool {
+    account_len == PRICE_ACCOUNT_SIZE || account_len == PRICE_ACCOUNT_SIZE_WITHOUT_STATS
 }
 
-#[cfg(not(target_arch = "bpf"))]
+// The C implementation of the aggregation is only kept to test that `aggregate::upd_aggregate`
+// behaves exactly the same
+#[cfg(all(test, feature = "test-c-aggregate"))]
 #[link(name = "cpyth-native")]
 extern "C" {
     pub fn c_upd_aggregate(_input: *mut u8, clock_slot: u64, clock_timestamp: i64) -> bool;
@@ -105,22 +191,153 @@ fn send_lamports<'a>(
     Ok(())
 }
 
-/// resizes a price account so that it fits the Time Machine
+/// Create `account`, owned by the program, with `space` bytes of data and rent exempt lamports
+/// paid by `funding_account`. `signers_seeds` are the seeds of `account` if it is a PDA of the
+/// program, it must sign the transaction otherwise. Lamports that were already sent to the
+/// address are kept, `create_account` would fail on them.
+fn create_program_account<'a>(
+    funding_account: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    space: usize,
+    signers_seeds: &[&[&[u8]]],
+) -> Result<(), ProgramError> {
+    let rent: Rent = Default::default();
+    let lamports_needed: u64 = rent
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+    if account.lamports() == 0 {
+        invoke_signed(
+            &create_account(
+                funding_account.key,
+                account.key,
+                lamports_needed,
+                try_convert(space)?,
+                program_id,
+            ),
+            &[
+                funding_account.clone(),
+                account.clone(),
+                system_program.clone(),
+            ],
+            signers_seeds,
+        )?;
+    } else {
+        if lamports_needed > 0 {
+            send_lamports(funding_account, account, system_program, lamports_needed)?;
+        }
+        invoke_signed(
+            &allocate(account.key, try_convert(space)?),
+            &[account.clone(), system_program.clone()],
+            signers_seeds,
+        )?;
+        invoke_signed(
+            &assign(account.key, program_id),
+            &[account.clone(), system_program.clone()],
+            signers_seeds,
+        )?;
+    }
+    Ok(())
+}
+
+/// Create the account of type `T` at `account`, the PDA of the program with `seeds`, unless it
+/// already exists, and check that it is fresh
+fn create_fresh_pda_account<'a, T: PythAccount>(
+    program_id: &Pubkey,
+    funding_account: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+) -> Result<(), ProgramError> {
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+    pyth_assert(
+        Pubkey::create_program_address(seeds, program_id) == Ok(*account.key),
+        OracleError::InvalidPdaAccount.into(),
+    )?;
+
+    if account.data_is_empty() {
+        create_program_account(
+            funding_account,
+            account,
+            system_program,
+            program_id,
+            T::minimum_size(),
+            &[seeds],
+        )?;
+    }
+    check_valid_writable_account(program_id, account, T::minimum_size())?;
+    check_valid_fresh_account(account)
+}
+
+/// Create the account of type `T` at `account`, which signs the transaction. The handlers then
+/// check it like the accounts created by the clients.
+fn create_signer_account<'a, T: PythAccount>(
+    program_id: &Pubkey,
+    funding_account: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<(), ProgramError> {
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+    pyth_assert(
+        account.data_is_empty(),
+        OracleError::InvalidFreshAccount.into(),
+    )?;
+
+    create_program_account(
+        funding_account,
+        account,
+        system_program,
+        program_id,
+        T::minimum_size(),
+        &[],
+    )
+}
+
+/// Zero the data of `account` and transfer all of its lamports to `recipient`
+fn close_account(account: &AccountInfo, recipient: &AccountInfo) -> Result<(), ProgramError> {
+    clear_account(account)?;
+
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    let recipient_lamports = recipient
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::InvalidArgument)?;
+    **recipient.try_borrow_mut_lamports()? = recipient_lamports;
+    Ok(())
+}
+
+/// resizes a price account so that it fits the Time Machine and the statistics of the publishers.
+/// Accounts that already fit the Time Machine are extended with the statistics only.
 /// key[0] funding account       [signer writable]
-/// key[1] price account         [Signer writable]
+/// key[1] price account         [writable]
 /// key[2] system program        [readable]
+/// key[3] permissions account   [readable]
 pub fn resize_price_account(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    _instruction_data: &[u8],
+    _hdr: &cmd_hdr_t,
 ) -> OracleResult {
-    let [funding_account_info, price_account_info, system_program] = match accounts {
-        [x, y, z] => Ok([x, y, z]),
-        _ => Err(ProgramError::InvalidArgument),
-    }?;
+    let [funding_account_info, price_account_info, system_program, permissions_account] =
+        match accounts {
+            [x, y, z, p] => Ok([x, y, z, p]),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
 
-    check_valid_funding_account(funding_account_info)?;
-    check_valid_signable_account(program_id, price_account_info, size_of::<pc_price_t>())?;
+    check_permissioned_funding_account(
+        program_id,
+        funding_account_info,
+        permissions_account,
+        command_t_e_cmd_resize_price_account,
+    )?;
+    check_valid_writable_account(program_id, price_account_info, size_of::<pc_price_t>())?;
     pyth_assert(
         check_id(system_program.key),
         OracleError::InvalidSystemAccount.into(),
@@ -132,52 +349,85 @@ pub fn resize_price_account(
     }
     let account_len = price_account_info.try_data_len()?;
     match account_len {
-        PRICE_T_SIZE => {
-            //ensure account is still rent exempt after resizing
-            let rent: Rent = Default::default();
-            let lamports_needed: u64 = rent
-                .minimum_balance(size_of::<PriceAccountWrapper>())
-                .saturating_sub(price_account_info.lamports());
-            if lamports_needed > 0 {
-                send_lamports(
-                    funding_account_info,
-                    price_account_info,
-                    system_program,
-                    lamports_needed,
-                )?;
-            }
-            //resize
-            //we do not need to zero initialize since this is the first time this memory
-            //is allocated
-            price_account_info.realloc(size_of::<PriceAccountWrapper>(), false)?;
+        PRICE_T_SIZE | PRICE_ACCOUNT_SIZE_WITHOUT_STATS => {
+            grow_price_account(funding_account_info, price_account_info, system_program)?;
             //The load below would fail if the account was not a price account, reverting the whole
             // transaction
             let mut price_account =
                 load_checked::<PriceAccountWrapper>(price_account_info, PC_VERSION)?;
-            //Initialize Time Machine
-            price_account.initialize_time_machine()?;
+            //Initialize Time Machine, unless it was already in use
+            if account_len == PRICE_T_SIZE {
+                price_account.initialize_time_machine()?;
+            }
+            emit(&Event::ResizePriceAccount(AccountEvent::new(
+                price_account_info.key,
+            )));
             Ok(SUCCESS)
         }
         PRICE_ACCOUNT_SIZE => Ok(SUCCESS),
-        _ => Err(ProgramError::InvalidArgument),
+        _ => Err(OracleError::InvalidAccountSize.into()),
     }
 }
 
+/// grow `price_account` to `PRICE_ACCOUNT_SIZE`, keeping it rent exempt with the lamports of
+/// `funding_account`
+fn grow_price_account<'a>(
+    funding_account: &AccountInfo<'a>,
+    price_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<(), ProgramError> {
+    //ensure account is still rent exempt after resizing
+    let rent: Rent = Default::default();
+    let lamports_needed: u64 = rent
+        .minimum_balance(PRICE_ACCOUNT_SIZE)
+        .saturating_sub(price_account.lamports());
+    if lamports_needed > 0 {
+        send_lamports(
+            funding_account,
+            price_account,
+            system_program,
+            lamports_needed,
+        )?;
+    }
+    //resize
+    //we do not need to zero initialize since this is the first time this memory
+    //is allocated
+    price_account.realloc(PRICE_ACCOUNT_SIZE, false)?;
+    Ok(())
+}
 
-/// initialize the first mapping account in a new linked-list of mapping accounts
+/// initialize the first mapping account in a new linked-list of mapping accounts. If the system
+/// program is passed, the mapping account is created first.
 /// accounts[0] funding account           [signer writable]
 /// accounts[1] new mapping account       [signer writable]
+/// accounts[2] system program            [readable], if any
+/// accounts[2 or 3] permissions account  [readable]
 pub fn init_mapping(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    _hdr: &cmd_hdr_t,
 ) -> OracleResult {
-    let [funding_account, fresh_mapping_account] = match accounts {
-        [x, y] => Ok([x, y]),
-        _ => Err(ProgramError::InvalidArgument),
-    }?;
-
-    check_valid_funding_account(funding_account)?;
+    let (funding_account, fresh_mapping_account, system_program, permissions_account) =
+        match accounts {
+            [x, y, p] => Ok((x, y, None, p)),
+            [x, y, z, p] => Ok((x, y, Some(z), p)),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_init_mapping,
+    )?;
+    if let Some(system_program) = system_program {
+        create_signer_account::<pc_map_table_t>(
+            program_id,
+            funding_account,
+            fresh_mapping_account,
+            system_program,
+        )?;
+    }
     check_valid_signable_account(
         program_id,
         fresh_mapping_account,
@@ -186,37 +436,70 @@ pub fn init_mapping(
     check_valid_fresh_account(fresh_mapping_account)?;
 
     // Initialize by setting to zero again (just in case) and populating the account header
-    let hdr = load::<cmd_hdr_t>(instruction_data)?;
-    initialize_pyth_account_checked::<pc_map_table_t>(fresh_mapping_account, hdr.ver_)?;
+    initialize_pyth_account_checked::<pc_map_table_t>(fresh_mapping_account, PC_VERSION)?;
 
+    emit(&Event::InitMapping(AccountEvent::new(
+        fresh_mapping_account.key,
+    )));
     Ok(SUCCESS)
 }
 
+/// add a mapping account after the tail mapping account. If the system program is passed, the
+/// new mapping account is created first.
+/// accounts[0] funding account           [signer writable]
+/// accounts[1] tail mapping account      [writable]
+/// accounts[2] new mapping account       [signer writable]
+/// accounts[3] system program            [readable], if any
+/// accounts[3 or 4] permissions account  [readable]
 pub fn add_mapping(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    _hdr: &cmd_hdr_t,
 ) -> OracleResult {
-    let [funding_account, cur_mapping, next_mapping] = match accounts {
-        [x, y, z] => Ok([x, y, z]),
-        _ => Err(ProgramError::InvalidArgument),
-    }?;
-
-    check_valid_funding_account(funding_account)?;
-    check_valid_signable_account(program_id, cur_mapping, size_of::<pc_map_table_t>())?;
+    let (funding_account, cur_mapping, next_mapping, system_program, permissions_account) =
+        match accounts {
+            [x, y, z, p] => Ok((x, y, z, None, p)),
+            [w, x, y, z, p] => Ok((w, x, y, Some(z), p)),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_add_mapping,
+    )?;
+    check_valid_writable_account(program_id, cur_mapping, size_of::<pc_map_table_t>())?;
+    if let Some(system_program) = system_program {
+        create_signer_account::<pc_map_table_t>(
+            program_id,
+            funding_account,
+            next_mapping,
+            system_program,
+        )?;
+    }
     check_valid_signable_account(program_id, next_mapping, size_of::<pc_map_table_t>())?;
     check_valid_fresh_account(next_mapping)?;
 
-    let hdr = load::<cmd_hdr_t>(instruction_data)?;
-    let mut cur_mapping = load_checked::<pc_map_table_t>(cur_mapping, hdr.ver_)?;
+    append_mapping(cur_mapping, next_mapping)
+}
+
+/// Initialize `next_mapping` and link it after `cur_mapping`, which must be the full tail of its
+/// linked-list
+fn append_mapping(cur_mapping: &AccountInfo, next_mapping: &AccountInfo) -> OracleResult {
+    let mut cur_mapping_data = load_checked::<pc_map_table_t>(cur_mapping, PC_VERSION)?;
     pyth_assert(
-        cur_mapping.num_ == PC_MAP_TABLE_SIZE && pubkey_is_zero(&cur_mapping.next_),
-        ProgramError::InvalidArgument,
+        cur_mapping_data.num_ == PC_MAP_TABLE_SIZE && pubkey_is_zero(&cur_mapping_data.next_),
+        OracleError::InvalidMappingTail.into(),
     )?;
 
-    initialize_pyth_account_checked::<pc_map_table_t>(next_mapping, hdr.ver_)?;
-    pubkey_assign(&mut cur_mapping.next_, &next_mapping.key.to_bytes());
+    initialize_pyth_account_checked::<pc_map_table_t>(next_mapping, PC_VERSION)?;
+    pubkey_assign(&mut cur_mapping_data.next_, &next_mapping.key.to_bytes());
 
+    emit(&Event::AddMapping(LinkEvent::new(
+        cur_mapping.key,
+        next_mapping.key,
+    )));
     Ok(SUCCESS)
 }
 
@@ -227,172 +510,403 @@ pub fn add_mapping(
 pub fn upd_price(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    cmd_args: &cmd_upd_price_t,
 ) -> OracleResult {
-    let cmd_args = load::<cmd_upd_price_t>(instruction_data)?;
-
     let [funding_account, price_account, clock_account] = match accounts {
         [x, y, z] => Ok([x, y, z]),
         [x, y, _, z] => Ok([x, y, z]),
-        _ => Err(ProgramError::InvalidArgument),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
 
     check_valid_funding_account(funding_account)?;
-    check_valid_writable_account(program_id, price_account, size_of::<pc_price_t>())?;
     // Check clock
     let clock = Clock::from_account_info(clock_account)?;
 
-    let mut publisher_index: usize = 0;
+    let update = pc_price_info_t {
+        price_:           cmd_args.price_,
+        conf_:            cmd_args.conf_,
+        status_:          cmd_args.status_,
+        pub_slot_:        cmd_args.pub_slot_,
+        corp_act_status_: 0,
+    };
+    upd_component_price(
+        program_id,
+        funding_account,
+        price_account,
+        &clock,
+        is_component_update(cmd_args)?,
+        &update,
+        cmd_args.pub_index_,
+    )
+}
+
+/// Apply the price update of `funding_account` to `price_account`, after aggregating the
+/// component prices of the previous slots if needed. If `is_component_update` is false, only the
+/// aggregation happens. The component of the publisher is looked up at `publisher_index_hint`
+/// first.
+fn upd_component_price(
+    program_id: &Pubkey,
+    funding_account: &AccountInfo,
+    price_account: &AccountInfo,
+    clock: &Clock,
+    is_component_update: bool,
+    update: &pc_price_info_t,
+    publisher_index_hint: u32,
+) -> OracleResult {
+    check_valid_writable_account(program_id, price_account, size_of::<pc_price_t>())?;
+
+    let publisher_index: usize;
     let latest_aggregate_price: pc_price_info_t;
+    let max_ci_divisor: i64;
     {
         // Verify that symbol account is initialized
-        let price_data = load_checked::<pc_price_t>(price_account, cmd_args.ver_)?;
+        let mut data = price_account.try_borrow_mut_data()?;
+        let (price_data, extra_components) = load_price_components_checked(&mut data, PC_VERSION)?;
 
         // Verify that publisher is authorized
-        while publisher_index < price_data.num_ as usize {
-            if pubkey_equal(
-                &price_data.comp_[publisher_index].pub_,
-                &funding_account.key.to_bytes(),
-            ) {
-                break;
-            }
-            publisher_index += 1;
-        }
-        pyth_assert(
-            publisher_index < price_data.num_ as usize,
-            ProgramError::InvalidArgument,
-        )?;
-
+        publisher_index = find_publisher_with_hint(
+            price_data,
+            extra_components,
+            &funding_account.key.to_bytes(),
+            try_convert(publisher_index_hint)?,
+        )
+        .ok_or(OracleError::UnknownPublisher)?;
 
         latest_aggregate_price = price_data.agg_;
-        let latest_publisher_price = price_data.comp_[publisher_index].latest_;
+        max_ci_divisor = match price_data.max_ci_divisor_ {
+            0 => PC_MAX_CI_DIVISOR.into(),
+            max_ci_divisor => max_ci_divisor.into(),
+        };
+        let latest_publisher_price = get_component(price_data, extra_components, publisher_index)
+            .ok_or(OracleError::UnknownPublisher)?
+            .latest_;
 
         // Check that publisher is publishing a more recent price
         pyth_assert(
-            !is_component_update(cmd_args)?
-                || cmd_args.pub_slot_ > latest_publisher_price.pub_slot_,
-            ProgramError::InvalidArgument,
+            !is_component_update || update.pub_slot_ > latest_publisher_price.pub_slot_,
+            OracleError::StalePublisherSlot.into(),
         )?;
     }
 
     // Try to update the aggregate
+    let aggregate_attempted = clock.slot > latest_aggregate_price.pub_slot_;
     let mut aggregate_updated = false;
-    if clock.slot > latest_aggregate_price.pub_slot_ {
-        unsafe {
-            aggregate_updated = c_upd_aggregate(
-                price_account.try_borrow_mut_data()?.as_mut_ptr(),
-                clock.slot,
-                clock.unix_timestamp,
-            );
+    if aggregate_attempted {
+        let mut data = price_account.try_borrow_mut_data()?;
+        let (price_data, extra_components) = load_price_components_checked(&mut data, PC_VERSION)?;
+        aggregate_updated = upd_aggregate(
+            price_data,
+            extra_components,
+            clock.slot,
+            clock.unix_timestamp,
+        );
+        if aggregate_updated {
+            emit(&aggregate_event(price_account.key, price_data));
         }
     }
 
     let account_len = price_account.try_data_len()?;
-    if aggregate_updated && account_len == PRICE_ACCOUNT_SIZE {
-        let mut price_account = load_account_as_mut::<PriceAccountWrapper>(price_account)?;
-        price_account.add_price_to_time_machine()?;
+    if aggregate_updated && has_time_machine(account_len) {
+        let mut data = price_account.try_borrow_mut_data()?;
+        let (price_data, time_machine) = load_time_machine_checked(&mut data, PC_VERSION)?;
+        time_machine.add_price(price_data)?;
+    }
+    if aggregate_attempted && account_len == PRICE_ACCOUNT_SIZE {
+        load_account_as_mut::<PriceAccountWrapper>(price_account)?
+            .record_aggregation(clock.slot, aggregate_updated);
     }
 
     // Try to update the publisher's price
-    if is_component_update(cmd_args)? {
-        let mut status: u32 = cmd_args.status_;
-        let mut threshold_conf = cmd_args.price_ / PC_MAX_CI_DIVISOR as i64;
+    if is_component_update {
+        let mut status: u32 = update.status_;
+        let mut threshold_conf = update.price_ / max_ci_divisor;
 
         if threshold_conf < 0 {
             threshold_conf = -threshold_conf;
         }
 
-        if cmd_args.conf_ > try_convert::<_, u64>(threshold_conf)? {
+        let conf_exceeded = update.conf_ > try_convert::<_, u64>(threshold_conf)?;
+        if conf_exceeded {
             status = PC_STATUS_UNKNOWN
         }
 
+        let stored_price: pc_price_info_t;
         {
-            let mut price_data = load_checked::<pc_price_t>(price_account, cmd_args.ver_)?;
-            let publisher_price = &mut price_data.comp_[publisher_index].latest_;
-            publisher_price.price_ = cmd_args.price_;
-            publisher_price.conf_ = cmd_args.conf_;
+            let mut data = price_account.try_borrow_mut_data()?;
+            let (price_data, extra_components) =
+                load_price_components_checked(&mut data, PC_VERSION)?;
+            let publisher_price =
+                &mut get_component_mut(price_data, extra_components, publisher_index)
+                    .ok_or(OracleError::UnknownPublisher)?
+                    .latest_;
+            publisher_price.price_ = update.price_;
+            publisher_price.conf_ = update.conf_;
             publisher_price.status_ = status;
-            publisher_price.pub_slot_ = cmd_args.pub_slot_;
+            publisher_price.pub_slot_ = update.pub_slot_;
+            stored_price = *publisher_price;
         }
+
+        if account_len == PRICE_ACCOUNT_SIZE {
+            let mut price_account = load_account_as_mut::<PriceAccountWrapper>(price_account)?;
+            price_account.record_publisher_update(publisher_index, conf_exceeded)?;
+        }
+
+        emit(&price_event(
+            funding_account.key,
+            price_account.key,
+            &stored_price,
+        ));
     }
 
     Ok(SUCCESS)
 }
 
+/// compute the simple moving average of the aggregate price over the last `window_` seconds and
+/// return it as a `SmaQueryResult` through the return data. `window_` has to be a multiple of
+/// `SMA_GRANULARITY`, the hour that is still in progress is not included.
+/// accounts[0] price account                                       []
+/// accounts[1] sysvar clock                                        []
+pub fn query_time_machine(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cmd_args: &cmd_query_time_machine_t,
+) -> OracleResult {
+    let [price_account, clock_account] = match accounts {
+        [x, y] => Ok([x, y]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+
+    pyth_assert(
+        price_account.owner == program_id,
+        OracleError::InvalidAccountOwner.into(),
+    )?;
+    pyth_assert(
+        has_time_machine(price_account.try_data_len()?),
+        OracleError::InvalidAccountSize.into(),
+    )?;
+    let clock = Clock::from_account_info(clock_account)?;
+
+    let mut data = price_account.try_borrow_mut_data()?;
+    let (price_data, time_machine) = load_time_machine_checked(&mut data, PC_VERSION)?;
+    let sma = time_machine.get_sma(price_data.expo_, clock.unix_timestamp, cmd_args.window_)?;
+    set_return_data(bytes_of(&sma));
+
+    Ok(SUCCESS)
+}
+
 pub fn upd_price_no_fail_on_error(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    cmd_args: &cmd_upd_price_t,
 ) -> OracleResult {
-    match upd_price(program_id, accounts, instruction_data) {
+    match upd_price(program_id, accounts, cmd_args) {
         Err(_) => Ok(SUCCESS),
         Ok(value) => Ok(value),
     }
 }
 
+/// a publisher updates several prices at once. The i-th entry updates the i-th price account. Each
+/// entry is applied like `upd_price` would. If `no_fail_on_error_` is set, the entries that fail
+/// are skipped instead of failing the whole instruction.
+/// accounts[0] publisher account                                   [signer writable]
+/// accounts[1] sysvar clock                                        []
+/// accounts[2..] price accounts to update                          [writable]
+pub fn upd_price_batch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cmd_args: &cmd_upd_price_batch_t,
+    entries: &[cmd_upd_price_batch_entry_t],
+) -> OracleResult {
+    let (funding_account, clock_account, price_accounts) = match accounts {
+        [x, y, rest @ ..] if rest.len() == entries.len() => Ok((x, y, rest)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+
+    check_valid_funding_account(funding_account)?;
+    // Check clock
+    let clock = Clock::from_account_info(clock_account)?;
+
+    for (entry, price_account) in entries.iter().zip(price_accounts) {
+        let update = pc_price_info_t {
+            price_:           entry.price_,
+            conf_:            entry.conf_,
+            status_:          entry.status_,
+            pub_slot_:        entry.pub_slot_,
+            corp_act_status_: 0,
+        };
+        let result = upd_component_price(
+            program_id,
+            funding_account,
+            price_account,
+            &clock,
+            true,
+            &update,
+            entry.pub_index_,
+        );
+        if cmd_args.no_fail_on_error_ == 0 {
+            result?;
+        }
+    }
+
+    Ok(SUCCESS)
+}
+
+/// resizes a product account so that it fits more metadata. Product accounts can only grow, up to
+/// `PC_MAX_PROD_ACC_SIZE`.
+/// key[0] funding account       [signer writable]
+/// key[1] product account       [writable]
+/// key[2] system program        [readable]
+/// key[3] permissions account   [readable]
+pub fn resize_product_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cmd_args: &cmd_resize_product_account_t,
+) -> OracleResult {
+    let [funding_account_info, product_account_info, system_program, permissions_account] =
+        match accounts {
+            [x, y, z, p] => Ok([x, y, z, p]),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account_info,
+        permissions_account,
+        command_t_e_cmd_resize_product_account,
+    )?;
+    check_valid_writable_account(program_id, product_account_info, PC_PROD_ACC_SIZE as usize)?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+    //throw an error if not a product account
+    {
+        load_checked::<pc_prod_t>(product_account_info, PC_VERSION)?;
+    }
 
-/// add a price account to a product account
+    let new_size: usize = try_convert(cmd_args.size_)?;
+    pyth_assert(
+        new_size > product_account_info.try_data_len()?
+            && new_size <= try_convert(PC_MAX_PROD_ACC_SIZE)?,
+        OracleError::InvalidResize.into(),
+    )?;
+
+    //ensure account is still rent exempt after resizing
+    let rent: Rent = Default::default();
+    let lamports_needed: u64 = rent
+        .minimum_balance(new_size)
+        .saturating_sub(product_account_info.lamports());
+    if lamports_needed > 0 {
+        send_lamports(
+            funding_account_info,
+            product_account_info,
+            system_program,
+            lamports_needed,
+        )?;
+    }
+    product_account_info.realloc(new_size, true)?;
+
+    emit(&Event::ResizeProductAccount(AccountEvent::new(
+        product_account_info.key,
+    )));
+    Ok(SUCCESS)
+}
+
+/// add a price account to a product account. If the system program is passed, the price account
+/// is created first.
 /// accounts[0] funding account                                   [signer writable]
-/// accounts[1] product account to add the price account to       [signer writable]
+/// accounts[1] product account to add the price account to       [writable]
 /// accounts[2] newly created price account                       [signer writable]
+/// accounts[3] system program                                    [readable], if any
+/// accounts[3 or 4] permissions account                          [readable]
 pub fn add_price(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    cmd_args: &cmd_add_price_t,
 ) -> OracleResult {
-    let cmd_args = load::<cmd_add_price_t>(instruction_data)?;
-
     check_exponent_range(cmd_args.expo_)?;
     pyth_assert(
         cmd_args.ptype_ != PC_PTYPE_UNKNOWN,
-        ProgramError::InvalidArgument,
+        OracleError::InvalidPriceType.into(),
     )?;
 
 
-    let [funding_account, product_account, price_account] = match accounts {
-        [x, y, z] => Ok([x, y, z]),
-        _ => Err(ProgramError::InvalidArgument),
-    }?;
+    let (funding_account, product_account, price_account, system_program, permissions_account) =
+        match accounts {
+            [x, y, z, p] => Ok((x, y, z, None, p)),
+            [w, x, y, z, p] => Ok((w, x, y, Some(z), p)),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
 
-    check_valid_funding_account(funding_account)?;
-    check_valid_signable_account(program_id, product_account, PC_PROD_ACC_SIZE as usize)?;
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_add_price,
+    )?;
+    check_valid_writable_account(program_id, product_account, PC_PROD_ACC_SIZE as usize)?;
+    if let Some(system_program) = system_program {
+        create_signer_account::<pc_price_t>(
+            program_id,
+            funding_account,
+            price_account,
+            system_program,
+        )?;
+    }
     check_valid_signable_account(program_id, price_account, size_of::<pc_price_t>())?;
     check_valid_fresh_account(price_account)?;
 
-    let mut product_data = load_checked::<pc_prod_t>(product_account, cmd_args.ver_)?;
+    append_price(product_account, price_account, cmd_args)
+}
 
-    let mut price_data =
-        initialize_pyth_account_checked::<pc_price_t>(price_account, cmd_args.ver_)?;
+/// Initialize `price_account` with the exponent and price type of `cmd_args` and add it to the
+/// head of the price accounts of `product_account`
+fn append_price(
+    product_account: &AccountInfo,
+    price_account: &AccountInfo,
+    cmd_args: &cmd_add_price_t,
+) -> OracleResult {
+    let mut product_data = load_checked::<pc_prod_t>(product_account, PC_VERSION)?;
+
+    let mut price_data = initialize_pyth_account_checked::<pc_price_t>(price_account, PC_VERSION)?;
     price_data.expo_ = cmd_args.expo_;
     price_data.ptype_ = cmd_args.ptype_;
     pubkey_assign(&mut price_data.prod_, &product_account.key.to_bytes());
     pubkey_assign(&mut price_data.next_, bytes_of(&product_data.px_acc_));
     pubkey_assign(&mut product_data.px_acc_, &price_account.key.to_bytes());
 
+    emit(&Event::AddPrice(LinkEvent::new(
+        product_account.key,
+        price_account.key,
+    )));
     Ok(SUCCESS)
 }
 
 pub fn init_price(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    cmd_args: &cmd_init_price_t,
 ) -> OracleResult {
-    let cmd_args = load::<cmd_init_price_t>(instruction_data)?;
-
     check_exponent_range(cmd_args.expo_)?;
 
-    let [funding_account, price_account] = match accounts {
-        [x, y] => Ok([x, y]),
-        _ => Err(ProgramError::InvalidArgument),
+    let [funding_account, price_account, permissions_account] = match accounts {
+        [x, y, p] => Ok([x, y, p]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
 
-    check_valid_funding_account(funding_account)?;
-    check_valid_signable_account(program_id, price_account, size_of::<pc_price_t>())?;
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_init_price,
+    )?;
+    check_valid_writable_account(program_id, price_account, size_of::<pc_price_t>())?;
 
-    let mut price_data = load_checked::<pc_price_t>(price_account, cmd_args.ver_)?;
+    let mut data = price_account.try_borrow_mut_data()?;
+    let (price_data, extra_components) = load_price_components_checked(&mut data, PC_VERSION)?;
     pyth_assert(
         price_data.ptype_ == cmd_args.ptype_,
-        ProgramError::InvalidArgument,
+        OracleError::InvalidPriceType.into(),
     )?;
 
     price_data.expo_ = cmd_args.expo_;
@@ -419,150 +933,232 @@ pub fn init_price(
         0,
         size_of::<pc_price_info_t>(),
     );
-    for i in 0..(price_data.comp_.len() as usize) {
+    for component in price_data
+        .comp_
+        .iter_mut()
+        .chain(extra_components.iter_mut())
+    {
         sol_memset(
-            bytes_of_mut(&mut price_data.comp_[i].agg_),
+            bytes_of_mut(&mut component.agg_),
             0,
             size_of::<pc_price_info_t>(),
         );
         sol_memset(
-            bytes_of_mut(&mut price_data.comp_[i].latest_),
+            bytes_of_mut(&mut component.latest_),
             0,
             size_of::<pc_price_info_t>(),
         );
     }
 
+    emit(&Event::InitPrice(InitPriceEvent {
+        price_account: price_account.key.to_bytes(),
+        expo:          cmd_args.expo_,
+        ptype:         cmd_args.ptype_,
+    }));
     Ok(SUCCESS)
 }
 
 /// add a publisher to a price account
 /// accounts[0] funding account                                   [signer writable]
-/// accounts[1] price account to add the publisher to             [signer writable]
+/// accounts[1] price account to add the publisher to             [writable]
+/// accounts[2] permissions account                               [readable]
 pub fn add_publisher(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    cmd_args: &cmd_add_publisher_t,
 ) -> OracleResult {
-    let cmd_args = load::<cmd_add_publisher_t>(instruction_data)?;
-
     pyth_assert(
-        instruction_data.len() == size_of::<cmd_add_publisher_t>()
-            && !pubkey_is_zero(&cmd_args.pub_),
-        ProgramError::InvalidArgument,
+        !pubkey_is_zero(&cmd_args.pub_),
+        OracleError::InvalidPublisherKey.into(),
     )?;
 
-    let [funding_account, price_account] = match accounts {
-        [x, y] => Ok([x, y]),
-        _ => Err(ProgramError::InvalidArgument),
+    let [funding_account, price_account, permissions_account] = match accounts {
+        [x, y, p] => Ok([x, y, p]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
 
-    check_valid_funding_account(funding_account)?;
-    check_valid_signable_account(program_id, price_account, size_of::<pc_price_t>())?;
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_add_publisher,
+    )?;
+    check_valid_writable_account(program_id, price_account, size_of::<pc_price_t>())?;
 
-    let mut price_data = load_checked::<pc_price_t>(price_account, cmd_args.ver_)?;
+    let mut data = price_account.try_borrow_mut_data()?;
+    let (price_data, extra_components) = load_price_components_checked(&mut data, PC_VERSION)?;
 
-    if price_data.num_ >= PC_COMP_SIZE {
-        return Err(ProgramError::InvalidArgument);
+    // Resized accounts also have the components of their extra publisher space
+    let current_index = num_components(price_data);
+    if current_index >= PC_COMP_SIZE as usize + extra_components.len() {
+        return Err(OracleError::ComponentTableFull.into());
     }
 
-    for i in 0..(price_data.num_ as usize) {
-        if pubkey_equal(&cmd_args.pub_, bytes_of(&price_data.comp_[i].pub_)) {
-            return Err(ProgramError::InvalidArgument);
-        }
+    if find_publisher(price_data, extra_components, bytes_of(&cmd_args.pub_)).is_some() {
+        return Err(OracleError::DuplicatePublisher.into());
     }
 
-    let current_index: usize = try_convert(price_data.num_)?;
-    sol_memset(
-        bytes_of_mut(&mut price_data.comp_[current_index]),
-        0,
-        size_of::<pc_price_comp>(),
-    );
-    pubkey_assign(
-        &mut price_data.comp_[current_index].pub_,
-        bytes_of(&cmd_args.pub_),
-    );
-    price_data.num_ += 1;
+    let component = get_component_mut(price_data, extra_components, current_index)
+        .ok_or(OracleError::ComponentTableFull)?;
+    sol_memset(bytes_of_mut(component), 0, size_of::<pc_price_comp>());
+    pubkey_assign(&mut component.pub_, bytes_of(&cmd_args.pub_));
+    // comp_ is filled first, the extra components are counted separately so that num_ keeps
+    // counting the components of comp_ only
+    if price_data.num_ < PC_COMP_SIZE {
+        price_data.num_ += 1;
+    } else {
+        price_data.num_extra_ += 1;
+    }
     price_data.size_ =
         try_convert::<_, u32>(size_of::<pc_price_t>() - size_of_val(&price_data.comp_))?
             + price_data.num_ * try_convert::<_, u32>(size_of::<pc_price_comp>())?;
+    drop(data);
+
+    if price_account.try_data_len()? == PRICE_ACCOUNT_SIZE {
+        load_account_as_mut::<PriceAccountWrapper>(price_account)?
+            .reset_publisher_stats(current_index)?;
+    }
+
+    emit(&Event::AddPublisher(PublisherEvent::new(
+        price_account.key,
+        &pubkey_to_solana(&cmd_args.pub_),
+    )));
     Ok(SUCCESS)
 }
 
 /// add a publisher to a price account
 /// accounts[0] funding account                                   [signer writable]
-/// accounts[1] price account to delete the publisher from        [signer writable]
+/// accounts[1] price account to delete the publisher from        [writable]
+/// accounts[2] permissions account                               [readable]
 pub fn del_publisher(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    cmd_args: &cmd_del_publisher_t,
 ) -> OracleResult {
-    let cmd_args = load::<cmd_del_publisher_t>(instruction_data)?;
-
     pyth_assert(
-        instruction_data.len() == size_of::<cmd_del_publisher_t>()
-            && !pubkey_is_zero(&cmd_args.pub_),
-        ProgramError::InvalidArgument,
+        !pubkey_is_zero(&cmd_args.pub_),
+        OracleError::InvalidPublisherKey.into(),
     )?;
 
-    let [funding_account, price_account] = match accounts {
-        [x, y] => Ok([x, y]),
-        _ => Err(ProgramError::InvalidArgument),
+    let [funding_account, price_account, permissions_account] = match accounts {
+        [x, y, p] => Ok([x, y, p]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
 
-    check_valid_funding_account(funding_account)?;
-    check_valid_signable_account(program_id, price_account, size_of::<pc_price_t>())?;
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_del_publisher,
+    )?;
+    check_valid_writable_account(program_id, price_account, size_of::<pc_price_t>())?;
 
-    let mut price_data = load_checked::<pc_price_t>(price_account, cmd_args.ver_)?;
+    let mut data = price_account.try_borrow_mut_data()?;
+    let (price_data, extra_components) = load_price_components_checked(&mut data, PC_VERSION)?;
 
-    for i in 0..(price_data.num_ as usize) {
-        if pubkey_equal(&cmd_args.pub_, bytes_of(&price_data.comp_[i].pub_)) {
-            for j in i + 1..(price_data.num_ as usize) {
-                price_data.comp_[j - 1] = price_data.comp_[j];
-            }
-            price_data.num_ -= 1;
-            let current_index: usize = try_convert(price_data.num_)?;
-            sol_memset(
-                bytes_of_mut(&mut price_data.comp_[current_index]),
-                0,
-                size_of::<pc_price_comp>(),
-            );
-            price_data.size_ =
-                try_convert::<_, u32>(size_of::<pc_price_t>() - size_of_val(&price_data.comp_))?
-                    + price_data.num_ * try_convert::<_, u32>(size_of::<pc_price_comp>())?;
-            return Ok(SUCCESS);
-        }
+    let publisher_index = find_publisher(price_data, extra_components, bytes_of(&cmd_args.pub_))
+        .ok_or(OracleError::UnknownPublisher)?;
+    for j in publisher_index + 1..num_components(price_data) {
+        let component =
+            *get_component(price_data, extra_components, j).ok_or(OracleError::UnknownPublisher)?;
+        *get_component_mut(price_data, extra_components, j - 1)
+            .ok_or(OracleError::UnknownPublisher)? = component;
+    }
+    // The last component moved, it is an extra component if there are any
+    if price_data.num_extra_ > 0 {
+        price_data.num_extra_ -= 1;
+    } else {
+        price_data.num_ -= 1;
+    }
+    let current_index = num_components(price_data);
+    sol_memset(
+        bytes_of_mut(
+            get_component_mut(price_data, extra_components, current_index)
+                .ok_or(OracleError::UnknownPublisher)?,
+        ),
+        0,
+        size_of::<pc_price_comp>(),
+    );
+    price_data.size_ =
+        try_convert::<_, u32>(size_of::<pc_price_t>() - size_of_val(&price_data.comp_))?
+            + price_data.num_ * try_convert::<_, u32>(size_of::<pc_price_comp>())?;
+    drop(data);
+
+    // The statistics stay at the index of the component of their publisher
+    if price_account.try_data_len()? == PRICE_ACCOUNT_SIZE {
+        load_account_as_mut::<PriceAccountWrapper>(price_account)?
+            .remove_publisher_stats(publisher_index)?;
     }
-    Err(ProgramError::InvalidArgument)
+
+    emit(&Event::DelPublisher(PublisherEvent::new(
+        price_account.key,
+        &pubkey_to_solana(&cmd_args.pub_),
+    )));
+    Ok(SUCCESS)
 }
 
+/// add a product account to a mapping account. If the system program is passed, the product
+/// account is created first.
+/// accounts[0] funding account           [signer writable]
+/// accounts[1] mapping account           [writable]
+/// accounts[2] new product account       [signer writable]
+/// accounts[3] system program            [readable], if any
+/// accounts[3 or 4] permissions account  [readable]
 pub fn add_product(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    _hdr: &cmd_hdr_t,
 ) -> OracleResult {
-    let [funding_account, tail_mapping_account, new_product_account] = match accounts {
-        [x, y, z] => Ok([x, y, z]),
-        _ => Err(ProgramError::InvalidArgument),
+    let (
+        funding_account,
+        tail_mapping_account,
+        new_product_account,
+        system_program,
+        permissions_account,
+    ) = match accounts {
+        [x, y, z, p] => Ok((x, y, z, None, p)),
+        [w, x, y, z, p] => Ok((w, x, y, Some(z), p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
 
-    check_valid_funding_account(funding_account)?;
-    check_valid_signable_account(
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_add_product,
+    )?;
+    check_valid_writable_account(
         program_id,
         tail_mapping_account,
         size_of::<pc_map_table_t>(),
     )?;
+    if let Some(system_program) = system_program {
+        create_signer_account::<pc_prod_t>(
+            program_id,
+            funding_account,
+            new_product_account,
+            system_program,
+        )?;
+    }
     check_valid_signable_account(program_id, new_product_account, PC_PROD_ACC_SIZE as usize)?;
     check_valid_fresh_account(new_product_account)?;
 
-    let hdr = load::<cmd_hdr_t>(instruction_data)?;
-    let mut mapping_data = load_checked::<pc_map_table_t>(tail_mapping_account, hdr.ver_)?;
+    append_product(tail_mapping_account, new_product_account)
+}
+
+/// Initialize `new_product_account` and add it to the products of `tail_mapping_account`
+fn append_product(
+    tail_mapping_account: &AccountInfo,
+    new_product_account: &AccountInfo,
+) -> OracleResult {
+    let mut mapping_data = load_checked::<pc_map_table_t>(tail_mapping_account, PC_VERSION)?;
     // The mapping account must have free space to add the product account
     pyth_assert(
         mapping_data.num_ < PC_MAP_TABLE_SIZE,
-        ProgramError::InvalidArgument,
+        OracleError::MappingAccountFull.into(),
     )?;
 
-    initialize_pyth_account_checked::<pc_prod_t>(new_product_account, hdr.ver_)?;
+    initialize_pyth_account_checked::<pc_prod_t>(new_product_account, PC_VERSION)?;
 
     let current_index: usize = try_convert(mapping_data.num_)?;
     pubkey_assign(
@@ -574,51 +1170,49 @@ pub fn add_product(
         try_convert::<_, u32>(size_of::<pc_map_table_t>() - size_of_val(&mapping_data.prod_))?
             + mapping_data.num_ * try_convert::<_, u32>(size_of::<pc_pub_key_t>())?;
 
+    emit(&Event::AddProduct(LinkEvent::new(
+        tail_mapping_account.key,
+        new_product_account.key,
+    )));
     Ok(SUCCESS)
 }
 
 /// Update the metadata associated with a product, overwriting any existing metadata.
-/// The metadata is provided as a list of key-value pairs at the end of the `instruction_data`.
 pub fn upd_product(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    _hdr: &cmd_hdr_t,
+    attributes: &[ProductAttribute],
 ) -> OracleResult {
-    let [funding_account, product_account] = match accounts {
-        [x, y] => Ok([x, y]),
-        _ => Err(ProgramError::InvalidArgument),
+    let [funding_account, product_account, permissions_account] = match accounts {
+        [x, y, p] => Ok([x, y, p]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
 
-    check_valid_funding_account(funding_account)?;
-    check_valid_signable_account(program_id, product_account, try_convert(PC_PROD_ACC_SIZE)?)?;
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_upd_product,
+    )?;
+    check_valid_writable_account(program_id, product_account, try_convert(PC_PROD_ACC_SIZE)?)?;
 
-    let hdr = load::<cmd_hdr_t>(instruction_data)?;
     {
         // Validate that product_account contains the appropriate account header
-        let mut _product_data = load_checked::<pc_prod_t>(product_account, hdr.ver_)?;
+        let mut _product_data = load_checked::<pc_prod_t>(product_account, PC_VERSION)?;
     }
 
+    // The metadata is stored as a list of key-value pairs, both of which are pc_str_t
+    let mut new_data = vec![];
+    for (key, value) in attributes {
+        write_pc_str_t(&mut new_data, key)?;
+        write_pc_str_t(&mut new_data, value)?;
+    }
+    let max_data_len = product_account.try_data_len()? - size_of::<pc_prod_t>();
     pyth_assert(
-        instruction_data.len() >= size_of::<cmd_upd_product_t>(),
-        ProgramError::InvalidInstructionData,
+        new_data.len() <= max_data_len,
+        OracleError::MetadataTooLarge.into(),
     )?;
-    let new_data_len = instruction_data.len() - size_of::<cmd_upd_product_t>();
-    let max_data_len = try_convert::<_, usize>(PC_PROD_ACC_SIZE)? - size_of::<pc_prod_t>();
-    pyth_assert(new_data_len <= max_data_len, ProgramError::InvalidArgument)?;
-
-    let new_data = &instruction_data[size_of::<cmd_upd_product_t>()..instruction_data.len()];
-    let mut idx = 0;
-    // new_data must be a list of key-value pairs, both of which are instances of pc_str_t.
-    // Try reading the key-value pairs to validate that new_data is properly formatted.
-    while idx < new_data.len() {
-        let key = read_pc_str_t(&new_data[idx..])?;
-        idx += key.len();
-        let value = read_pc_str_t(&new_data[idx..])?;
-        idx += value.len();
-    }
-
-    // This assertion shouldn't ever fail, but be defensive.
-    pyth_assert(idx == new_data.len(), ProgramError::InvalidArgument)?;
 
     {
         let mut data = product_account.try_borrow_mut_data()?;
@@ -626,39 +1220,688 @@ pub fn upd_product(
         // This case is handled by updating the .size_ field below.
         sol_memcpy(
             &mut data[size_of::<pc_prod_t>()..],
-            new_data,
+            &new_data,
             new_data.len(),
         );
     }
 
-    let mut product_data = load_checked::<pc_prod_t>(product_account, hdr.ver_)?;
+    let mut product_data = load_checked::<pc_prod_t>(product_account, PC_VERSION)?;
     product_data.size_ = try_convert(size_of::<pc_prod_t>() + new_data.len())?;
 
+    emit(&Event::UpdProduct(AccountEvent::new(product_account.key)));
     Ok(SUCCESS)
 }
 
+/// Set the value of a single attribute of a product, leaving the other attributes untouched.
+/// The attribute is updated in place if it already exists, otherwise it is added at the end.
+/// accounts[0] funding account       [signer writable]
+/// accounts[1] product account       [writable]
+/// accounts[2] permissions account   [readable]
+pub fn set_product_attr(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _hdr: &cmd_hdr_t,
+    key: &[u8],
+    value: &[u8],
+) -> OracleResult {
+    let [funding_account, product_account, permissions_account] = match accounts {
+        [x, y, p] => Ok([x, y, p]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_set_product_attr,
+    )?;
+    check_valid_writable_account(program_id, product_account, try_convert(PC_PROD_ACC_SIZE)?)?;
+
+    let mut new_attr = vec![];
+    write_pc_str_t(&mut new_attr, key)?;
+    write_pc_str_t(&mut new_attr, value)?;
+    replace_product_attr(product_account, key, &new_attr)?;
+
+    emit(&Event::SetProductAttr(AccountEvent::new(
+        product_account.key,
+    )));
+    Ok(SUCCESS)
+}
+
+/// Remove a single attribute of a product, leaving the other attributes untouched.
+/// accounts[0] funding account       [signer writable]
+/// accounts[1] product account       [writable]
+/// accounts[2] permissions account   [readable]
+pub fn del_product_attr(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _hdr: &cmd_hdr_t,
+    key: &[u8],
+) -> OracleResult {
+    let [funding_account, product_account, permissions_account] = match accounts {
+        [x, y, p] => Ok([x, y, p]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_del_product_attr,
+    )?;
+    check_valid_writable_account(program_id, product_account, try_convert(PC_PROD_ACC_SIZE)?)?;
+
+    // The attribute must exist
+    pyth_assert(
+        replace_product_attr(product_account, key, &[])?,
+        OracleError::AttributeNotFound.into(),
+    )?;
+
+    emit(&Event::DelProductAttr(AccountEvent::new(
+        product_account.key,
+    )));
+    Ok(SUCCESS)
+}
+
+/// Replace the key-value pair of `key` (without its length prefix) in the metadata of
+/// `product_account` with `new_attr`, shifting the attributes that follow it. If `key` isn't found,
+/// `new_attr` is appended. Returns whether `key` was found.
+fn replace_product_attr(
+    product_account: &AccountInfo,
+    key: &[u8],
+    new_attr: &[u8],
+) -> Result<bool, ProgramError> {
+    let old_size: usize =
+        try_convert(load_checked::<pc_prod_t>(product_account, PC_VERSION)?.size_)?;
+
+    let mut data = product_account.try_borrow_mut_data()?;
+    pyth_assert(
+        size_of::<pc_prod_t>() <= old_size && old_size <= data.len(),
+        ProgramError::InvalidAccountData,
+    )?;
+
+    // Find the existing key-value pair, if any
+    let mut attr_start = old_size;
+    let mut attr_end = old_size;
+    let mut idx = size_of::<pc_prod_t>();
+    while idx < old_size {
+        let current_key = read_pc_str_t(&data[idx..old_size])?;
+        let current_value = read_pc_str_t(&data[idx + current_key.len()..old_size])?;
+        let next_idx = idx + current_key.len() + current_value.len();
+        if current_key[1..] == *key {
+            attr_start = idx;
+            attr_end = next_idx;
+            break;
+        }
+        idx = next_idx;
+    }
+
+    let new_size = old_size - (attr_end - attr_start) + new_attr.len();
+    pyth_assert(new_size <= data.len(), OracleError::MetadataTooLarge.into())?;
+
+    data.copy_within(attr_end..old_size, attr_start + new_attr.len());
+    data[attr_start..attr_start + new_attr.len()].copy_from_slice(new_attr);
+    drop(data);
+
+    let mut product_data = load_checked::<pc_prod_t>(product_account, PC_VERSION)?;
+    product_data.size_ = try_convert(new_size)?;
+
+    Ok(attr_start != old_size)
+}
+
 pub fn set_min_pub(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    instruction_data: &[u8],
+    cmd: &cmd_set_min_pub_t,
 ) -> OracleResult {
-    let cmd = load::<cmd_set_min_pub_t>(instruction_data)?;
+    let [funding_account, price_account, permissions_account] = match accounts {
+        [x, y, p] => Ok([x, y, p]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
 
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_set_min_pub,
+    )?;
+    check_valid_writable_account(program_id, price_account, size_of::<pc_price_t>())?;
+
+    let mut price_account_data = load_checked::<pc_price_t>(price_account, PC_VERSION)?;
+    price_account_data.min_pub_ = cmd.min_pub_;
+
+    emit(&Event::SetMinPub(PriceParameterEvent::new(
+        price_account.key,
+        cmd.min_pub_.into(),
+    )));
+    Ok(SUCCESS)
+}
+
+/// Set the maximum number of slots a component price can lag behind the current slot and still
+/// be included in the aggregate. Zero restores the default of `PC_MAX_SEND_LATENCY`.
+/// accounts[0] funding account       [signer writable]
+/// accounts[1] price account         [writable]
+/// accounts[2] permissions account   [readable]
+pub fn set_max_latency(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cmd: &cmd_set_max_latency_t,
+) -> OracleResult {
+    let [funding_account, price_account, permissions_account] = match accounts {
+        [x, y, p] => Ok([x, y, p]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_set_max_latency,
+    )?;
+    check_valid_writable_account(program_id, price_account, size_of::<pc_price_t>())?;
+
+    let mut price_account_data = load_checked::<pc_price_t>(price_account, PC_VERSION)?;
+    price_account_data.max_latency_ = cmd.max_latency_;
+
+    emit(&Event::SetMaxLatency(PriceParameterEvent::new(
+        price_account.key,
+        cmd.max_latency_.into(),
+    )));
+    Ok(SUCCESS)
+}
+
+/// Set the divisor of the price above which a published confidence interval marks the component
+/// price as unknown. Zero restores the default of `PC_MAX_CI_DIVISOR`. A divisor of 1 is rejected
+/// so that the threshold can always be negated.
+/// accounts[0] funding account       [signer writable]
+/// accounts[1] price account         [writable]
+/// accounts[2] permissions account   [readable]
+pub fn set_max_ci_divisor(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cmd: &cmd_set_max_ci_divisor_t,
+) -> OracleResult {
     pyth_assert(
-        instruction_data.len() == size_of::<cmd_set_min_pub_t>(),
-        ProgramError::InvalidArgument,
+        cmd.max_ci_divisor_ != 1,
+        OracleError::InvalidMaxCiDivisor.into(),
     )?;
 
-    let [funding_account, price_account] = match accounts {
-        [x, y] => Ok([x, y]),
-        _ => Err(ProgramError::InvalidArgument),
+    let [funding_account, price_account, permissions_account] = match accounts {
+        [x, y, p] => Ok([x, y, p]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_set_max_ci_divisor,
+    )?;
+    check_valid_writable_account(program_id, price_account, size_of::<pc_price_t>())?;
+
+    let mut price_account_data = load_checked::<pc_price_t>(price_account, PC_VERSION)?;
+    price_account_data.max_ci_divisor_ = cmd.max_ci_divisor_;
+
+    emit(&Event::SetMaxCiDivisor(PriceParameterEvent::new(
+        price_account.key,
+        cmd.max_ci_divisor_.into(),
+    )));
+    Ok(SUCCESS)
+}
+
+/// Delete a price account that has no publishers left. The price account is unlinked from the
+/// list of price accounts of its product, its data is zeroed and its lamports are transferred to
+/// the funding account.
+/// If the price account is not the first one of the list, the price account that precedes it has
+/// to be provided so that it can point to the next one.
+/// accounts[0] funding account          [signer writable]
+/// accounts[1] product account          [writable]
+/// accounts[2] price account            [writable]
+/// accounts[3] previous price account   [writable] (optional)
+/// accounts[3 or 4] permissions account [readable]
+pub fn del_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _cmd_args: &cmd_hdr_t,
+) -> OracleResult {
+    let (
+        funding_account,
+        product_account,
+        price_account,
+        previous_price_account,
+        permissions_account,
+    ) = match accounts {
+        [w, x, y, p] => Ok((w, x, y, None, p)),
+        [w, x, y, z, p] => Ok((w, x, y, Some(z), p)),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_del_price,
+    )?;
+    check_valid_writable_account(program_id, product_account, PC_PROD_ACC_SIZE as usize)?;
+    check_valid_writable_account(program_id, price_account, size_of::<pc_price_t>())?;
+
+    {
+        let mut product_data = load_checked::<pc_prod_t>(product_account, PC_VERSION)?;
+        let price_data = load_checked::<pc_price_t>(price_account, PC_VERSION)?;
+
+        // Publishers have to be removed first
+        pyth_assert(
+            price_data.num_ == 0,
+            OracleError::PriceAccountHasPublishers.into(),
+        )?;
+        pyth_assert(
+            pubkey_equal(&price_data.prod_, &product_account.key.to_bytes()),
+            OracleError::PriceAccountNotLinked.into(),
+        )?;
+
+        match previous_price_account {
+            None => {
+                pyth_assert(
+                    pubkey_equal(&product_data.px_acc_, &price_account.key.to_bytes()),
+                    OracleError::PriceAccountNotLinked.into(),
+                )?;
+                pubkey_assign(&mut product_data.px_acc_, bytes_of(&price_data.next_));
+            }
+            Some(previous_price_account) => {
+                check_valid_writable_account(
+                    program_id,
+                    previous_price_account,
+                    size_of::<pc_price_t>(),
+                )?;
+                let mut previous_price_data =
+                    load_checked::<pc_price_t>(previous_price_account, PC_VERSION)?;
+                pyth_assert(
+                    pubkey_equal(&previous_price_data.next_, &price_account.key.to_bytes()),
+                    OracleError::PriceAccountNotLinked.into(),
+                )?;
+                pubkey_assign(&mut previous_price_data.next_, bytes_of(&price_data.next_));
+            }
+        }
+    }
+
+    close_account(price_account, funding_account)?;
+
+    emit(&Event::DelPrice(LinkEvent::new(
+        product_account.key,
+        price_account.key,
+    )));
+    Ok(SUCCESS)
+}
+
+/// Delete a product account that has no price accounts. The product is removed from the mapping
+/// account by moving the last product of the mapping into its slot, then the product account is
+/// zeroed and its lamports are transferred to the funding account.
+/// accounts[0] funding account       [signer writable]
+/// accounts[1] mapping account       [writable]
+/// accounts[2] product account       [writable]
+/// accounts[3] permissions account   [readable]
+pub fn del_product(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _cmd_args: &cmd_hdr_t,
+) -> OracleResult {
+    let [funding_account, mapping_account, product_account, permissions_account] = match accounts {
+        [x, y, z, p] => Ok([x, y, z, p]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_del_product,
+    )?;
+    check_valid_writable_account(program_id, mapping_account, size_of::<pc_map_table_t>())?;
+    check_valid_writable_account(program_id, product_account, PC_PROD_ACC_SIZE as usize)?;
+
+    {
+        let mut mapping_data = load_checked::<pc_map_table_t>(mapping_account, PC_VERSION)?;
+        let product_data = load_checked::<pc_prod_t>(product_account, PC_VERSION)?;
+
+        // Price accounts have to be removed first
+        pyth_assert(
+            pubkey_is_zero(&product_data.px_acc_),
+            OracleError::ProductAccountHasPrices.into(),
+        )?;
+
+        let num_products: usize = try_convert(mapping_data.num_)?;
+        let product_index = mapping_data.prod_[..num_products]
+            .iter()
+            .position(|key| pubkey_equal(key, &product_account.key.to_bytes()))
+            .ok_or(OracleError::ProductNotInMapping)?;
+
+        let last_index = num_products - 1;
+        mapping_data.prod_[product_index] = mapping_data.prod_[last_index];
+        sol_memset(
+            bytes_of_mut(&mut mapping_data.prod_[last_index]),
+            0,
+            size_of::<pc_pub_key_t>(),
+        );
+        mapping_data.num_ -= 1;
+        mapping_data.size_ =
+            try_convert::<_, u32>(size_of::<pc_map_table_t>() - size_of_val(&mapping_data.prod_))?
+                + mapping_data.num_ * try_convert::<_, u32>(size_of::<pc_pub_key_t>())?;
+    }
+
+    close_account(product_account, funding_account)?;
+
+    emit(&Event::DelProduct(LinkEvent::new(
+        mapping_account.key,
+        product_account.key,
+    )));
+    Ok(SUCCESS)
+}
+
+/// upgrades an account of a supported older version to the layout of PC_VERSION. Price accounts
+/// resized before the publisher statistics were added are grown to hold them, whatever their
+/// version. Accounts that already have the current layout are left unchanged.
+/// key[0] funding account       [signer writable]
+/// key[1] account to upgrade    [writable]
+/// key[2] system program        [readable]
+/// key[3] permissions account   [readable]
+pub fn migrate_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _hdr: &cmd_hdr_t,
+) -> OracleResult {
+    let [funding_account, account, system_program, permissions_account] = match accounts {
+        [x, y, z, p] => Ok([x, y, z, p]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
+    }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_migrate_account,
+    )?;
+    check_valid_writable_account(program_id, account, size_of::<pc_acc>())?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+
+    let (account_type, version) = {
+        let account_header = load_account_as::<pc_acc>(account)?;
+        pyth_assert(
+            account_header.magic_ == PC_MAGIC
+                && matches!(
+                    account_header.type_,
+                    PC_ACCTYPE_MAPPING | PC_ACCTYPE_PRODUCT | PC_ACCTYPE_PRICE
+                ),
+            OracleError::InvalidAccountHeader.into(),
+        )?;
+        (account_header.type_, account_header.ver_)
+    };
+    pyth_assert(
+        is_supported_version(version),
+        OracleError::InvalidAccountVersion.into(),
+    )?;
+
+    upgrade_account_layout(funding_account, account, system_program, account_type)?;
+    load_account_as_mut::<pc_acc>(account)?.ver_ = PC_VERSION;
+
+    emit(&Event::MigrateAccount(AccountEvent::new(account.key)));
+    Ok(SUCCESS)
+}
+
+/// Upgrade `account`, of type `account_type`, to the current layout of its type. An upgrade that
+/// needs to grow the account is paid by `funding_account`.
+/// The only layout that changed since PC_MIN_VERSION is the one of resized price accounts, which
+/// gained the publisher statistics after the Time Machine. A bump of PC_VERSION that changes the
+/// layout of an account type must add the upgrade of that type here.
+fn upgrade_account_layout<'a>(
+    funding_account: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    account_type: u32,
+) -> Result<(), ProgramError> {
+    if account_type == PC_ACCTYPE_PRICE
+        && account.try_data_len()? == PRICE_ACCOUNT_SIZE_WITHOUT_STATS
+    {
+        grow_price_account(funding_account, account, system_program)?;
+    }
+    Ok(())
+}
+
+/// set the authorities of the permissions account, creating it if needed. Only the upgrade
+/// authority of the program can run it, so that it can always recover the permissions.
+/// key[0] upgrade authority     [signer writable]
+/// key[1] programdata account   [readable]
+/// key[2] permissions account   [writable]
+/// key[3] system program        [readable]
+pub fn upd_permissions(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cmd_args: &cmd_upd_permissions_t,
+) -> OracleResult {
+    let [funding_account, programdata_account, permissions_account, system_program] = match accounts
+    {
+        [w, x, y, z] => Ok([w, x, y, z]),
+        _ => Err(OracleError::InvalidNumberOfAccounts),
     }?;
 
     check_valid_funding_account(funding_account)?;
-    check_valid_signable_account(program_id, price_account, size_of::<pc_price_t>())?;
+    pyth_assert(
+        get_upgrade_authority(program_id, programdata_account)? == Some(*funding_account.key),
+        OracleError::PermissionDenied.into(),
+    )?;
+    pyth_assert(
+        check_id(system_program.key),
+        OracleError::InvalidSystemAccount.into(),
+    )?;
+    let (permissions_address, bump_seed) = get_permissions_address(program_id);
+    pyth_assert(
+        *permissions_account.key == permissions_address,
+        OracleError::InvalidPermissionsAccount.into(),
+    )?;
 
-    let mut price_account_data = load_checked::<pc_price_t>(price_account, cmd.ver_)?;
-    price_account_data.min_pub_ = cmd.min_pub_;
+    if permissions_account.data_is_empty() {
+        create_program_account(
+            funding_account,
+            permissions_account,
+            system_program,
+            program_id,
+            size_of::<pc_permissions_t>(),
+            &[&[PERMISSIONS_SEED.as_bytes(), &[bump_seed]]],
+        )?;
+    }
+    check_valid_writable_account(
+        program_id,
+        permissions_account,
+        size_of::<pc_permissions_t>(),
+    )?;
+
+    // All the fields are overwritten below, so an account of an older version doesn't need to be
+    // migrated
+    let account_version = load_account_as::<pc_acc>(permissions_account)?.ver_;
+    if valid_fresh_account(permissions_account)
+        || (account_version < PC_VERSION && is_supported_version(account_version))
+    {
+        initialize_pyth_account_checked::<pc_permissions_t>(permissions_account, PC_VERSION)?;
+    }
+
+    let mut permissions_data = load_checked::<pc_permissions_t>(permissions_account, PC_VERSION)?;
+    permissions_data.master_authority_ = cmd_args.master_authority_;
+    permissions_data.data_curation_authority_ = cmd_args.data_curation_authority_;
+    permissions_data.security_authority_ = cmd_args.security_authority_;
 
+    emit(&Event::UpdPermissions(PermissionsEvent {
+        master_authority:        pubkey_to_solana(&cmd_args.master_authority_).to_bytes(),
+        data_curation_authority: pubkey_to_solana(&cmd_args.data_curation_authority_).to_bytes(),
+        security_authority:      pubkey_to_solana(&cmd_args.security_authority_).to_bytes(),
+    }));
     Ok(SUCCESS)
 }
+
+/// initialize the first mapping account of a new linked-list, created at the PDA of the program
+/// with seeds [MAPPING_SEED]
+/// accounts[0] funding account           [signer writable]
+/// accounts[1] new mapping account       [writable]
+/// accounts[2] system program            [readable]
+/// accounts[3] permissions account       [readable]
+pub fn init_mapping_pda(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _hdr: &cmd_hdr_t,
+) -> OracleResult {
+    let [funding_account, fresh_mapping_account, system_program, permissions_account] =
+        match accounts {
+            [x, y, z, p] => Ok([x, y, z, p]),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_init_mapping_pda,
+    )?;
+    let (_, bump_seed) = get_mapping_address(program_id, None);
+    create_fresh_pda_account::<pc_map_table_t>(
+        program_id,
+        funding_account,
+        fresh_mapping_account,
+        system_program,
+        &[MAPPING_SEED.as_bytes(), &[bump_seed]],
+    )?;
+
+    initialize_pyth_account_checked::<pc_map_table_t>(fresh_mapping_account, PC_VERSION)?;
+
+    emit(&Event::InitMapping(AccountEvent::new(
+        fresh_mapping_account.key,
+    )));
+    Ok(SUCCESS)
+}
+
+/// add a mapping account after the tail mapping account, created at the PDA of the program with
+/// seeds [MAPPING_SEED, tail mapping account]
+/// accounts[0] funding account           [signer writable]
+/// accounts[1] tail mapping account      [writable]
+/// accounts[2] new mapping account       [writable]
+/// accounts[3] system program            [readable]
+/// accounts[4] permissions account       [readable]
+pub fn add_mapping_pda(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _hdr: &cmd_hdr_t,
+) -> OracleResult {
+    let [funding_account, cur_mapping, next_mapping, system_program, permissions_account] =
+        match accounts {
+            [w, x, y, z, p] => Ok([w, x, y, z, p]),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_add_mapping_pda,
+    )?;
+    check_valid_writable_account(program_id, cur_mapping, size_of::<pc_map_table_t>())?;
+    let (_, bump_seed) = get_mapping_address(program_id, Some(cur_mapping.key));
+    create_fresh_pda_account::<pc_map_table_t>(
+        program_id,
+        funding_account,
+        next_mapping,
+        system_program,
+        &[
+            MAPPING_SEED.as_bytes(),
+            cur_mapping.key.as_ref(),
+            &[bump_seed],
+        ],
+    )?;
+
+    append_mapping(cur_mapping, next_mapping)
+}
+
+/// add a product account to a mapping account, created at the PDA of the program with seeds
+/// [PRODUCT_SEED, seed]
+/// accounts[0] funding account           [signer writable]
+/// accounts[1] mapping account           [writable]
+/// accounts[2] new product account       [writable]
+/// accounts[3] system program            [readable]
+/// accounts[4] permissions account       [readable]
+pub fn add_product_pda(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _hdr: &cmd_hdr_t,
+    seed: &[u8],
+) -> OracleResult {
+    let [funding_account, mapping_account, product_account, system_program, permissions_account] =
+        match accounts {
+            [w, x, y, z, p] => Ok([w, x, y, z, p]),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_add_product_pda,
+    )?;
+    check_valid_writable_account(program_id, mapping_account, size_of::<pc_map_table_t>())?;
+    let (_, bump_seed) = get_product_address(program_id, seed);
+    create_fresh_pda_account::<pc_prod_t>(
+        program_id,
+        funding_account,
+        product_account,
+        system_program,
+        &[PRODUCT_SEED.as_bytes(), seed, &[bump_seed]],
+    )?;
+
+    append_product(mapping_account, product_account)
+}
+
+/// add a price account to a product account, created at the PDA of the program with seeds
+/// [PRICE_SEED, product account, price type]
+/// accounts[0] funding account                                   [signer writable]
+/// accounts[1] product account to add the price account to       [writable]
+/// accounts[2] new price account                                 [writable]
+/// accounts[3] system program                                    [readable]
+/// accounts[4] permissions account                               [readable]
+pub fn add_price_pda(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cmd_args: &cmd_add_price_t,
+) -> OracleResult {
+    check_exponent_range(cmd_args.expo_)?;
+    pyth_assert(
+        cmd_args.ptype_ != PC_PTYPE_UNKNOWN,
+        OracleError::InvalidPriceType.into(),
+    )?;
+
+    let [funding_account, product_account, price_account, system_program, permissions_account] =
+        match accounts {
+            [w, x, y, z, p] => Ok([w, x, y, z, p]),
+            _ => Err(OracleError::InvalidNumberOfAccounts),
+        }?;
+
+    check_permissioned_funding_account(
+        program_id,
+        funding_account,
+        permissions_account,
+        command_t_e_cmd_add_price_pda,
+    )?;
+    check_valid_writable_account(program_id, product_account, PC_PROD_ACC_SIZE as usize)?;
+    let (_, bump_seed) = get_price_address(program_id, product_account.key, cmd_args.ptype_);
+    create_fresh_pda_account::<pc_price_t>(
+        program_id,
+        funding_account,
+        price_account,
+        system_program,
+        &[
+            PRICE_SEED.as_bytes(),
+            product_account.key.as_ref(),
+            &cmd_args.ptype_.to_le_bytes(),
+            &[bump_seed],
+        ],
+    )?;
+
+    append_price(product_account, price_account, cmd_args)
+}
@@ -0,0 +1,99 @@
+use std::mem::size_of;
+
+use thiserror::Error;
+
+use crate::c_oracle_header::{
+    pc_prod_t,
+    PC_VERSION,
+};
+use crate::deserialize::read_checked_bytes;
+use crate::utils::{
+    read_pc_str_t,
+    try_convert,
+    write_pc_str_t,
+};
+
+/// The metadata of a product, as key-value pairs in the order they are stored in the account. Keys
+/// are unique.
+pub type ProductMetadata = Vec<(String, String)>;
+
+/// Errors returned when parsing or serializing product metadata. Offsets are relative to the
+/// start of the metadata.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum MetadataError {
+    #[error("The account is not a valid product account")]
+    InvalidAccount,
+    #[error("The string at offset {offset} goes past the end of the metadata")]
+    Truncated { offset: usize },
+    #[error("The key at offset {offset} has no value")]
+    MissingValue { offset: usize },
+    #[error("The string at offset {offset} is not valid UTF-8")]
+    InvalidUtf8 { offset: usize },
+    #[error("The key {key} appears more than once")]
+    DuplicateKey { key: String },
+    #[error("The string of {len} bytes is longer than 255 bytes")]
+    Oversize { len: usize },
+}
+
+/// Parse the metadata stored in `data`, the data of a product account
+pub fn load_product_metadata(data: &[u8]) -> Result<ProductMetadata, MetadataError> {
+    let product = read_checked_bytes::<pc_prod_t>(data, PC_VERSION)
+        .map_err(|_| MetadataError::InvalidAccount)?;
+    let metadata = try_convert::<_, usize>(product.size_)
+        .ok()
+        .and_then(|size| data.get(size_of::<pc_prod_t>()..size))
+        .ok_or(MetadataError::InvalidAccount)?;
+    parse_product_metadata(metadata)
+}
+
+/// Parse `metadata`, the list of key-value `pc_str_t`s stored after the `pc_prod_t` header of a
+/// product account
+pub fn parse_product_metadata(metadata: &[u8]) -> Result<ProductMetadata, MetadataError> {
+    let mut result = ProductMetadata::new();
+    let mut offset = 0;
+    while offset < metadata.len() {
+        let key_offset = offset;
+        let key = parse_string(metadata, &mut offset)?;
+        if offset == metadata.len() {
+            return Err(MetadataError::MissingValue { offset: key_offset });
+        }
+        let value = parse_string(metadata, &mut offset)?;
+        check_unique_key(&result, &key)?;
+        result.push((key, value));
+    }
+    Ok(result)
+}
+
+/// Serialize `metadata` in the format of `parse_product_metadata`, keeping the order of the
+/// key-value pairs, so that the metadata of a product account is serialized back to the same bytes
+pub fn serialize_product_metadata(metadata: &ProductMetadata) -> Result<Vec<u8>, MetadataError> {
+    let mut result = vec![];
+    for (i, (key, _)) in metadata.iter().enumerate() {
+        check_unique_key(&metadata[..i], key)?;
+    }
+    for string in metadata.iter().flat_map(|(key, value)| [key, value]) {
+        write_pc_str_t(&mut result, string.as_bytes())
+            .map_err(|_| MetadataError::Oversize { len: string.len() })?;
+    }
+    Ok(result)
+}
+
+// Fail with `DuplicateKey` if `key` is one of the keys of `metadata`
+fn check_unique_key(metadata: &[(String, String)], key: &str) -> Result<(), MetadataError> {
+    if metadata.iter().any(|(existing_key, _)| existing_key == key) {
+        return Err(MetadataError::DuplicateKey {
+            key: key.to_string(),
+        });
+    }
+    Ok(())
+}
+
+// Parse the `pc_str_t` at `offset` in `metadata` and move `offset` after it
+fn parse_string(metadata: &[u8], offset: &mut usize) -> Result<String, MetadataError> {
+    let string = read_pc_str_t(&metadata[*offset..])
+        .map_err(|_| MetadataError::Truncated { offset: *offset })?;
+    let result = String::from_utf8(string[1..].to_vec())
+        .map_err(|_| MetadataError::InvalidUtf8 { offset: *offset })?;
+    *offset += string.len();
+    Ok(result)
+}
@@ -0,0 +1,133 @@
+//! Accessors for the prices stored in a price account, for programs and off-chain clients that
+//! consume the oracle. They read the raw account data, so integrators don't need to re-implement
+//! the layout of `pc_price_t`. `AccountWalker` enumerates the products and prices listed by a
+//! chain of mapping accounts, and `load_product_metadata` parses the metadata of a product.
+//! `read_publisher_stats` reads the statistics of the publishers of a price account, and
+//! `find_publisher_index` the index to pass in `pub_index_` when a publisher updates its price.
+//! The `read_` functions copy the data and accept the unaligned buffers returned by RPC clients.
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    pc_price_t,
+    PC_STATUS_TRADING,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    load_checked_bytes,
+    read_checked_bytes,
+};
+use crate::time_machine_types::{
+    find_publisher,
+    num_components,
+    PriceAccountWrapper,
+};
+
+mod metadata;
+mod walker;
+pub use crate::time_machine_types::PublisherStats;
+pub use metadata::*;
+pub use walker::*;
+
+/// A price with a confidence interval, both in units of `10^expo`, published at the unix
+/// timestamp `publish_time`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Price {
+    pub price:        i64,
+    pub conf:         u64,
+    pub expo:         i32,
+    pub publish_time: i64,
+}
+
+/// Interpret `data`, the data of a price account, as a `pc_price_t` without copying it. Fails with
+/// `InvalidAccountHeader` or `InvalidAccountVersion` if the header of the account is not the one
+/// of a price account of the current version, and with `InvalidArgument` if `data` is too short or
+/// not 8-byte aligned. The data of the accounts passed to a program always is, off-chain buffers
+/// should be read with `read_price_account`.
+pub fn load_price_account(data: &[u8]) -> Result<&pc_price_t, ProgramError> {
+    load_checked_bytes::<pc_price_t>(data, PC_VERSION)
+}
+
+/// Copy `data`, the data of a price account, into a `pc_price_t`. Fails like
+/// `load_price_account`, except that `data` doesn't need to be aligned.
+pub fn read_price_account(data: &[u8]) -> Result<pc_price_t, ProgramError> {
+    read_checked_bytes::<pc_price_t>(data, PC_VERSION)
+}
+
+/// The statistics of the publishers of `data`, the data of a price account resized with
+/// `resize_price_account`, in the order of the components of the account. Fails like
+/// `read_price_account`, and with `InvalidArgument` if the account was not resized.
+pub fn read_publisher_stats(data: &[u8]) -> Result<Vec<PublisherStats>, ProgramError> {
+    let price_account = read_checked_bytes::<PriceAccountWrapper>(data, PC_VERSION)?;
+    Ok(price_account
+        .publisher_stats
+        .get(..num_components(&price_account.price_data))
+        .ok_or(ProgramError::InvalidArgument)?
+        .to_vec())
+}
+
+/// The index of the component of `publisher` in `data`, the data of a price account, or `None` if
+/// `publisher` doesn't publish to the account. Fails like `read_price_account`. Passing the index
+/// in the `pub_index_` of `cmd_upd_price_t` saves the program from looking the publisher up
+/// among all the components.
+pub fn find_publisher_index(data: &[u8], publisher: &Pubkey) -> Result<Option<u32>, ProgramError> {
+    let price_data = read_price_account(data)?;
+    let extra_components = read_checked_bytes::<PriceAccountWrapper>(data, PC_VERSION)
+        .map(|price_account| price_account.extra_components)
+        .ok();
+    Ok(find_publisher(
+        &price_data,
+        extra_components
+            .as_ref()
+            .map_or(&[], |components| &components[..]),
+        &publisher.to_bytes(),
+    )
+    .map(|index| index as u32))
+}
+
+impl pc_price_t {
+    /// The current aggregate price, or `None` if its status is not TRADING
+    pub fn get_current_price(&self) -> Option<Price> {
+        if self.agg_.status_ != PC_STATUS_TRADING {
+            return None;
+        }
+        Some(Price {
+            price:        self.agg_.price_,
+            conf:         self.agg_.conf_,
+            expo:         self.expo_,
+            publish_time: self.timestamp_,
+        })
+    }
+
+    /// The exponential moving average of the aggregate price and confidence
+    pub fn get_ema_price(&self) -> Price {
+        Price {
+            price:        self.twap_.val_,
+            conf:         self.twac_.val_ as u64,
+            expo:         self.expo_,
+            publish_time: self.timestamp_,
+        }
+    }
+
+    /// The latest aggregate price with status TRADING, if it was published at most `max_age`
+    /// seconds before `clock`. When the current aggregate is not TRADING, this is the previous
+    /// one, so a price that stopped trading is still returned until it becomes too old.
+    pub fn get_price_no_older_than(&self, clock: &Clock, max_age: u64) -> Option<Price> {
+        let price = self.get_current_price().unwrap_or(Price {
+            price:        self.prev_price_,
+            conf:         self.prev_conf_,
+            expo:         self.expo_,
+            publish_time: self.prev_timestamp_,
+        });
+        // A price account that never had a TRADING aggregate has no previous price
+        if price.publish_time == 0 {
+            return None;
+        }
+        let age = clock.unix_timestamp.checked_sub(price.publish_time)?;
+        if age > i64::try_from(max_age).unwrap_or(i64::MAX) {
+            return None;
+        }
+        Some(price)
+    }
+}
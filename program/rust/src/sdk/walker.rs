@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use std::mem::size_of;
+
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;
+
+use crate::c_oracle_header::{
+    pc_map_table_t,
+    pc_price_t,
+    pc_prod_t,
+    PythAccount,
+    PC_ACCTYPE_MAPPING,
+    PC_ACCTYPE_PRODUCT,
+    PC_MAP_TABLE_SIZE,
+    PC_VERSION,
+};
+use crate::deserialize::read_checked_bytes;
+use crate::utils::{
+    pubkey_is_zero,
+    pubkey_to_solana,
+    try_convert,
+};
+
+/// A product account listed in a mapping account
+#[derive(Clone)]
+pub struct ProductAccount {
+    pub key:      Pubkey,
+    pub product:  pc_prod_t,
+    /// The key-value metadata stored after the `pc_prod_t` header
+    pub metadata: Vec<u8>,
+}
+
+/// A price account in the list of prices of a product account
+#[derive(Clone)]
+pub struct PriceAccount {
+    pub key:         Pubkey,
+    pub product_key: Pubkey,
+    pub price:       Box<pc_price_t>,
+}
+
+#[derive(Clone)]
+pub enum WalkItem {
+    Product(ProductAccount),
+    Price(PriceAccount),
+}
+
+/// Errors that stop an `AccountWalker`. `from` is the account that references `key`.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum WalkError {
+    #[error("Account {key} referenced by {from} could not be fetched")]
+    DanglingKey { key: Pubkey, from: Pubkey },
+    #[error("Account {from} references the zero key")]
+    ZeroKey { from: Pubkey },
+    #[error("Account {key} referenced by {from} was already visited")]
+    Cycle { key: Pubkey, from: Pubkey },
+    #[error("Account {key} is not a valid {account_type} account")]
+    InvalidAccount {
+        key:          Pubkey,
+        account_type: &'static str,
+    },
+}
+
+/// Iterator over the accounts reachable from a mapping account. It follows the products of each
+/// mapping account in the `next_` chain, and yields every product followed by the prices in its
+/// `px_acc_` list. Accounts are read with `fetch`, which returns `None` for unknown keys, so that
+/// a local account dump can supply them as well as an RPC node.
+///
+/// The iterator stops after yielding its first error.
+pub struct AccountWalker<F> {
+    fetch:         F,
+    visited:       HashSet<Pubkey>,
+    mapping_key:   Pubkey,
+    mapping:       Box<pc_map_table_t>,
+    product_index: usize,
+    // The next price to visit, with the key of the account that references it
+    next_price:    Option<(Pubkey, Pubkey)>,
+    done:          bool,
+}
+
+impl<F: FnMut(&Pubkey) -> Option<Vec<u8>>> AccountWalker<F> {
+    /// Start from the mapping account `mapping_key`, whose data is `mapping_data`
+    pub fn new(mapping_key: &Pubkey, mapping_data: &[u8], fetch: F) -> Result<Self, WalkError> {
+        Ok(AccountWalker {
+            fetch,
+            visited: HashSet::from([*mapping_key]),
+            mapping_key: *mapping_key,
+            mapping: Box::new(read_account(mapping_key, mapping_data)?),
+            product_index: 0,
+            next_price: None,
+            done: false,
+        })
+    }
+
+    fn fetch_account(&mut self, key: Pubkey, from: Pubkey) -> Result<Vec<u8>, WalkError> {
+        if !self.visited.insert(key) {
+            return Err(WalkError::Cycle { key, from });
+        }
+        (self.fetch)(&key).ok_or(WalkError::DanglingKey { key, from })
+    }
+
+    fn visit_product(&mut self, key: Pubkey) -> Result<WalkItem, WalkError> {
+        let data = self.fetch_account(key, self.mapping_key)?;
+        let product: pc_prod_t = read_account(&key, &data)?;
+        let metadata = try_convert::<_, usize>(product.size_)
+            .ok()
+            .and_then(|size| data.get(size_of::<pc_prod_t>()..size))
+            .ok_or(WalkError::InvalidAccount {
+                key,
+                account_type: account_type::<pc_prod_t>(),
+            })?
+            .to_vec();
+        if !pubkey_is_zero(&product.px_acc_) {
+            self.next_price = Some((pubkey_to_solana(&product.px_acc_), key));
+        }
+        Ok(WalkItem::Product(ProductAccount {
+            key,
+            product,
+            metadata,
+        }))
+    }
+
+    fn visit_price(&mut self, key: Pubkey, from: Pubkey) -> Result<WalkItem, WalkError> {
+        let data = self.fetch_account(key, from)?;
+        let price: Box<pc_price_t> = Box::new(read_account(&key, &data)?);
+        if !pubkey_is_zero(&price.next_) {
+            self.next_price = Some((pubkey_to_solana(&price.next_), key));
+        }
+        Ok(WalkItem::Price(PriceAccount {
+            key,
+            product_key: pubkey_to_solana(&price.prod_),
+            price,
+        }))
+    }
+
+    fn next_item(&mut self) -> Option<Result<WalkItem, WalkError>> {
+        loop {
+            if let Some((key, from)) = self.next_price.take() {
+                return Some(self.visit_price(key, from));
+            }
+
+            let num_products = self.mapping.num_ as usize;
+            if num_products > PC_MAP_TABLE_SIZE as usize {
+                return Some(Err(WalkError::InvalidAccount {
+                    key:          self.mapping_key,
+                    account_type: account_type::<pc_map_table_t>(),
+                }));
+            }
+            if self.product_index < num_products {
+                let key = &self.mapping.prod_[self.product_index];
+                self.product_index += 1;
+                if pubkey_is_zero(key) {
+                    return Some(Err(WalkError::ZeroKey {
+                        from: self.mapping_key,
+                    }));
+                }
+                let key = pubkey_to_solana(key);
+                return Some(self.visit_product(key));
+            }
+
+            if pubkey_is_zero(&self.mapping.next_) {
+                return None;
+            }
+            let key = pubkey_to_solana(&self.mapping.next_);
+            let mapping = self
+                .fetch_account(key, self.mapping_key)
+                .and_then(|data| read_account(&key, &data));
+            match mapping {
+                Err(error) => return Some(Err(error)),
+                Ok(mapping) => {
+                    self.mapping_key = key;
+                    *self.mapping = mapping;
+                    self.product_index = 0;
+                }
+            }
+        }
+    }
+}
+
+impl<F: FnMut(&Pubkey) -> Option<Vec<u8>>> Iterator for AccountWalker<F> {
+    type Item = Result<WalkItem, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let item = self.next_item();
+        self.done = !matches!(item, Some(Ok(_)));
+        item
+    }
+}
+
+fn read_account<T: PythAccount>(key: &Pubkey, data: &[u8]) -> Result<T, WalkError> {
+    read_checked_bytes::<T>(data, PC_VERSION).map_err(|_| WalkError::InvalidAccount {
+        key:          *key,
+        account_type: account_type::<T>(),
+    })
+}
+
+fn account_type<T: PythAccount>() -> &'static str {
+    match T::ACCOUNT_TYPE {
+        PC_ACCTYPE_MAPPING => "mapping",
+        PC_ACCTYPE_PRODUCT => "product",
+        _ => "price",
+    }
+}
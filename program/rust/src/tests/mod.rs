@@ -2,12 +2,39 @@ mod test_add_mapping;
 mod test_add_price;
 mod test_add_product;
 mod test_add_publisher;
+mod test_create_account;
+mod test_del_price;
+mod test_del_product;
 mod test_del_publisher;
+mod test_events;
+mod test_extra_components;
 mod test_init_mapping;
 mod test_init_price;
+mod test_instruction;
+mod test_instruction_builders;
+mod test_migrate_account;
+mod test_pda_accounts;
+mod test_permissions;
+mod test_product_attr;
+mod test_product_metadata;
+mod test_publisher_index;
+mod test_publisher_stats;
+mod test_query_time_machine;
+mod test_resize_product_account;
+mod test_sdk;
+mod test_set_max_ci_divisor;
+mod test_set_max_latency;
 mod test_set_min_pub;
+mod test_sma;
+mod test_tick_tracker;
 mod test_upd_aggregate;
+#[cfg(feature = "test-c-aggregate")]
+mod test_upd_aggregate_c;
 mod test_upd_price;
+mod test_upd_price_batch;
+#[cfg(feature = "test-bpf")]
+mod test_upd_price_compute_units;
 mod test_upd_price_no_fail_on_error;
 mod test_upd_product;
 mod test_utils;
+mod test_walker;
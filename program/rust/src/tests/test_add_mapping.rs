@@ -12,16 +12,18 @@ use crate::deserialize::{
     load_checked,
 };
 
+use crate::error::OracleError;
 use crate::rust_oracle::add_mapping;
-use crate::tests::test_utils::AccountSetup;
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
 use crate::utils::{
     clear_account,
     pubkey_assign,
     pubkey_equal,
     pubkey_is_zero,
 };
-use bytemuck::bytes_of;
-use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 
 #[test]
@@ -30,13 +32,16 @@ fn test_add_mapping() {
         ver_: PC_VERSION,
         cmd_: command_t_e_cmd_add_mapping as i32,
     };
-    let instruction_data = bytes_of::<cmd_hdr_t>(&hdr);
 
     let program_id = Pubkey::new_unique();
 
     let mut funding_setup = AccountSetup::new_funding();
     let funding_account = funding_setup.to_account_info();
 
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
     let mut curr_mapping_setup = AccountSetup::new::<pc_map_table_t>(&program_id);
     let cur_mapping = curr_mapping_setup.to_account_info();
     initialize_pyth_account_checked::<pc_map_table_t>(&cur_mapping, PC_VERSION).unwrap();
@@ -55,9 +60,10 @@ fn test_add_mapping() {
         &[
             funding_account.clone(),
             cur_mapping.clone(),
-            next_mapping.clone()
+            next_mapping.clone(),
+            permissions_account.clone()
         ],
-        instruction_data
+        &hdr
     )
     .is_ok());
 
@@ -83,11 +89,12 @@ fn test_add_mapping() {
             &[
                 funding_account.clone(),
                 cur_mapping.clone(),
-                next_mapping.clone()
+                next_mapping.clone(),
+                permissions_account.clone()
             ],
-            instruction_data
+            &hdr
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::InvalidMappingTail.into())
     );
 
     {
@@ -104,11 +111,12 @@ fn test_add_mapping() {
             &[
                 funding_account.clone(),
                 cur_mapping.clone(),
-                next_mapping.clone()
+                next_mapping.clone(),
+                permissions_account.clone()
             ],
-            instruction_data
+            &hdr
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::InvalidAccountHeader.into())
     );
 
     {
@@ -121,9 +129,10 @@ fn test_add_mapping() {
         &[
             funding_account.clone(),
             cur_mapping.clone(),
-            next_mapping.clone()
+            next_mapping.clone(),
+            permissions_account.clone()
         ],
-        instruction_data
+        &hdr
     )
     .is_ok());
 }
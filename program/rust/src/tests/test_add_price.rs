@@ -1,7 +1,8 @@
 use crate::error::OracleError;
-use crate::tests::test_utils::AccountSetup;
-use bytemuck::bytes_of;
-use solana_program::program_error::ProgramError;
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
 use solana_program::pubkey::Pubkey;
 
 use crate::c_oracle_header::{
@@ -41,14 +42,16 @@ fn test_add_price() {
         expo_:  1,
         ptype_: 1,
     };
-    let instruction_data_add_product = bytes_of::<cmd_hdr_t>(&hdr_add_product);
-    let mut instruction_data_add_price = bytes_of::<cmd_add_price>(&hdr_add_price);
 
     let program_id = Pubkey::new_unique();
 
     let mut funding_setup = AccountSetup::new_funding();
     let funding_account = funding_setup.to_account_info();
 
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
     let mut mapping_setup = AccountSetup::new::<pc_map_table_t>(&program_id);
     let mapping_account = mapping_setup.to_account_info();
     initialize_pyth_account_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
@@ -67,9 +70,10 @@ fn test_add_price() {
         &[
             funding_account.clone(),
             mapping_account.clone(),
-            product_account.clone()
+            product_account.clone(),
+            permissions_account.clone()
         ],
-        instruction_data_add_product
+        &hdr_add_product
     )
     .is_ok());
 
@@ -78,9 +82,10 @@ fn test_add_price() {
         &[
             funding_account.clone(),
             product_account.clone(),
-            price_account.clone()
+            price_account.clone(),
+            permissions_account.clone()
         ],
-        instruction_data_add_price
+        &hdr_add_price
     )
     .is_ok());
 
@@ -105,9 +110,10 @@ fn test_add_price() {
         &[
             funding_account.clone(),
             product_account.clone(),
-            price_account_2.clone()
+            price_account_2.clone(),
+            permissions_account.clone()
         ],
-        instruction_data_add_price
+        &hdr_add_price
     )
     .is_ok());
 
@@ -134,10 +140,14 @@ fn test_add_price() {
     assert_eq!(
         add_price(
             &program_id,
-            &[funding_account.clone(), product_account.clone()],
-            instruction_data_add_price
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_add_price
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::InvalidNumberOfAccounts.into())
     );
 
     // Price account is already initialized
@@ -147,9 +157,10 @@ fn test_add_price() {
             &[
                 funding_account.clone(),
                 product_account.clone(),
-                price_account.clone()
+                price_account.clone(),
+                permissions_account.clone()
             ],
-            instruction_data_add_price
+            &hdr_add_price
         ),
         Err(OracleError::InvalidFreshAccount.into())
     );
@@ -163,7 +174,6 @@ fn test_add_price() {
         expo_:  6,
         ptype_: 0,
     };
-    instruction_data_add_price = bytes_of::<cmd_add_price>(&hdr_add_price);
 
 
     assert_eq!(
@@ -172,11 +182,12 @@ fn test_add_price() {
             &[
                 funding_account.clone(),
                 product_account.clone(),
-                price_account.clone()
+                price_account.clone(),
+                permissions_account.clone()
             ],
-            instruction_data_add_price
+            &hdr_add_price
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::InvalidPriceType.into())
     );
 
 
@@ -187,7 +198,6 @@ fn test_add_price() {
         expo_:  6,
         ptype_: 1,
     };
-    instruction_data_add_price = bytes_of::<cmd_add_price>(&hdr_add_price);
     price_account.is_signer = false;
 
     assert_eq!(
@@ -196,9 +206,10 @@ fn test_add_price() {
             &[
                 funding_account.clone(),
                 product_account.clone(),
-                price_account.clone()
+                price_account.clone(),
+                permissions_account.clone()
             ],
-            instruction_data_add_price
+            &hdr_add_price
         ),
         Err(OracleError::InvalidSignableAccount.into())
     );
@@ -214,10 +225,11 @@ fn test_add_price() {
             &[
                 funding_account.clone(),
                 product_account.clone(),
-                price_account.clone()
+                price_account.clone(),
+                permissions_account.clone()
             ],
-            instruction_data_add_price
+            &hdr_add_price
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::InvalidAccountHeader.into())
     );
 }
@@ -1,11 +1,12 @@
 use std::mem::size_of;
 
 use crate::error::OracleError;
-use crate::tests::test_utils::AccountSetup;
-use bytemuck::bytes_of;
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
 use solana_program::account_info::AccountInfo;
 use solana_program::clock::Epoch;
-use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
 
@@ -39,13 +40,16 @@ fn test_add_product() {
         ver_: PC_VERSION,
         cmd_: command_t_e_cmd_add_product as i32,
     };
-    let instruction_data = bytes_of::<cmd_hdr_t>(&hdr);
 
     let program_id = Pubkey::new_unique();
 
     let mut funding_setup = AccountSetup::new_funding();
     let funding_account = funding_setup.to_account_info();
 
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
     let mut mapping_setup = AccountSetup::new::<pc_map_table_t>(&program_id);
     let mapping_account = mapping_setup.to_account_info();
     initialize_pyth_account_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
@@ -61,9 +65,10 @@ fn test_add_product() {
         &[
             funding_account.clone(),
             mapping_account.clone(),
-            product_account.clone()
+            product_account.clone(),
+            permissions_account.clone()
         ],
-        instruction_data
+        &hdr
     )
     .is_ok());
 
@@ -88,9 +93,10 @@ fn test_add_product() {
         &[
             funding_account.clone(),
             mapping_account.clone(),
-            product_account_2.clone()
+            product_account_2.clone(),
+            permissions_account.clone()
         ],
-        instruction_data
+        &hdr
     )
     .is_ok());
     {
@@ -123,9 +129,10 @@ fn test_add_product() {
             &[
                 funding_account.clone(),
                 mapping_account.clone(),
-                product_account_3.clone()
+                product_account_3.clone(),
+                permissions_account.clone()
             ],
-            instruction_data
+            &hdr
         ),
         Err(OracleError::InvalidSignableAccount.into())
     );
@@ -142,9 +149,10 @@ fn test_add_product() {
             &[
                 funding_account.clone(),
                 mapping_account.clone(),
-                product_account.clone()
+                product_account.clone(),
+                permissions_account.clone()
             ],
-            instruction_data
+            &hdr
         )
         .is_ok());
         let mapping_data = load_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
@@ -163,11 +171,12 @@ fn test_add_product() {
             &[
                 funding_account.clone(),
                 mapping_account.clone(),
-                product_account.clone()
+                product_account.clone(),
+                permissions_account.clone()
             ],
-            instruction_data
+            &hdr
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::MappingAccountFull.into())
     );
 
     let mapping_data = load_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
@@ -1,6 +1,8 @@
-use crate::tests::test_utils::AccountSetup;
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
 use bytemuck::bytes_of;
-use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
 
@@ -37,11 +39,14 @@ fn test_add_publisher() {
         cmd_: command_t_e_cmd_add_product as i32,
         pub_: publisher,
     };
-    let mut instruction_data = bytes_of::<cmd_add_publisher>(&cmd);
 
     let mut funding_setup = AccountSetup::new_funding();
     let funding_account = funding_setup.to_account_info();
 
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
     let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
     let price_account = price_setup.to_account_info();
     initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
@@ -53,10 +58,14 @@ fn test_add_publisher() {
     assert_eq!(
         add_publisher(
             &program_id,
-            &[funding_account.clone(), price_account.clone(),],
-            instruction_data
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &cmd
         ),
-        Err(OracleError::InvalidSignableAccount.into())
+        Err(OracleError::InvalidWritableAccount.into())
     );
 
     // Now give the price account enough lamports to be rent exempt
@@ -66,8 +75,12 @@ fn test_add_publisher() {
 
     assert!(add_publisher(
         &program_id,
-        &[funding_account.clone(), price_account.clone(),],
-        instruction_data
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &cmd
     )
     .is_ok());
 
@@ -88,10 +101,14 @@ fn test_add_publisher() {
     assert_eq!(
         add_publisher(
             &program_id,
-            &[funding_account.clone(), price_account.clone(),],
-            instruction_data
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &cmd
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::DuplicatePublisher.into())
     );
 
     clear_account(&price_account).unwrap();
@@ -100,10 +117,14 @@ fn test_add_publisher() {
     assert_eq!(
         add_publisher(
             &program_id,
-            &[funding_account.clone(), price_account.clone(),],
-            instruction_data
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &cmd
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::InvalidAccountHeader.into())
     );
 
     initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
@@ -111,11 +132,14 @@ fn test_add_publisher() {
     //Fill up price node
     for i in 0..PC_COMP_SIZE {
         cmd.pub_ = pc_pub_key_t::new_unique();
-        instruction_data = bytes_of::<cmd_add_publisher>(&cmd);
         assert!(add_publisher(
             &program_id,
-            &[funding_account.clone(), price_account.clone(),],
-            instruction_data
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &cmd
         )
         .is_ok());
 
@@ -135,13 +159,16 @@ fn test_add_publisher() {
     }
 
     cmd.pub_ = pc_pub_key_t::new_unique();
-    instruction_data = bytes_of::<cmd_add_publisher>(&cmd);
     assert_eq!(
         add_publisher(
             &program_id,
-            &[funding_account.clone(), price_account.clone(),],
-            instruction_data
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &cmd
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::ComponentTableFull.into())
     );
 }
@@ -0,0 +1,211 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Epoch;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+
+use crate::c_oracle_header::{
+    cmd_add_price_t,
+    cmd_hdr_t,
+    command_t_e_cmd_add_mapping,
+    command_t_e_cmd_add_price,
+    command_t_e_cmd_add_product,
+    command_t_e_cmd_init_mapping,
+    pc_map_table_t,
+    pc_price_t,
+    pc_prod_t,
+    PC_PTYPE_PRICE,
+    PC_VERSION,
+};
+use crate::deserialize::initialize_pyth_account_checked;
+use crate::error::OracleError;
+use crate::instruction::{
+    self,
+    get_permissions_address,
+    OracleInstruction,
+};
+use crate::rust_oracle::{
+    add_mapping,
+    add_price,
+    add_product,
+    init_mapping,
+};
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
+
+#[test]
+fn test_create_account() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let system_program_key = system_program::id();
+    let mut system_program_balance = 0;
+    let system_program_account = AccountInfo::new(
+        &system_program_key,
+        false,
+        false,
+        &mut system_program_balance,
+        &mut [],
+        &system_program_key,
+        true,
+        Epoch::default(),
+    );
+
+    let mut mapping_setup = AccountSetup::new::<pc_map_table_t>(&program_id);
+    let mapping_account = mapping_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
+
+    let mut product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account = product_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+
+    // An account that already has data, e.g. created by the client, can't be created again
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+
+    let add_price_cmd = cmd_add_price_t {
+        ver_:   PC_VERSION,
+        cmd_:   command_t_e_cmd_add_price as i32,
+        expo_:  -8,
+        ptype_: PC_PTYPE_PRICE,
+    };
+    assert_eq!(
+        add_price(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                price_account.clone(),
+                system_program_account.clone(),
+                permissions_account.clone()
+            ],
+            &add_price_cmd
+        ),
+        Err(OracleError::InvalidFreshAccount.into())
+    );
+    assert_eq!(
+        add_product(
+            &program_id,
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                price_account.clone(),
+                system_program_account.clone(),
+                permissions_account.clone()
+            ],
+            &populate_hdr(command_t_e_cmd_add_product)
+        ),
+        Err(OracleError::InvalidFreshAccount.into())
+    );
+    assert_eq!(
+        add_mapping(
+            &program_id,
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                price_account.clone(),
+                system_program_account.clone(),
+                permissions_account.clone()
+            ],
+            &populate_hdr(command_t_e_cmd_add_mapping)
+        ),
+        Err(OracleError::InvalidFreshAccount.into())
+    );
+
+    // The account before the permissions account must be the system program
+    assert_eq!(
+        init_mapping(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                funding_account.clone(),
+                permissions_account.clone()
+            ],
+            &populate_hdr(command_t_e_cmd_init_mapping)
+        ),
+        Err(OracleError::InvalidSystemAccount.into())
+    );
+
+    // Without the system program, the account created by the client is used
+    assert!(add_price(
+        &program_id,
+        &[
+            funding_account.clone(),
+            product_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &add_price_cmd
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_create_account_builders() {
+    let program_id = Pubkey::new_unique();
+    let funding = Pubkey::new_unique();
+    let mapping = Pubkey::new_unique();
+    let product = Pubkey::new_unique();
+    let price = Pubkey::new_unique();
+    let permissions = get_permissions_address(&program_id).0;
+
+    let add_price =
+        instruction::create_and_add_price(&program_id, &funding, &product, &price, -8, 1);
+    assert_eq!(
+        metas(&add_price),
+        [
+            (funding, true, true),
+            (product, false, true),
+            (price, true, true),
+            (system_program::id(), false, false),
+            (permissions, false, false)
+        ]
+    );
+    assert!(matches!(
+        OracleInstruction::unpack(&add_price.data),
+        Ok(OracleInstruction::AddPrice(_))
+    ));
+
+    let init_mapping = instruction::create_and_init_mapping(&program_id, &funding, &mapping);
+    assert_eq!(
+        metas(&init_mapping),
+        [
+            (funding, true, true),
+            (mapping, true, true),
+            (system_program::id(), false, false),
+            (permissions, false, false)
+        ]
+    );
+
+    for instruction in [
+        instruction::create_and_add_mapping(&program_id, &funding, &mapping, &price),
+        instruction::create_and_add_product(&program_id, &funding, &mapping, &product),
+    ] {
+        assert_eq!(instruction.accounts.len(), 5);
+        assert_eq!(instruction.accounts[3].pubkey, system_program::id());
+        assert_eq!(instruction.accounts[4].pubkey, permissions);
+    }
+}
+
+fn populate_hdr(cmd: u32) -> cmd_hdr_t {
+    cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: cmd as i32,
+    }
+}
+
+fn metas(instruction: &solana_program::instruction::Instruction) -> Vec<(Pubkey, bool, bool)> {
+    instruction
+        .accounts
+        .iter()
+        .map(|meta| (meta.pubkey, meta.is_signer, meta.is_writable))
+        .collect()
+}
@@ -0,0 +1,336 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    cmd_add_price,
+    cmd_hdr_t,
+    command_t_e_cmd_add_price,
+    command_t_e_cmd_add_product,
+    command_t_e_cmd_del_price,
+    pc_map_table_t,
+    pc_price_t,
+    pc_prod_t,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::rust_oracle::{
+    add_price,
+    add_product,
+    del_price,
+};
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
+use crate::utils::{
+    pubkey_equal,
+    pubkey_is_zero,
+};
+
+#[test]
+fn test_del_price() {
+    let hdr_add_product = cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_add_product as i32,
+    };
+    let hdr_add_price = cmd_add_price {
+        ver_:   PC_VERSION,
+        cmd_:   command_t_e_cmd_add_price as i32,
+        expo_:  1,
+        ptype_: 1,
+    };
+    let hdr_del_price = cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_del_price as i32,
+    };
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut mapping_setup = AccountSetup::new::<pc_map_table_t>(&program_id);
+    let mapping_account = mapping_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
+
+    let mut product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account = product_setup.to_account_info();
+
+    let mut price_setup_1 = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account_1 = price_setup_1.to_account_info();
+    let mut price_setup_2 = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account_2 = price_setup_2.to_account_info();
+    let mut price_setup_3 = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account_3 = price_setup_3.to_account_info();
+
+    assert!(add_product(
+        &program_id,
+        &[
+            funding_account.clone(),
+            mapping_account.clone(),
+            product_account.clone(),
+            permissions_account.clone()
+        ],
+        &hdr_add_product
+    )
+    .is_ok());
+
+    // The product's list of price accounts is 3 -> 2 -> 1
+    for price_account in [&price_account_1, &price_account_2, &price_account_3] {
+        assert!(add_price(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_add_price
+        )
+        .is_ok());
+    }
+
+    // Price accounts with publishers can't be deleted
+    load_checked::<pc_price_t>(&price_account_3, PC_VERSION)
+        .unwrap()
+        .num_ = 1;
+    assert_eq!(
+        del_price(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                price_account_3.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_del_price
+        ),
+        Err(OracleError::PriceAccountHasPublishers.into())
+    );
+    load_checked::<pc_price_t>(&price_account_3, PC_VERSION)
+        .unwrap()
+        .num_ = 0;
+
+    // Price account 2 isn't the first of the list, the previous price account is required
+    assert_eq!(
+        del_price(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                price_account_2.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_del_price
+        ),
+        Err(OracleError::PriceAccountNotLinked.into())
+    );
+
+    // Price account 1 doesn't point to price account 2
+    assert_eq!(
+        del_price(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                price_account_2.clone(),
+                price_account_1.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_del_price
+        ),
+        Err(OracleError::PriceAccountNotLinked.into())
+    );
+
+    let funding_lamports = funding_account.lamports();
+    let price_lamports = price_account_2.lamports();
+    assert!(del_price(
+        &program_id,
+        &[
+            funding_account.clone(),
+            product_account.clone(),
+            price_account_2.clone(),
+            price_account_3.clone(),
+            permissions_account.clone()
+        ],
+        &hdr_del_price
+    )
+    .is_ok());
+
+    assert_eq!(price_account_2.lamports(), 0);
+    assert_eq!(
+        funding_account.lamports(),
+        funding_lamports + price_lamports
+    );
+    assert!(price_account_2
+        .try_borrow_data()
+        .unwrap()
+        .iter()
+        .all(|x| *x == 0));
+    {
+        let price_data_3 = load_checked::<pc_price_t>(&price_account_3, PC_VERSION).unwrap();
+        let product_data = load_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+        assert!(pubkey_equal(
+            &price_data_3.next_,
+            &price_account_1.key.to_bytes()
+        ));
+        assert!(pubkey_equal(
+            &product_data.px_acc_,
+            &price_account_3.key.to_bytes()
+        ));
+    }
+
+    // Delete the first price account of the list
+    assert!(del_price(
+        &program_id,
+        &[
+            funding_account.clone(),
+            product_account.clone(),
+            price_account_3.clone(),
+            permissions_account.clone()
+        ],
+        &hdr_del_price
+    )
+    .is_ok());
+    {
+        let product_data = load_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+        assert!(pubkey_equal(
+            &product_data.px_acc_,
+            &price_account_1.key.to_bytes()
+        ));
+    }
+
+    // Delete the last one
+    assert!(del_price(
+        &program_id,
+        &[
+            funding_account.clone(),
+            product_account.clone(),
+            price_account_1.clone(),
+            permissions_account.clone()
+        ],
+        &hdr_del_price
+    )
+    .is_ok());
+    {
+        let product_data = load_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+        assert!(pubkey_is_zero(&product_data.px_acc_));
+    }
+    assert_eq!(
+        funding_account.lamports(),
+        funding_lamports + 3 * price_lamports
+    );
+
+    // The price account is gone
+    assert!(del_price(
+        &program_id,
+        &[
+            funding_account.clone(),
+            product_account.clone(),
+            price_account_1.clone(),
+            permissions_account.clone()
+        ],
+        &hdr_del_price
+    )
+    .is_err());
+}
+
+#[test]
+fn test_del_price_wrong_product() {
+    let hdr_add_product = cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_add_product as i32,
+    };
+    let hdr_add_price = cmd_add_price {
+        ver_:   PC_VERSION,
+        cmd_:   command_t_e_cmd_add_price as i32,
+        expo_:  1,
+        ptype_: 1,
+    };
+    let hdr_del_price = cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_del_price as i32,
+    };
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut mapping_setup = AccountSetup::new::<pc_map_table_t>(&program_id);
+    let mapping_account = mapping_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
+
+    let mut product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account = product_setup.to_account_info();
+    let mut other_product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let other_product_account = other_product_setup.to_account_info();
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+
+    for product_account in [&product_account, &other_product_account] {
+        assert!(add_product(
+            &program_id,
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                product_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_add_product
+        )
+        .is_ok());
+    }
+
+    assert!(add_price(
+        &program_id,
+        &[
+            funding_account.clone(),
+            product_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &hdr_add_price
+    )
+    .is_ok());
+
+    assert_eq!(
+        del_price(
+            &program_id,
+            &[
+                funding_account.clone(),
+                other_product_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_del_price
+        ),
+        Err(OracleError::PriceAccountNotLinked.into())
+    );
+
+    // Wrong number of accounts
+    assert_eq!(
+        del_price(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_del_price
+        ),
+        Err(OracleError::InvalidNumberOfAccounts.into())
+    );
+}
@@ -0,0 +1,214 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    cmd_hdr_t,
+    command_t_e_cmd_add_product,
+    command_t_e_cmd_del_product,
+    pc_map_table_t,
+    pc_prod_t,
+    PythAccount,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::rust_oracle::{
+    add_product,
+    del_product,
+};
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
+use crate::utils::{
+    pubkey_assign,
+    pubkey_equal,
+    pubkey_is_zero,
+};
+
+#[test]
+fn test_del_product() {
+    let hdr_add_product = cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_add_product as i32,
+    };
+    let hdr_del_product = cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_del_product as i32,
+    };
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut mapping_setup = AccountSetup::new::<pc_map_table_t>(&program_id);
+    let mapping_account = mapping_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
+
+    let mut product_setup_1 = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account_1 = product_setup_1.to_account_info();
+    let mut product_setup_2 = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account_2 = product_setup_2.to_account_info();
+    let mut product_setup_3 = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account_3 = product_setup_3.to_account_info();
+
+    for product_account in [&product_account_1, &product_account_2, &product_account_3] {
+        assert!(add_product(
+            &program_id,
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                product_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_add_product
+        )
+        .is_ok());
+    }
+
+    // Products with price accounts can't be deleted
+    pubkey_assign(
+        &mut load_checked::<pc_prod_t>(&product_account_1, PC_VERSION)
+            .unwrap()
+            .px_acc_,
+        &Pubkey::new_unique().to_bytes(),
+    );
+    assert_eq!(
+        del_product(
+            &program_id,
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                product_account_1.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_del_product
+        ),
+        Err(OracleError::ProductAccountHasPrices.into())
+    );
+    pubkey_assign(
+        &mut load_checked::<pc_prod_t>(&product_account_1, PC_VERSION)
+            .unwrap()
+            .px_acc_,
+        &Pubkey::default().to_bytes(),
+    );
+
+    // The last product takes the place of the deleted one
+    let funding_lamports = funding_account.lamports();
+    let product_lamports = product_account_1.lamports();
+    assert!(del_product(
+        &program_id,
+        &[
+            funding_account.clone(),
+            mapping_account.clone(),
+            product_account_1.clone(),
+            permissions_account.clone()
+        ],
+        &hdr_del_product
+    )
+    .is_ok());
+
+    assert_eq!(product_account_1.lamports(), 0);
+    assert_eq!(
+        funding_account.lamports(),
+        funding_lamports + product_lamports
+    );
+    assert!(product_account_1
+        .try_borrow_data()
+        .unwrap()
+        .iter()
+        .all(|x| *x == 0));
+    {
+        let mapping_data = load_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
+        assert_eq!(mapping_data.num_, 2);
+        assert_eq!(mapping_data.size_, pc_map_table_t::INITIAL_SIZE + 2 * 32);
+        assert!(pubkey_equal(
+            &mapping_data.prod_[0],
+            &product_account_3.key.to_bytes()
+        ));
+        assert!(pubkey_equal(
+            &mapping_data.prod_[1],
+            &product_account_2.key.to_bytes()
+        ));
+        assert!(pubkey_is_zero(&mapping_data.prod_[2]));
+    }
+
+    // The product isn't in the mapping
+    let mut other_product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let other_product_account = other_product_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_prod_t>(&other_product_account, PC_VERSION).unwrap();
+    assert_eq!(
+        del_product(
+            &program_id,
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                other_product_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_del_product
+        ),
+        Err(OracleError::ProductNotInMapping.into())
+    );
+
+    // Delete the last product of the mapping
+    assert!(del_product(
+        &program_id,
+        &[
+            funding_account.clone(),
+            mapping_account.clone(),
+            product_account_2.clone(),
+            permissions_account.clone()
+        ],
+        &hdr_del_product
+    )
+    .is_ok());
+    {
+        let mapping_data = load_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
+        assert_eq!(mapping_data.num_, 1);
+        assert_eq!(mapping_data.size_, pc_map_table_t::INITIAL_SIZE + 32);
+        assert!(pubkey_equal(
+            &mapping_data.prod_[0],
+            &product_account_3.key.to_bytes()
+        ));
+        assert!(pubkey_is_zero(&mapping_data.prod_[1]));
+    }
+
+    assert!(del_product(
+        &program_id,
+        &[
+            funding_account.clone(),
+            mapping_account.clone(),
+            product_account_3.clone(),
+            permissions_account.clone()
+        ],
+        &hdr_del_product
+    )
+    .is_ok());
+    {
+        let mapping_data = load_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
+        assert_eq!(mapping_data.num_, 0);
+        assert_eq!(mapping_data.size_, pc_map_table_t::INITIAL_SIZE);
+    }
+
+    // Wrong number of accounts
+    assert_eq!(
+        del_product(
+            &program_id,
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_del_product
+        ),
+        Err(OracleError::InvalidNumberOfAccounts.into())
+    );
+}
@@ -1,10 +1,12 @@
 use crate::deserialize::{
     initialize_pyth_account_checked,
     load_checked,
-    load_mut,
 };
 use crate::rust_oracle::del_publisher;
-use crate::tests::test_utils::AccountSetup;
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
 use bytemuck::bytes_of;
 use solana_program::pubkey::Pubkey;
 
@@ -48,15 +50,19 @@ fn test_del_publisher() {
     let publisher = pc_pub_key_t::new_unique();
     let publisher2 = pc_pub_key_t::new_unique();
 
-    let mut instruction_data = [0u8; size_of::<cmd_del_publisher>()];
-    let mut hdr = load_mut::<cmd_del_publisher>(&mut instruction_data).unwrap();
-    hdr.ver_ = PC_VERSION;
-    hdr.cmd_ = command_t_e_cmd_del_publisher as i32;
-    hdr.pub_ = publisher;
+    let cmd = cmd_del_publisher {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_del_publisher as i32,
+        pub_: publisher,
+    };
 
     let mut funding_setup = AccountSetup::new_funding();
     let funding_account = funding_setup.to_account_info();
 
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
     let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
     let price_account = price_setup.to_account_info();
     initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
@@ -69,8 +75,12 @@ fn test_del_publisher() {
 
     assert!(del_publisher(
         &program_id,
-        &[funding_account.clone(), price_account.clone(),],
-        &instruction_data
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &cmd
     )
     .is_ok());
 
@@ -95,8 +105,12 @@ fn test_del_publisher() {
     // Delete publisher at position 0
     assert!(del_publisher(
         &program_id,
-        &[funding_account.clone(), price_account.clone(),],
-        &instruction_data
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &cmd
     )
     .is_ok());
 
@@ -136,8 +150,12 @@ fn test_del_publisher() {
     // Delete publisher at position 1
     assert!(del_publisher(
         &program_id,
-        &[funding_account.clone(), price_account.clone(),],
-        &instruction_data
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &cmd
     )
     .is_ok());
 
@@ -0,0 +1,290 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    cmd_add_publisher_t,
+    cmd_upd_price_batch_entry_t,
+    cmd_upd_price_batch_t,
+    cmd_upd_price_t,
+    command_t_e_cmd_add_publisher,
+    command_t_e_cmd_upd_price_batch,
+    command_t_e_cmd_upd_price_no_fail_on_error,
+    pc_price_t,
+    pc_pub_key_t,
+    PC_STATUS_TRADING,
+    PC_STATUS_UNKNOWN,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::events::{
+    aggregate_event,
+    AccountEvent,
+    Event,
+    EventError,
+    PublisherEvent,
+    UpdateAggregateEvent,
+    UpdatePriceEvent,
+    EVENT_SCHEMA_VERSION,
+};
+use crate::rust_oracle::{
+    add_publisher,
+    upd_price_batch,
+    upd_price_no_fail_on_error,
+};
+use crate::tests::test_utils::{
+    set_master_authority,
+    take_emitted_events,
+    update_clock_slot,
+    AccountSetup,
+};
+use crate::utils::pubkey_assign;
+
+#[test]
+fn test_upd_price_events() {
+    let program_id = Pubkey::new_unique();
+    take_emitted_events();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut price_setup_1 = AccountSetup::new::<pc_price_t>(&program_id);
+    let mut price_account_1 = price_setup_1.to_account_info();
+    price_account_1.is_signer = false;
+    let mut price_setup_2 = AccountSetup::new::<pc_price_t>(&program_id);
+    let mut price_account_2 = price_setup_2.to_account_info();
+    price_account_2.is_signer = false;
+    for price_account in [&price_account_1, &price_account_2] {
+        initialize_pyth_account_checked::<pc_price_t>(price_account, PC_VERSION).unwrap();
+        let mut price_data = load_checked::<pc_price_t>(price_account, PC_VERSION).unwrap();
+        // The aggregate is up to date, so that only the component prices change
+        price_data.agg_.pub_slot_ = 1;
+        price_data.num_ = 1;
+        pubkey_assign(
+            &mut price_data.comp_[0].pub_,
+            &funding_account.key.to_bytes(),
+        );
+    }
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.to_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+    update_clock_slot(&mut clock_account, 1);
+
+    let accounts = [
+        funding_account.clone(),
+        price_account_1.clone(),
+        clock_account.clone(),
+    ];
+
+    // The event has the price as it is stored, with the confidence interval too wide
+    assert!(
+        upd_price_no_fail_on_error(&program_id, &accounts, &populate_upd_price(100, 50, 1)).is_ok()
+    );
+    let expected = UpdatePriceEvent {
+        publisher:       funding_account.key.to_bytes(),
+        price_account:   price_account_1.key.to_bytes(),
+        price:           100,
+        conf:            50,
+        pub_slot:        1,
+        status:          PC_STATUS_UNKNOWN,
+        corp_act_status: 0,
+    };
+    assert_eq!(take_emitted_events(), [Event::UpdatePrice(expected)]);
+
+    // A failed update is skipped without any event
+    assert!(
+        upd_price_no_fail_on_error(&program_id, &accounts, &populate_upd_price(100, 1, 1)).is_ok()
+    );
+    assert_eq!(
+        load_checked::<pc_price_t>(&price_account_1, PC_VERSION)
+            .unwrap()
+            .comp_[0]
+            .latest_
+            .conf_,
+        50
+    );
+    assert_eq!(take_emitted_events(), []);
+
+    let mut unknown_publisher_setup = AccountSetup::new_funding();
+    let unknown_publisher = unknown_publisher_setup.to_account_info();
+    assert!(upd_price_no_fail_on_error(
+        &program_id,
+        &[
+            unknown_publisher,
+            price_account_1.clone(),
+            clock_account.clone()
+        ],
+        &populate_upd_price(100, 1, 2)
+    )
+    .is_ok());
+    assert_eq!(take_emitted_events(), []);
+
+    // A batch only has the events of the entries that were applied
+    let entry = |price, pub_slot| cmd_upd_price_batch_entry_t {
+        status_:    PC_STATUS_TRADING,
+        pub_index_: 0,
+        price_:     price,
+        conf_:      1,
+        pub_slot_:  pub_slot,
+    };
+    assert!(upd_price_batch(
+        &program_id,
+        &[
+            funding_account.clone(),
+            clock_account.clone(),
+            price_account_1.clone(),
+            price_account_2.clone()
+        ],
+        &cmd_upd_price_batch_t {
+            ver_:              PC_VERSION,
+            cmd_:              command_t_e_cmd_upd_price_batch as i32,
+            no_fail_on_error_: 1,
+            num_:              2,
+        },
+        &[entry(42, 1), entry(43, 2)]
+    )
+    .is_ok());
+    assert_eq!(
+        take_emitted_events(),
+        [Event::UpdatePrice(UpdatePriceEvent {
+            price_account: price_account_2.key.to_bytes(),
+            price: 43,
+            conf: 1,
+            pub_slot: 2,
+            status: PC_STATUS_TRADING,
+            ..expected
+        })]
+    );
+}
+
+#[test]
+fn test_admin_events() {
+    let program_id = Pubkey::new_unique();
+    take_emitted_events();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+
+    let publisher = Pubkey::new_unique();
+    let cmd = cmd_add_publisher_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_add_publisher as i32,
+        pub_: pc_pub_key_t {
+            k1_: publisher.to_bytes(),
+        },
+    };
+    let accounts = [
+        funding_account.clone(),
+        price_account.clone(),
+        permissions_account.clone(),
+    ];
+    assert!(add_publisher(&program_id, &accounts, &cmd).is_ok());
+    assert_eq!(
+        take_emitted_events(),
+        [Event::AddPublisher(PublisherEvent {
+            price_account: price_account.key.to_bytes(),
+            publisher:     publisher.to_bytes(),
+        })]
+    );
+
+    // An instruction that fails has no event
+    assert_eq!(
+        add_publisher(&program_id, &accounts, &cmd),
+        Err(OracleError::DuplicatePublisher.into())
+    );
+    assert_eq!(take_emitted_events(), []);
+}
+
+#[test]
+fn test_encode_decode() {
+    let price_account = Pubkey::new_unique();
+    let mut price_data: pc_price_t = bytemuck::Zeroable::zeroed();
+    price_data.expo_ = -5;
+    price_data.agg_.price_ = 100;
+    price_data.agg_.conf_ = 2;
+    price_data.agg_.status_ = PC_STATUS_TRADING;
+    price_data.agg_.pub_slot_ = 10;
+    price_data.twap_.val_ = 90;
+    price_data.twac_.val_ = 3;
+    price_data.timestamp_ = 1000;
+
+    let event = aggregate_event(&price_account, &price_data);
+    assert_eq!(
+        event,
+        Event::UpdateAggregate(UpdateAggregateEvent {
+            price_account: price_account.to_bytes(),
+            price:         100,
+            conf:          2,
+            twap:          90,
+            twac:          3,
+            pub_slot:      10,
+            solana_time:   1000,
+            expo:          -5,
+            status:        PC_STATUS_TRADING,
+        })
+    );
+
+    let record = event.encode();
+    assert_eq!(record[0..4], EVENT_SCHEMA_VERSION.to_le_bytes());
+    assert_eq!(record[4..8], 2u32.to_le_bytes());
+    assert_eq!(Event::decode(&record), Ok(event));
+
+    let event = Event::ResizePriceAccount(AccountEvent {
+        account: price_account.to_bytes(),
+    });
+    let mut record = event.encode();
+    assert_eq!(Event::decode(&record), Ok(event));
+
+    assert_eq!(
+        Event::decode(&record[..record.len() - 1]),
+        Err(EventError::InvalidLength)
+    );
+    assert_eq!(Event::decode(&record[..4]), Err(EventError::InvalidLength));
+
+    record[4..8].copy_from_slice(&1000u32.to_le_bytes());
+    assert_eq!(Event::decode(&record), Err(EventError::UnknownKind(1000)));
+
+    record[0..4].copy_from_slice(&(EVENT_SCHEMA_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        Event::decode(&record),
+        Err(EventError::UnsupportedVersion(EVENT_SCHEMA_VERSION + 1))
+    );
+}
+
+#[cfg(feature = "client")]
+#[test]
+fn test_decode_log() {
+    let event = Event::InitMapping(AccountEvent {
+        account: Pubkey::new_unique().to_bytes(),
+    });
+    let log = format!("Program data: {}", base64::encode(event.encode()));
+    assert_eq!(Event::decode_log(&log), Ok(event));
+    assert_eq!(
+        Event::decode_log("Program log: Pyth oracle contract"),
+        Err(EventError::InvalidLog)
+    );
+}
+
+fn populate_upd_price(price: i64, conf: u64, pub_slot: u64) -> cmd_upd_price_t {
+    cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price_no_fail_on_error as i32,
+        status_:    PC_STATUS_TRADING,
+        pub_index_: 0,
+        price_:     price,
+        conf_:      conf,
+        pub_slot_:  pub_slot,
+    }
+}
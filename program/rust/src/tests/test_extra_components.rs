@@ -0,0 +1,280 @@
+use bytemuck::bytes_of;
+use solana_program::account_info::AccountInfo;
+use solana_program::pubkey::Pubkey;
+use std::mem::size_of;
+
+use crate::c_oracle_header::{
+    cmd_add_publisher_t,
+    cmd_del_publisher_t,
+    cmd_upd_price_t,
+    command_t_e_cmd_add_publisher,
+    command_t_e_cmd_del_publisher,
+    command_t_e_cmd_upd_price,
+    pc_price_t,
+    pc_pub_key_t,
+    PC_COMP_SIZE,
+    PC_EXTRA_COMP_SIZE,
+    PC_STATUS_TRADING,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::rust_oracle::{
+    add_publisher,
+    del_publisher,
+    upd_price,
+};
+use crate::tests::test_utils::{
+    set_master_authority,
+    update_clock_slot,
+    AccountSetup,
+};
+use crate::time_machine_types::{
+    PriceAccountWrapper,
+    MAX_NUM_COMPONENTS,
+};
+use crate::utils::pubkey_equal;
+
+#[test]
+fn test_extra_components() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut price_setup = AccountSetup::new::<PriceAccountWrapper>(&program_id);
+    let mut price_account = price_setup.to_account_info();
+    initialize_pyth_account_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+    load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION)
+        .unwrap()
+        .initialize_time_machine()
+        .unwrap();
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.to_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+
+    let mut publisher_setups: Vec<AccountSetup> = (0..MAX_NUM_COMPONENTS)
+        .map(|_| AccountSetup::new_funding())
+        .collect();
+    let publishers: Vec<AccountInfo> = publisher_setups
+        .iter_mut()
+        .map(|setup| setup.to_account_info())
+        .collect();
+
+    let admin_accounts = [
+        funding_account.clone(),
+        price_account.clone(),
+        permissions_account.clone(),
+    ];
+
+    // A resized account has room for PC_EXTRA_COMP_SIZE more publishers
+    for publisher in publishers.iter() {
+        assert!(add_publisher(
+            &program_id,
+            &admin_accounts,
+            &populate_add_publisher(publisher.key)
+        )
+        .is_ok());
+    }
+    // The table is full, an existing publisher can't be added again either
+    assert_eq!(
+        add_publisher(
+            &program_id,
+            &admin_accounts,
+            &populate_add_publisher(publishers[MAX_NUM_COMPONENTS - 1].key)
+        ),
+        Err(OracleError::ComponentTableFull.into())
+    );
+    assert_eq!(
+        add_publisher(
+            &program_id,
+            &admin_accounts,
+            &populate_add_publisher(&Pubkey::new_unique())
+        ),
+        Err(OracleError::ComponentTableFull.into())
+    );
+    {
+        let price_data = load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        // num_ only counts the components of comp_, for the readers that don't know the extra ones
+        assert_eq!(price_data.price_data.num_, PC_COMP_SIZE);
+        assert_eq!(price_data.price_data.num_extra_, PC_EXTRA_COMP_SIZE);
+        assert_eq!(
+            price_data.price_data.size_ as usize,
+            size_of::<pc_price_t>()
+        );
+        assert!(pubkey_equal(
+            &price_data.extra_components[PC_EXTRA_COMP_SIZE as usize - 1].pub_,
+            &publishers[MAX_NUM_COMPONENTS - 1].key.to_bytes()
+        ));
+    }
+
+    // The publishers of the extra components publish and are aggregated like the others
+    price_account.is_signer = false;
+    update_clock_slot(&mut clock_account, 1);
+    for (i, publisher) in publishers.iter().enumerate() {
+        let price = if i < PC_COMP_SIZE as usize { 100 } else { 200 };
+        assert!(upd_price(
+            &program_id,
+            &[
+                publisher.clone(),
+                price_account.clone(),
+                clock_account.clone()
+            ],
+            &populate_upd_price(price, 1)
+        )
+        .is_ok());
+    }
+    update_clock_slot(&mut clock_account, 2);
+    assert!(upd_price(
+        &program_id,
+        &[
+            publishers[0].clone(),
+            price_account.clone(),
+            clock_account.clone()
+        ],
+        &populate_upd_price(100, 2)
+    )
+    .is_ok());
+    {
+        let price_data = load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.price_data.num_qt_ as usize, MAX_NUM_COMPONENTS);
+        assert_eq!(price_data.price_data.agg_.status_, PC_STATUS_TRADING);
+        assert_eq!(price_data.price_data.agg_.price_, 100);
+        assert_eq!(price_data.extra_components[0].agg_.price_, 200);
+        assert_eq!(
+            price_data.publisher_stats[MAX_NUM_COMPONENTS - 1].num_aggregated,
+            1
+        );
+    }
+
+    // Deleting a publisher moves the first extra component to the end of comp_
+    assert!(del_publisher(
+        &program_id,
+        &admin_accounts,
+        &populate_del_publisher(publishers[0].key)
+    )
+    .is_ok());
+    {
+        let price_data = load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.price_data.num_, PC_COMP_SIZE);
+        assert_eq!(price_data.price_data.num_extra_, PC_EXTRA_COMP_SIZE - 1);
+        assert!(pubkey_equal(
+            &price_data.price_data.comp_[PC_COMP_SIZE as usize - 1].pub_,
+            &publishers[PC_COMP_SIZE as usize].key.to_bytes()
+        ));
+        assert_eq!(
+            price_data.price_data.comp_[PC_COMP_SIZE as usize - 1]
+                .latest_
+                .price_,
+            200
+        );
+        assert!(
+            bytes_of(&price_data.extra_components[PC_EXTRA_COMP_SIZE as usize - 1])
+                .iter()
+                .all(|byte| *byte == 0)
+        );
+    }
+    assert!(del_publisher(
+        &program_id,
+        &admin_accounts,
+        &populate_del_publisher(publishers[MAX_NUM_COMPONENTS - 1].key)
+    )
+    .is_ok());
+    assert_eq!(
+        add_publisher(
+            &program_id,
+            &admin_accounts,
+            &populate_add_publisher(publishers[MAX_NUM_COMPONENTS - 2].key)
+        ),
+        Err(OracleError::DuplicatePublisher.into())
+    );
+    assert_eq!(
+        del_publisher(
+            &program_id,
+            &admin_accounts,
+            &populate_del_publisher(publishers[MAX_NUM_COMPONENTS - 1].key)
+        ),
+        Err(OracleError::UnknownPublisher.into())
+    );
+}
+
+#[test]
+fn test_extra_components_not_resized() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+
+    let admin_accounts = [
+        funding_account.clone(),
+        price_account.clone(),
+        permissions_account.clone(),
+    ];
+
+    // An account without the extra publisher space still has PC_COMP_SIZE components
+    for _ in 0..PC_COMP_SIZE {
+        assert!(add_publisher(
+            &program_id,
+            &admin_accounts,
+            &populate_add_publisher(&Pubkey::new_unique())
+        )
+        .is_ok());
+    }
+    assert_eq!(
+        add_publisher(
+            &program_id,
+            &admin_accounts,
+            &populate_add_publisher(&Pubkey::new_unique())
+        ),
+        Err(OracleError::ComponentTableFull.into())
+    );
+}
+
+fn populate_add_publisher(publisher: &Pubkey) -> cmd_add_publisher_t {
+    cmd_add_publisher_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_add_publisher as i32,
+        pub_: pc_pub_key_t {
+            k1_: publisher.to_bytes(),
+        },
+    }
+}
+
+fn populate_del_publisher(publisher: &Pubkey) -> cmd_del_publisher_t {
+    cmd_del_publisher_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_del_publisher as i32,
+        pub_: pc_pub_key_t {
+            k1_: publisher.to_bytes(),
+        },
+    }
+}
+
+fn populate_upd_price(price: i64, pub_slot: u64) -> cmd_upd_price_t {
+    cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price as i32,
+        status_:    PC_STATUS_TRADING,
+        price_:     price,
+        conf_:      1,
+        pub_slot_:  pub_slot,
+        pub_index_: 0,
+    }
+}
@@ -9,10 +9,11 @@ use crate::c_oracle_header::{
 use crate::deserialize::load_account_as;
 use crate::error::OracleError;
 use crate::rust_oracle::init_mapping;
-use crate::tests::test_utils::AccountSetup;
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
 use crate::utils::clear_account;
-use bytemuck::bytes_of;
-use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -23,7 +24,6 @@ fn test_init_mapping() {
         ver_: PC_VERSION,
         cmd_: command_t_e_cmd_init_mapping as i32,
     };
-    let instruction_data = bytes_of::<cmd_hdr_t>(&hdr);
 
     let program_id = Pubkey::new_unique();
     let program_id_2 = Pubkey::new_unique();
@@ -31,13 +31,21 @@ fn test_init_mapping() {
     let mut funding_setup = AccountSetup::new_funding();
     let mut funding_account = funding_setup.to_account_info();
 
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
     let mut mapping_setup = AccountSetup::new::<pc_map_table_t>(&program_id);
     let mut mapping_account = mapping_setup.to_account_info();
 
     assert!(init_mapping(
         &program_id,
-        &[funding_account.clone(), mapping_account.clone()],
-        instruction_data
+        &[
+            funding_account.clone(),
+            mapping_account.clone(),
+            permissions_account.clone()
+        ],
+        &hdr
     )
     .is_ok());
 
@@ -53,8 +61,12 @@ fn test_init_mapping() {
     assert_eq!(
         init_mapping(
             &program_id,
-            &[funding_account.clone(), mapping_account.clone()],
-            instruction_data
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr
         ),
         Err(OracleError::InvalidFreshAccount.into())
     );
@@ -62,8 +74,12 @@ fn test_init_mapping() {
     clear_account(&mapping_account).unwrap();
 
     assert_eq!(
-        init_mapping(&program_id, &[funding_account.clone()], instruction_data),
-        Err(ProgramError::InvalidArgument)
+        init_mapping(
+            &program_id,
+            &[funding_account.clone(), permissions_account.clone()],
+            &hdr
+        ),
+        Err(OracleError::InvalidNumberOfAccounts.into())
     );
 
     funding_account.is_signer = false;
@@ -71,8 +87,12 @@ fn test_init_mapping() {
     assert_eq!(
         init_mapping(
             &program_id,
-            &[funding_account.clone(), mapping_account.clone()],
-            instruction_data
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr
         ),
         Err(OracleError::InvalidFundingAccount.into())
     );
@@ -83,8 +103,12 @@ fn test_init_mapping() {
     assert_eq!(
         init_mapping(
             &program_id,
-            &[funding_account.clone(), mapping_account.clone()],
-            instruction_data
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr
         ),
         Err(OracleError::InvalidSignableAccount.into())
     );
@@ -95,8 +119,12 @@ fn test_init_mapping() {
     assert_eq!(
         init_mapping(
             &program_id,
-            &[funding_account.clone(), mapping_account.clone()],
-            instruction_data
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr
         ),
         Err(OracleError::InvalidFundingAccount.into())
     );
@@ -107,8 +135,12 @@ fn test_init_mapping() {
     assert_eq!(
         init_mapping(
             &program_id,
-            &[funding_account.clone(), mapping_account.clone()],
-            instruction_data
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr
         ),
         Err(OracleError::InvalidSignableAccount.into())
     );
@@ -119,8 +151,12 @@ fn test_init_mapping() {
     assert_eq!(
         init_mapping(
             &program_id,
-            &[funding_account.clone(), mapping_account.clone()],
-            instruction_data
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr
         ),
         Err(OracleError::InvalidSignableAccount.into())
     );
@@ -132,8 +168,12 @@ fn test_init_mapping() {
     assert_eq!(
         init_mapping(
             &program_id,
-            &[funding_account.clone(), mapping_account.clone()],
-            instruction_data
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr
         ),
         Err(OracleError::InvalidSignableAccount.into())
     );
@@ -142,8 +182,12 @@ fn test_init_mapping() {
 
     assert!(init_mapping(
         &program_id,
-        &[funding_account.clone(), mapping_account.clone()],
-        instruction_data
+        &[
+            funding_account.clone(),
+            mapping_account.clone(),
+            permissions_account.clone()
+        ],
+        &hdr
     )
     .is_ok());
 }
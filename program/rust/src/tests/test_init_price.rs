@@ -1,5 +1,4 @@
 use bytemuck::bytes_of;
-use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 
 use crate::c_oracle_header::{
@@ -15,7 +14,10 @@ use crate::deserialize::{
     load_checked,
 };
 use crate::rust_oracle::init_price;
-use crate::tests::test_utils::AccountSetup;
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
 use crate::utils::{
     pubkey_assign,
     pubkey_equal,
@@ -33,7 +35,6 @@ fn test_init_price() {
         ptype_: ptype,
     };
 
-    let instruction_data = bytes_of::<cmd_init_price_t>(&cmd);
 
     let program_id = Pubkey::new_unique();
     let publisher = pc_pub_key_t::new_unique();
@@ -44,6 +45,10 @@ fn test_init_price() {
     let mut funding_setup = AccountSetup::new_funding();
     let funding_account = funding_setup.to_account_info();
 
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
     let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
     let mut price_account = price_setup.to_account_info();
 
@@ -51,10 +56,14 @@ fn test_init_price() {
     assert_eq!(
         init_price(
             &program_id,
-            &[funding_account.clone(), price_account.clone()],
-            instruction_data
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &cmd
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::InvalidAccountHeader.into())
     );
 
     initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
@@ -94,8 +103,12 @@ fn test_init_price() {
 
     assert!(init_price(
         &program_id,
-        &[funding_account.clone(), price_account.clone()],
-        instruction_data
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &cmd
     )
     .is_ok());
 
@@ -140,30 +153,37 @@ fn test_init_price() {
         assert_eq!(price_data.comp_[num_components - 1].latest_.price_, 0);
     }
 
-    price_account.is_signer = false;
+    price_account.is_writable = false;
     assert_eq!(
         init_price(
             &program_id,
-            &[funding_account.clone(), price_account.clone()],
-            instruction_data
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &cmd
         ),
-        Err(OracleError::InvalidSignableAccount.into())
+        Err(OracleError::InvalidWritableAccount.into())
     );
 
-    price_account.is_signer = true;
+    price_account.is_writable = true;
     let cmd: cmd_init_price_t = cmd_init_price_t {
         ver_:   PC_VERSION,
         cmd_:   command_t_e_cmd_init_price as i32,
         expo_:  -(PC_MAX_NUM_DECIMALS as i32) - 1,
         ptype_: ptype,
     };
-    let instruction_data = bytes_of::<cmd_init_price_t>(&cmd);
     assert_eq!(
         init_price(
             &program_id,
-            &[funding_account.clone(), price_account.clone()],
-            instruction_data
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &cmd
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::InvalidExponent.into())
     );
 }
@@ -0,0 +1,364 @@
+use std::mem::size_of;
+
+use bytemuck::bytes_of;
+use solana_program::program_error::ProgramError;
+
+use crate::c_oracle_header::{
+    cmd_add_publisher_t,
+    cmd_hdr_t,
+    cmd_upd_price_batch_entry_t,
+    cmd_upd_price_batch_t,
+    cmd_upd_price_t,
+    command_t_e_cmd_add_publisher,
+    command_t_e_cmd_agg_price,
+    command_t_e_cmd_del_product_attr,
+    command_t_e_cmd_init_mapping,
+    command_t_e_cmd_set_product_attr,
+    command_t_e_cmd_upd_price,
+    command_t_e_cmd_upd_price_batch,
+    command_t_e_cmd_upd_product,
+    command_t_e_cmd_upd_test,
+    pc_pub_key_t,
+    PC_STATUS_TRADING,
+    PC_VERSION,
+};
+use crate::error::OracleError;
+use crate::instruction::OracleInstruction;
+
+#[test]
+fn test_unpack_fixed_size() {
+    let cmd = cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price as i32,
+        status_:    PC_STATUS_TRADING,
+        pub_index_: 0,
+        price_:     42,
+        conf_:      2,
+        pub_slot_:  1,
+    };
+    let instruction_data = bytes_of(&cmd).to_vec();
+    match OracleInstruction::unpack(&instruction_data).unwrap() {
+        OracleInstruction::UpdPrice(unpacked) => {
+            assert_eq!(bytes_of(&unpacked), bytes_of(&cmd))
+        }
+        _ => panic!("expected UpdPrice"),
+    }
+
+    // The instruction data doesn't need to be aligned
+    let mut unaligned = vec![0u8; instruction_data.len() + 1];
+    unaligned[1..].copy_from_slice(&instruction_data);
+    assert!(matches!(
+        OracleInstruction::unpack(&unaligned[1..]),
+        Ok(OracleInstruction::UpdPrice(_))
+    ));
+
+    // Trailing bytes are ignored by the commands that always ignored them
+    let mut trailing = instruction_data.clone();
+    trailing.push(0);
+    assert!(OracleInstruction::unpack(&trailing).is_ok());
+
+    // But the data has to contain the whole command
+    assert_eq!(
+        OracleInstruction::unpack(&instruction_data[..size_of::<cmd_upd_price_t>() - 1]).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        OracleInstruction::unpack(&[]).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+
+    // Commands with an exact size reject trailing bytes
+    let cmd = cmd_add_publisher_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_add_publisher as i32,
+        pub_: pc_pub_key_t::new_unique(),
+    };
+    let mut instruction_data = bytes_of(&cmd).to_vec();
+    assert!(matches!(
+        OracleInstruction::unpack(&instruction_data),
+        Ok(OracleInstruction::AddPublisher(_))
+    ));
+    instruction_data.push(0);
+    assert_eq!(
+        OracleInstruction::unpack(&instruction_data).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn test_unpack_unrecognized() {
+    for cmd in [command_t_e_cmd_upd_test as i32, 1000] {
+        let hdr = cmd_hdr_t {
+            ver_: PC_VERSION,
+            cmd_: cmd,
+        };
+        assert_eq!(
+            OracleInstruction::unpack(bytes_of(&hdr)).err(),
+            Some(OracleError::UnrecognizedInstruction.into())
+        );
+    }
+}
+
+#[test]
+fn test_unpack_upd_product() {
+    let mut instruction_data = populate_instruction(
+        command_t_e_cmd_upd_product,
+        &["symbol", "BTC/USD", "description", ""],
+    );
+    match OracleInstruction::unpack(&instruction_data).unwrap() {
+        OracleInstruction::UpdProduct { attributes, .. } => assert_eq!(
+            attributes,
+            [
+                (b"symbol".to_vec(), b"BTC/USD".to_vec()),
+                (b"description".to_vec(), vec![])
+            ]
+        ),
+        _ => panic!("expected UpdProduct"),
+    }
+
+    // No attributes
+    assert!(matches!(
+        OracleInstruction::unpack(&populate_instruction(command_t_e_cmd_upd_product, &[])),
+        Ok(OracleInstruction::UpdProduct { attributes, .. }) if attributes.is_empty()
+    ));
+
+    // Uneven number of keys and values
+    assert_eq!(
+        OracleInstruction::unpack(&populate_instruction(
+            command_t_e_cmd_upd_product,
+            &["symbol", "BTC/USD", "base"]
+        ))
+        .err(),
+        Some(ProgramError::InvalidArgument)
+    );
+
+    // Bad size on the first string
+    instruction_data[size_of::<cmd_hdr_t>()] = 200;
+    assert_eq!(
+        OracleInstruction::unpack(&instruction_data).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn test_unpack_product_attr() {
+    match OracleInstruction::unpack(&populate_instruction(
+        command_t_e_cmd_set_product_attr,
+        &["symbol", "BTC/USD"],
+    ))
+    .unwrap()
+    {
+        OracleInstruction::SetProductAttr { key, value, .. } => {
+            assert_eq!(key, b"symbol");
+            assert_eq!(value, b"BTC/USD");
+        }
+        _ => panic!("expected SetProductAttr"),
+    }
+
+    // Exactly one key-value pair is allowed
+    for strings in [&["symbol"][..], &["symbol", "BTC/USD", "base", "BTC"], &[]] {
+        assert_eq!(
+            OracleInstruction::unpack(&populate_instruction(
+                command_t_e_cmd_set_product_attr,
+                strings
+            ))
+            .err(),
+            Some(ProgramError::InvalidArgument)
+        );
+    }
+
+    match OracleInstruction::unpack(&populate_instruction(
+        command_t_e_cmd_del_product_attr,
+        &["symbol"],
+    ))
+    .unwrap()
+    {
+        OracleInstruction::DelProductAttr { key, .. } => assert_eq!(key, b"symbol"),
+        _ => panic!("expected DelProductAttr"),
+    }
+
+    // Exactly one key is allowed
+    for strings in [&["symbol", "base"][..], &[]] {
+        assert_eq!(
+            OracleInstruction::unpack(&populate_instruction(
+                command_t_e_cmd_del_product_attr,
+                strings
+            ))
+            .err(),
+            Some(ProgramError::InvalidArgument)
+        );
+    }
+}
+
+#[test]
+fn test_unpack_upd_price_batch() {
+    let cmd = cmd_upd_price_batch_t {
+        ver_:              PC_VERSION,
+        cmd_:              command_t_e_cmd_upd_price_batch as i32,
+        no_fail_on_error_: 1,
+        num_:              2,
+    };
+    let entry = cmd_upd_price_batch_entry_t {
+        status_:    PC_STATUS_TRADING,
+        pub_index_: 0,
+        price_:     42,
+        conf_:      2,
+        pub_slot_:  1,
+    };
+    let mut instruction_data = bytes_of(&cmd).to_vec();
+    instruction_data.extend_from_slice(bytes_of(&entry));
+    instruction_data.extend_from_slice(bytes_of(&entry));
+
+    match OracleInstruction::unpack(&instruction_data).unwrap() {
+        OracleInstruction::UpdPriceBatch { cmd, entries } => {
+            assert_eq!(cmd.no_fail_on_error_, 1);
+            assert_eq!(entries.len(), 2);
+            assert_eq!(bytes_of(&entries[1]), bytes_of(&entry));
+        }
+        _ => panic!("expected UpdPriceBatch"),
+    }
+
+    // The instruction data has to contain exactly num_ entries
+    instruction_data.pop();
+    assert_eq!(
+        OracleInstruction::unpack(&instruction_data).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+    instruction_data.extend_from_slice(&[0; 1 + size_of::<cmd_upd_price_batch_entry_t>()]);
+    assert_eq!(
+        OracleInstruction::unpack(&instruction_data).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn test_pack() {
+    let instructions = [
+        OracleInstruction::InitMapping(cmd_hdr_t {
+            ver_: PC_VERSION,
+            cmd_: command_t_e_cmd_init_mapping as i32,
+        }),
+        OracleInstruction::AddPublisher(cmd_add_publisher_t {
+            ver_: PC_VERSION,
+            cmd_: command_t_e_cmd_add_publisher as i32,
+            pub_: pc_pub_key_t::new_unique(),
+        }),
+        OracleInstruction::UpdProduct {
+            hdr:        cmd_hdr_t {
+                ver_: PC_VERSION,
+                cmd_: command_t_e_cmd_upd_product as i32,
+            },
+            attributes: vec![
+                (b"symbol".to_vec(), b"BTC/USD".to_vec()),
+                (b"base".to_vec(), b"BTC".to_vec()),
+            ],
+        },
+        OracleInstruction::UpdPriceBatch {
+            cmd:     cmd_upd_price_batch_t {
+                ver_:              PC_VERSION,
+                cmd_:              command_t_e_cmd_upd_price_batch as i32,
+                no_fail_on_error_: 0,
+                num_:              1,
+            },
+            entries: vec![cmd_upd_price_batch_entry_t {
+                status_:    PC_STATUS_TRADING,
+                pub_index_: 0,
+                price_:     42,
+                conf_:      2,
+                pub_slot_:  1,
+            }],
+        },
+        OracleInstruction::DelProductAttr {
+            hdr: cmd_hdr_t {
+                ver_: PC_VERSION,
+                cmd_: command_t_e_cmd_del_product_attr as i32,
+            },
+            key: b"symbol".to_vec(),
+        },
+    ];
+
+    // Packing and unpacking gives back the same instruction
+    for instruction in instructions {
+        let instruction_data = instruction.pack().unwrap();
+        let unpacked = OracleInstruction::unpack(&instruction_data).unwrap();
+        assert_eq!(unpacked.command(), instruction.command());
+        assert_eq!(unpacked.pack().unwrap(), instruction_data);
+    }
+
+    assert_eq!(
+        OracleInstruction::SetProductAttr {
+            hdr:   cmd_hdr_t {
+                ver_: PC_VERSION,
+                cmd_: command_t_e_cmd_set_product_attr as i32,
+            },
+            key:   b"symbol".to_vec(),
+            value: b"BTC/USD".to_vec(),
+        }
+        .pack()
+        .unwrap(),
+        populate_instruction(command_t_e_cmd_set_product_attr, &["symbol", "BTC/USD"])
+    );
+
+    // The command is the one of the variant
+    let cmd = cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price as i32,
+        status_:    PC_STATUS_TRADING,
+        pub_index_: 0,
+        price_:     42,
+        conf_:      2,
+        pub_slot_:  1,
+    };
+    let instruction_data = OracleInstruction::AggPrice(cmd).pack().unwrap();
+    assert!(matches!(
+        OracleInstruction::unpack(&instruction_data),
+        Ok(OracleInstruction::AggPrice(cmd_upd_price_t { price_: 42, cmd_, .. }))
+            if cmd_ == command_t_e_cmd_agg_price as i32
+    ));
+
+    // The number of entries of a batch is the length of the list
+    let instruction_data = OracleInstruction::UpdPriceBatch {
+        cmd:     cmd_upd_price_batch_t {
+            ver_:              PC_VERSION,
+            cmd_:              command_t_e_cmd_upd_price_batch as i32,
+            no_fail_on_error_: 0,
+            num_:              5,
+        },
+        entries: vec![],
+    }
+    .pack()
+    .unwrap();
+    assert_eq!(instruction_data.len(), size_of::<cmd_upd_price_batch_t>());
+    assert!(matches!(
+        OracleInstruction::unpack(&instruction_data),
+        Ok(OracleInstruction::UpdPriceBatch { cmd, entries }) if cmd.num_ == 0 && entries.is_empty()
+    ));
+
+    // Strings are limited to 255 bytes
+    assert_eq!(
+        OracleInstruction::DelProductAttr {
+            hdr: cmd_hdr_t {
+                ver_: PC_VERSION,
+                cmd_: command_t_e_cmd_del_product_attr as i32,
+            },
+            key: vec![b'x'; 256],
+        }
+        .pack()
+        .err(),
+        Some(OracleError::IntegerCastingError.into())
+    );
+}
+
+// Create an instruction with the given command followed by strings
+fn populate_instruction(cmd: u32, strings: &[&str]) -> Vec<u8> {
+    let hdr = cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: cmd as i32,
+    };
+    let mut instruction_data = bytes_of(&hdr).to_vec();
+    for s in strings {
+        instruction_data.push(s.len() as u8);
+        instruction_data.extend_from_slice(s.as_bytes());
+    }
+    instruction_data
+}
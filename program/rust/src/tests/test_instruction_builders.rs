@@ -0,0 +1,397 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Epoch;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::clock;
+use solana_program::{
+    bpf_loader_upgradeable,
+    system_program,
+};
+
+use crate::c_oracle_header::{
+    pc_map_table_t,
+    pc_price_t,
+    pc_prod_t,
+    PC_PTYPE_PRICE,
+    PC_STATUS_TRADING,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    load_account_as,
+    load_checked,
+};
+use crate::error::{
+    OracleError,
+    OracleResult,
+};
+use crate::instruction::{
+    self,
+    OracleInstruction,
+};
+use crate::processor::process_instruction;
+use crate::tests::test_utils::{
+    update_clock_slot,
+    AccountSetup,
+};
+use crate::utils::{
+    get_permissions_address,
+    pubkey_equal,
+};
+
+#[test]
+fn test_instruction_builders() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut programdata_setup = AccountSetup::new_programdata(&program_id, funding_account.key);
+    let programdata_account = programdata_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+
+    let system_program_key = system_program::id();
+    let mut system_program_balance = 0;
+    let system_program_account = AccountInfo::new(
+        &system_program_key,
+        false,
+        false,
+        &mut system_program_balance,
+        &mut [],
+        &system_program_key,
+        true,
+        Epoch::default(),
+    );
+
+    let mut mapping_setup = AccountSetup::new::<pc_map_table_t>(&program_id);
+    let mapping_account = mapping_setup.to_account_info();
+
+    let mut product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account = product_setup.to_account_info();
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.to_account_info();
+    update_clock_slot(&mut clock_account, 1);
+
+    let accounts = [
+        funding_account.clone(),
+        mapping_account.clone(),
+        product_account.clone(),
+        price_account.clone(),
+        clock_account.clone(),
+        programdata_account.clone(),
+        permissions_account.clone(),
+        system_program_account.clone(),
+    ];
+    let funding = funding_account.key;
+    let mapping = mapping_account.key;
+    let product = product_account.key;
+    let price = price_account.key;
+
+    // Instructions for another version of the program are rejected
+    let mut init_mapping = instruction::init_mapping(&program_id, funding, mapping);
+    init_mapping.data[0..4].copy_from_slice(&(PC_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        process(&init_mapping, &accounts),
+        Err(OracleError::InvalidInstructionVersion.into())
+    );
+
+    // Admin instructions need the permissions account to be set up by the upgrade authority
+    assert_eq!(
+        process(
+            &instruction::init_mapping(&program_id, funding, mapping),
+            &accounts
+        ),
+        Err(OracleError::InvalidAccountHeader.into())
+    );
+    let other = Pubkey::new_unique();
+    assert!(process(
+        &instruction::upd_permissions(&program_id, funding, funding, &other, &other),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::init_mapping(&program_id, funding, mapping),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::add_product(&program_id, funding, mapping, product),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::upd_product(
+            &program_id,
+            funding,
+            product,
+            &[(b"symbol".to_vec(), b"BTC/USD".to_vec())]
+        )
+        .unwrap(),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::set_product_attr(&program_id, funding, product, b"base", b"BTC").unwrap(),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::del_product_attr(&program_id, funding, product, b"symbol").unwrap(),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::add_price(&program_id, funding, product, price, -8, PC_PTYPE_PRICE),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::init_price(&program_id, funding, price, -6, PC_PTYPE_PRICE),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::set_min_pub(&program_id, funding, price, 1),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::set_max_latency(&program_id, funding, price, 10),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::set_max_ci_divisor(&program_id, funding, price, 10),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::add_publisher(&program_id, funding, price, funding),
+        &accounts
+    )
+    .is_ok());
+    {
+        let price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.expo_, -6);
+        assert_eq!(price_data.min_pub_, 1);
+        assert_eq!(price_data.max_latency_, 10);
+        assert_eq!(price_data.max_ci_divisor_, 10);
+        assert_eq!(price_data.num_, 1);
+        assert!(pubkey_equal(&price_data.comp_[0].pub_, &funding.to_bytes()));
+    }
+
+    assert!(process(
+        &instruction::upd_price(&program_id, funding, price, 0, PC_STATUS_TRADING, 42, 2, 1),
+        &accounts
+    )
+    .is_ok());
+    update_clock_slot(&mut clock_account, 2);
+    assert!(process(
+        &instruction::upd_price_no_fail_on_error(
+            &program_id,
+            funding,
+            price,
+            u32::MAX,
+            PC_STATUS_TRADING,
+            43,
+            2,
+            2
+        ),
+        &accounts
+    )
+    .is_ok());
+    update_clock_slot(&mut clock_account, 3);
+    assert!(process(
+        &instruction::upd_price_batch(
+            &program_id,
+            funding,
+            &[(*price, 0, PC_STATUS_TRADING, 44, 2, 3)],
+            false
+        )
+        .unwrap(),
+        &accounts
+    )
+    .is_ok());
+    update_clock_slot(&mut clock_account, 4);
+    assert!(process(
+        &instruction::agg_price(&program_id, funding, price),
+        &accounts
+    )
+    .is_ok());
+    {
+        let price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.comp_[0].latest_.price_, 44);
+        assert_eq!(price_data.agg_.price_, 44);
+        assert_eq!(price_data.agg_.pub_slot_, 4);
+    }
+
+    assert!(process(
+        &instruction::del_publisher(&program_id, funding, price, funding),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::del_price(&program_id, funding, product, price, None),
+        &accounts
+    )
+    .is_ok());
+    assert!(process(
+        &instruction::del_product(&program_id, funding, mapping, product),
+        &accounts
+    )
+    .is_ok());
+    assert_eq!(
+        load_account_as::<pc_map_table_t>(&mapping_account)
+            .unwrap()
+            .num_,
+        0
+    );
+}
+
+#[test]
+fn test_instruction_builders_accounts() {
+    let program_id = Pubkey::new_unique();
+    let funding = Pubkey::new_unique();
+    let price = Pubkey::new_unique();
+    let previous_price = Pubkey::new_unique();
+    let product = Pubkey::new_unique();
+
+    let permissions = get_permissions_address(&program_id).0;
+
+    let resize = instruction::resize_price_account(&program_id, &funding, &price);
+    assert_eq!(
+        metas(&resize),
+        [
+            (funding, true, true),
+            (price, false, true),
+            (system_program::id(), false, false),
+            (permissions, false, false)
+        ]
+    );
+    assert!(matches!(
+        OracleInstruction::unpack(&resize.data),
+        Ok(OracleInstruction::ResizePriceAccount(_))
+    ));
+
+    let resize = instruction::resize_product_account(&program_id, &funding, &product, 1024);
+    assert_eq!(
+        metas(&resize),
+        [
+            (funding, true, true),
+            (product, false, true),
+            (system_program::id(), false, false),
+            (permissions, false, false)
+        ]
+    );
+    assert!(matches!(
+        OracleInstruction::unpack(&resize.data),
+        Ok(OracleInstruction::ResizeProductAccount(cmd)) if cmd.size_ == 1024
+    ));
+
+    let migrate = instruction::migrate_account(&program_id, &funding, &product);
+    assert_eq!(
+        metas(&migrate),
+        [
+            (funding, true, true),
+            (product, false, true),
+            (system_program::id(), false, false),
+            (permissions, false, false)
+        ]
+    );
+    assert!(matches!(
+        OracleInstruction::unpack(&migrate.data),
+        Ok(OracleInstruction::MigrateAccount(hdr)) if hdr.ver_ == PC_VERSION
+    ));
+
+    let query = instruction::query_time_machine(&program_id, &price, 3600);
+    assert_eq!(
+        metas(&query),
+        [(price, false, false), (clock::id(), false, false)]
+    );
+    assert!(matches!(
+        OracleInstruction::unpack(&query.data),
+        Ok(OracleInstruction::QueryTimeMachine(cmd)) if cmd.window_ == 3600
+    ));
+
+    let del_price = instruction::del_price(
+        &program_id,
+        &funding,
+        &product,
+        &price,
+        Some(&previous_price),
+    );
+    assert_eq!(
+        metas(&del_price),
+        [
+            (funding, true, true),
+            (product, false, true),
+            (price, false, true),
+            (previous_price, false, true),
+            (permissions, false, false)
+        ]
+    );
+
+    let upd_permissions = instruction::upd_permissions(
+        &program_id,
+        &funding,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+    );
+    let programdata =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0;
+    assert_eq!(
+        metas(&upd_permissions),
+        [
+            (funding, true, true),
+            (programdata, false, false),
+            (permissions, false, true),
+            (system_program::id(), false, false)
+        ]
+    );
+    assert!(matches!(
+        OracleInstruction::unpack(&upd_permissions.data),
+        Ok(OracleInstruction::UpdPermissions(_))
+    ));
+
+    assert!(
+        instruction::set_product_attr(&program_id, &funding, &product, &[b'x'; 256], b"").is_err()
+    );
+}
+
+// Run instruction with the accounts of accounts that it references, flagged as signer and
+// writable as in the instruction
+fn process(instruction: &Instruction, accounts: &[AccountInfo]) -> OracleResult {
+    let instruction_accounts: Vec<AccountInfo> = instruction
+        .accounts
+        .iter()
+        .map(|meta| {
+            let mut account = accounts
+                .iter()
+                .find(|account| *account.key == meta.pubkey)
+                .unwrap()
+                .clone();
+            account.is_signer = meta.is_signer;
+            account.is_writable = meta.is_writable;
+            account
+        })
+        .collect();
+    process_instruction(
+        &instruction.program_id,
+        &instruction_accounts,
+        &OracleInstruction::unpack(&instruction.data)?,
+    )
+}
+
+fn metas(instruction: &Instruction) -> Vec<(Pubkey, bool, bool)> {
+    instruction
+        .accounts
+        .iter()
+        .map(|meta| (meta.pubkey, meta.is_signer, meta.is_writable))
+        .collect()
+}
@@ -0,0 +1,263 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Epoch;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use std::mem::size_of;
+
+use crate::c_oracle_header::{
+    cmd_hdr_t,
+    command_t_e_cmd_migrate_account,
+    pc_price_t,
+    pc_prod_t,
+    PC_MIN_VERSION,
+    PC_VERSION,
+    PUBLISHER_STATS_SIZE,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+    load_time_machine_checked,
+};
+use crate::error::OracleError;
+use crate::instruction::OracleInstruction;
+use crate::processor::process_instruction;
+use crate::rust_oracle::migrate_account;
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
+use crate::time_machine_types::{
+    PriceAccountWrapper,
+    SMA_GRANULARITY,
+};
+
+#[test]
+fn test_migrate_account() {
+    let hdr = populate_instruction(PC_VERSION);
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+    load_checked::<pc_price_t>(&price_account, PC_VERSION)
+        .unwrap()
+        .expo_ = -8;
+
+    let mut product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account = product_setup.to_account_info();
+
+    let system_program_key = system_program::id();
+    let mut system_program_balance = 0;
+    let system_program_account = AccountInfo::new(
+        &system_program_key,
+        false,
+        false,
+        &mut system_program_balance,
+        &mut [],
+        &system_program_key,
+        true,
+        Epoch::default(),
+    );
+
+    // An account that is already at PC_VERSION is left unchanged
+    let data_before = price_account.try_borrow_data().unwrap().to_vec();
+    assert!(migrate_account(
+        &program_id,
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            system_program_account.clone(),
+            permissions_account.clone()
+        ],
+        &hdr
+    )
+    .is_ok());
+    assert_eq!(*price_account.try_borrow_data().unwrap(), data_before);
+
+    // Every supported version of the instruction is accepted
+    for version in PC_MIN_VERSION..=PC_VERSION {
+        assert!(process_instruction(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                system_program_account.clone(),
+                permissions_account.clone()
+            ],
+            &OracleInstruction::MigrateAccount(populate_instruction(version))
+        )
+        .is_ok());
+    }
+    for version in [PC_MIN_VERSION - 1, PC_VERSION + 1] {
+        assert_eq!(
+            process_instruction(
+                &program_id,
+                &[
+                    funding_account.clone(),
+                    price_account.clone(),
+                    system_program_account.clone(),
+                    permissions_account.clone()
+                ],
+                &OracleInstruction::MigrateAccount(populate_instruction(version))
+            ),
+            Err(OracleError::InvalidInstructionVersion.into())
+        );
+    }
+
+    // Accounts of an unsupported version can't be migrated
+    for version in [PC_MIN_VERSION - 1, PC_VERSION + 1] {
+        load_checked::<pc_price_t>(&price_account, PC_VERSION)
+            .unwrap()
+            .ver_ = version;
+        assert_eq!(
+            migrate_account(
+                &program_id,
+                &[
+                    funding_account.clone(),
+                    price_account.clone(),
+                    system_program_account.clone(),
+                    permissions_account.clone()
+                ],
+                &hdr
+            ),
+            Err(OracleError::InvalidAccountVersion.into())
+        );
+        assert_eq!(
+            load_checked::<pc_price_t>(&price_account, PC_VERSION).err(),
+            Some(OracleError::InvalidAccountVersion.into())
+        );
+        initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+    }
+
+    // The account must be initialized
+    assert_eq!(
+        migrate_account(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                system_program_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr
+        ),
+        Err(OracleError::InvalidAccountHeader.into())
+    );
+
+    // The system program is required
+    assert_eq!(
+        migrate_account(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr
+        ),
+        Err(OracleError::InvalidNumberOfAccounts.into())
+    );
+    assert_eq!(
+        migrate_account(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                funding_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr
+        ),
+        Err(OracleError::InvalidSystemAccount.into())
+    );
+}
+
+#[test]
+fn test_migrate_price_account_without_stats() {
+    let hdr = populate_instruction(PC_VERSION);
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let system_program_key = system_program::id();
+    let mut system_program_balance = 0;
+    let system_program_account = AccountInfo::new(
+        &system_program_key,
+        false,
+        false,
+        &mut system_program_balance,
+        &mut [],
+        &system_program_key,
+        true,
+        Epoch::default(),
+    );
+
+    // A price account resized before the publisher statistics were added, with a time machine
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+    let size_without_stats = size_of::<PriceAccountWrapper>() - PUBLISHER_STATS_SIZE as usize;
+    price_account.realloc(size_without_stats, false).unwrap();
+    **price_account.try_borrow_mut_lamports().unwrap() =
+        Rent::default().minimum_balance(size_without_stats);
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+    {
+        let mut data = price_account.try_borrow_mut_data().unwrap();
+        let (price_data, time_machine) = load_time_machine_checked(&mut data, PC_VERSION).unwrap();
+        price_data.expo_ = -8;
+        time_machine
+            .sma_tracker
+            .initialize(SMA_GRANULARITY)
+            .unwrap();
+    }
+
+    let accounts = [
+        funding_account.clone(),
+        price_account.clone(),
+        system_program_account.clone(),
+        permissions_account.clone(),
+    ];
+    assert!(migrate_account(&program_id, &accounts, &hdr).is_ok());
+    assert_eq!(price_account.data_len(), size_of::<PriceAccountWrapper>());
+    {
+        let price_data = load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.price_data.expo_, -8);
+        assert_eq!(
+            price_data.time_machine.sma_tracker.granularity,
+            SMA_GRANULARITY
+        );
+        assert!(price_data
+            .publisher_stats
+            .iter()
+            .all(|stats| stats.num_updates == 0 && stats.last_aggregate_slot == 0));
+    }
+
+    // The account has the current layout, migrating it again does nothing. The transfer of the
+    // system program is not executed in tests
+    **price_account.try_borrow_mut_lamports().unwrap() =
+        Rent::default().minimum_balance(size_of::<PriceAccountWrapper>());
+    let data_before = price_account.try_borrow_data().unwrap().to_vec();
+    assert!(migrate_account(&program_id, &accounts, &hdr).is_ok());
+    assert_eq!(*price_account.try_borrow_data().unwrap(), data_before);
+}
+
+fn populate_instruction(version: u32) -> cmd_hdr_t {
+    cmd_hdr_t {
+        ver_: version,
+        cmd_: command_t_e_cmd_migrate_account as i32,
+    }
+}
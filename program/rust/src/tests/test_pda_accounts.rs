@@ -0,0 +1,300 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Epoch;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+
+use crate::c_oracle_header::{
+    cmd_add_price_t,
+    cmd_hdr_t,
+    command_t_e_cmd_add_mapping_pda,
+    command_t_e_cmd_add_price_pda,
+    command_t_e_cmd_add_product_pda,
+    command_t_e_cmd_init_mapping_pda,
+    pc_map_table_t,
+    pc_price_t,
+    pc_prod_t,
+    PC_ACCTYPE_MAPPING,
+    PC_MAP_TABLE_SIZE,
+    PC_PTYPE_PRICE,
+    PC_VERSION,
+};
+use crate::deserialize::load_checked;
+use crate::error::OracleError;
+use crate::instruction::{
+    self,
+    OracleInstruction,
+};
+use crate::rust_oracle::{
+    add_mapping_pda,
+    add_price_pda,
+    add_product_pda,
+    init_mapping_pda,
+};
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
+use crate::utils::{
+    get_mapping_address,
+    get_price_address,
+    get_product_address,
+    pubkey_equal,
+};
+
+#[test]
+fn test_pda_accounts() {
+    let program_id = Pubkey::new_unique();
+    let symbol = b"Crypto.BTC/USD";
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let system_program_key = system_program::id();
+    let mut system_program_balance = 0;
+    let system_program_account = AccountInfo::new(
+        &system_program_key,
+        false,
+        false,
+        &mut system_program_balance,
+        &mut [],
+        &system_program_key,
+        true,
+        Epoch::default(),
+    );
+
+    // The system program transfer is not executed in tests, so the accounts are allocated
+    // beforehand
+    let mapping_address = get_mapping_address(&program_id, None).0;
+    let mut mapping_setup =
+        AccountSetup::new_with_key::<pc_map_table_t>(&program_id, &mapping_address);
+    let mapping_account = mapping_setup.to_account_info();
+
+    let next_mapping_address = get_mapping_address(&program_id, Some(&mapping_address)).0;
+    let mut next_mapping_setup =
+        AccountSetup::new_with_key::<pc_map_table_t>(&program_id, &next_mapping_address);
+    let next_mapping_account = next_mapping_setup.to_account_info();
+
+    let product_address = get_product_address(&program_id, symbol).0;
+    let mut product_setup = AccountSetup::new_with_key::<pc_prod_t>(&program_id, &product_address);
+    let product_account = product_setup.to_account_info();
+
+    let price_address = get_price_address(&program_id, &product_address, PC_PTYPE_PRICE).0;
+    let mut price_setup = AccountSetup::new_with_key::<pc_price_t>(&program_id, &price_address);
+    let price_account = price_setup.to_account_info();
+
+    // A keypair account is not the PDA
+    let mut keypair_setup = AccountSetup::new::<pc_map_table_t>(&program_id);
+    let keypair_account = keypair_setup.to_account_info();
+
+    let init_mapping_hdr = populate_hdr(command_t_e_cmd_init_mapping_pda);
+    assert_eq!(
+        init_mapping_pda(
+            &program_id,
+            &[
+                funding_account.clone(),
+                keypair_account.clone(),
+                system_program_account.clone(),
+                permissions_account.clone()
+            ],
+            &init_mapping_hdr
+        ),
+        Err(OracleError::InvalidPdaAccount.into())
+    );
+    assert_eq!(
+        init_mapping_pda(
+            &program_id,
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                funding_account.clone(),
+                permissions_account.clone()
+            ],
+            &init_mapping_hdr
+        ),
+        Err(OracleError::InvalidSystemAccount.into())
+    );
+    assert!(init_mapping_pda(
+        &program_id,
+        &[
+            funding_account.clone(),
+            mapping_account.clone(),
+            system_program_account.clone(),
+            permissions_account.clone()
+        ],
+        &init_mapping_hdr
+    )
+    .is_ok());
+    assert_eq!(
+        load_checked::<pc_map_table_t>(&mapping_account, PC_VERSION)
+            .unwrap()
+            .type_,
+        PC_ACCTYPE_MAPPING
+    );
+    assert_eq!(
+        init_mapping_pda(
+            &program_id,
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                system_program_account.clone(),
+                permissions_account.clone()
+            ],
+            &init_mapping_hdr
+        ),
+        Err(OracleError::InvalidFreshAccount.into())
+    );
+
+    // The product account is derived from its seed
+    let add_product_hdr = populate_hdr(command_t_e_cmd_add_product_pda);
+    assert_eq!(
+        add_product_pda(
+            &program_id,
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                product_account.clone(),
+                system_program_account.clone(),
+                permissions_account.clone()
+            ],
+            &add_product_hdr,
+            b"Crypto.ETH/USD"
+        ),
+        Err(OracleError::InvalidPdaAccount.into())
+    );
+    assert!(add_product_pda(
+        &program_id,
+        &[
+            funding_account.clone(),
+            mapping_account.clone(),
+            product_account.clone(),
+            system_program_account.clone(),
+            permissions_account.clone()
+        ],
+        &add_product_hdr,
+        symbol
+    )
+    .is_ok());
+    {
+        let mapping_data = load_checked::<pc_map_table_t>(&mapping_account, PC_VERSION).unwrap();
+        assert_eq!(mapping_data.num_, 1);
+        assert!(pubkey_equal(
+            &mapping_data.prod_[0],
+            &product_address.to_bytes()
+        ));
+    }
+
+    // The price account is derived from the product account and the price type
+    let mut add_price_cmd = cmd_add_price_t {
+        ver_:   PC_VERSION,
+        cmd_:   command_t_e_cmd_add_price_pda as i32,
+        expo_:  -8,
+        ptype_: PC_PTYPE_PRICE + 1,
+    };
+    assert_eq!(
+        add_price_pda(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                price_account.clone(),
+                system_program_account.clone(),
+                permissions_account.clone()
+            ],
+            &add_price_cmd
+        ),
+        Err(OracleError::InvalidPdaAccount.into())
+    );
+    add_price_cmd.ptype_ = PC_PTYPE_PRICE;
+    assert!(add_price_pda(
+        &program_id,
+        &[
+            funding_account.clone(),
+            product_account.clone(),
+            price_account.clone(),
+            system_program_account.clone(),
+            permissions_account.clone()
+        ],
+        &add_price_cmd
+    )
+    .is_ok());
+    {
+        let price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        let product_data = load_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.expo_, -8);
+        assert_eq!(price_data.ptype_, PC_PTYPE_PRICE);
+        assert!(pubkey_equal(&price_data.prod_, &product_address.to_bytes()));
+        assert!(pubkey_equal(
+            &product_data.px_acc_,
+            &price_address.to_bytes()
+        ));
+    }
+
+    // The next mapping account is derived from the tail mapping account
+    let add_mapping_hdr = populate_hdr(command_t_e_cmd_add_mapping_pda);
+    let add_mapping_accounts = [
+        funding_account.clone(),
+        mapping_account.clone(),
+        next_mapping_account.clone(),
+        system_program_account.clone(),
+        permissions_account.clone(),
+    ];
+    assert_eq!(
+        add_mapping_pda(&program_id, &add_mapping_accounts, &add_mapping_hdr),
+        Err(OracleError::InvalidMappingTail.into())
+    );
+    load_checked::<pc_map_table_t>(&mapping_account, PC_VERSION)
+        .unwrap()
+        .num_ = PC_MAP_TABLE_SIZE;
+    assert!(add_mapping_pda(&program_id, &add_mapping_accounts, &add_mapping_hdr).is_ok());
+    assert!(pubkey_equal(
+        &load_checked::<pc_map_table_t>(&mapping_account, PC_VERSION)
+            .unwrap()
+            .next_,
+        &next_mapping_address.to_bytes()
+    ));
+}
+
+#[test]
+fn test_add_product_pda_seed() {
+    let program_id = Pubkey::new_unique();
+    let funding = Pubkey::new_unique();
+    let mapping = Pubkey::new_unique();
+
+    let add_product =
+        instruction::add_product_pda(&program_id, &funding, &mapping, b"Crypto.BTC/USD").unwrap();
+    assert_eq!(
+        add_product.accounts[2].pubkey,
+        get_product_address(&program_id, b"Crypto.BTC/USD").0
+    );
+    assert!(matches!(
+        OracleInstruction::unpack(&add_product.data),
+        Ok(OracleInstruction::AddProductPda { seed, .. }) if seed == b"Crypto.BTC/USD"
+    ));
+
+    // The seed must have 1 to 32 bytes
+    assert!(instruction::add_product_pda(&program_id, &funding, &mapping, b"").is_err());
+    assert!(instruction::add_product_pda(&program_id, &funding, &mapping, &[b'x'; 33]).is_err());
+    let mut data = add_product.data;
+    data.truncate(8);
+    assert_eq!(
+        OracleInstruction::unpack(&data).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+    data.extend_from_slice(&[b'x'; 33]);
+    assert_eq!(
+        OracleInstruction::unpack(&data).err(),
+        Some(ProgramError::InvalidArgument)
+    );
+}
+
+fn populate_hdr(cmd: u32) -> cmd_hdr_t {
+    cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: cmd as i32,
+    }
+}
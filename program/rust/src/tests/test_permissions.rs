@@ -0,0 +1,367 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::bpf_loader_upgradeable::UpgradeableLoaderState;
+use solana_program::clock::Epoch;
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+
+use crate::c_oracle_header::{
+    cmd_add_publisher_t,
+    cmd_set_min_pub_t,
+    cmd_upd_permissions_t,
+    command_t_e_cmd_add_publisher,
+    command_t_e_cmd_set_min_pub,
+    command_t_e_cmd_upd_permissions,
+    pc_permissions_t,
+    pc_price_t,
+    pc_pub_key_t,
+    PC_ACCTYPE_PERMISSIONS,
+    PC_MAGIC,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::rust_oracle::{
+    add_publisher,
+    set_min_pub,
+    upd_permissions,
+};
+use crate::tests::test_utils::AccountSetup;
+use crate::utils::pubkey_equal;
+
+#[test]
+fn test_upd_permissions() {
+    let program_id = Pubkey::new_unique();
+
+    let mut upgrade_authority_setup = AccountSetup::new_funding();
+    let upgrade_authority_account = upgrade_authority_setup.to_account_info();
+
+    let mut attacker_setup = AccountSetup::new_funding();
+    let attacker_account = attacker_setup.to_account_info();
+
+    let mut programdata_setup =
+        AccountSetup::new_programdata(&program_id, upgrade_authority_account.key);
+    let programdata_account = programdata_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+
+    let mut other_permissions_setup = AccountSetup::new_permissions(&Pubkey::new_unique());
+    let other_permissions_account = other_permissions_setup.to_account_info();
+
+    let system_program_key = system_program::id();
+    let mut system_program_balance = 0;
+    let system_program_account = AccountInfo::new(
+        &system_program_key,
+        false,
+        false,
+        &mut system_program_balance,
+        &mut [],
+        &system_program_key,
+        true,
+        Epoch::default(),
+    );
+
+    let master_authority = Pubkey::new_unique();
+    let data_curation_authority = Pubkey::new_unique();
+    let security_authority = Pubkey::new_unique();
+    let cmd = populate_instruction(
+        &master_authority,
+        &data_curation_authority,
+        &security_authority,
+    );
+
+    // Only the upgrade authority can set the permissions
+    assert_eq!(
+        upd_permissions(
+            &program_id,
+            &[
+                attacker_account.clone(),
+                programdata_account.clone(),
+                permissions_account.clone(),
+                system_program_account.clone()
+            ],
+            &cmd
+        ),
+        Err(OracleError::PermissionDenied.into())
+    );
+
+    // The programdata account must be the one of the program
+    assert_eq!(
+        upd_permissions(
+            &program_id,
+            &[
+                upgrade_authority_account.clone(),
+                attacker_account.clone(),
+                permissions_account.clone(),
+                system_program_account.clone()
+            ],
+            &cmd
+        ),
+        Err(OracleError::InvalidProgramDataAccount.into())
+    );
+
+    // The permissions account must be the PDA of the program
+    assert_eq!(
+        upd_permissions(
+            &program_id,
+            &[
+                upgrade_authority_account.clone(),
+                programdata_account.clone(),
+                other_permissions_account.clone(),
+                system_program_account.clone()
+            ],
+            &cmd
+        ),
+        Err(OracleError::InvalidPermissionsAccount.into())
+    );
+
+    assert!(upd_permissions(
+        &program_id,
+        &[
+            upgrade_authority_account.clone(),
+            programdata_account.clone(),
+            permissions_account.clone(),
+            system_program_account.clone()
+        ],
+        &cmd
+    )
+    .is_ok());
+    {
+        let permissions_data =
+            load_checked::<pc_permissions_t>(&permissions_account, PC_VERSION).unwrap();
+        assert_eq!(permissions_data.magic_, PC_MAGIC);
+        assert_eq!(permissions_data.type_, PC_ACCTYPE_PERMISSIONS);
+        assert!(pubkey_equal(
+            &permissions_data.master_authority_,
+            &master_authority.to_bytes()
+        ));
+        assert!(pubkey_equal(
+            &permissions_data.data_curation_authority_,
+            &data_curation_authority.to_bytes()
+        ));
+        assert!(pubkey_equal(
+            &permissions_data.security_authority_,
+            &security_authority.to_bytes()
+        ));
+    }
+
+    // The authorities can be rotated
+    let cmd = populate_instruction(&security_authority, &master_authority, &master_authority);
+    assert!(upd_permissions(
+        &program_id,
+        &[
+            upgrade_authority_account.clone(),
+            programdata_account.clone(),
+            permissions_account.clone(),
+            system_program_account.clone()
+        ],
+        &cmd
+    )
+    .is_ok());
+    {
+        let permissions_data =
+            load_checked::<pc_permissions_t>(&permissions_account, PC_VERSION).unwrap();
+        assert!(pubkey_equal(
+            &permissions_data.master_authority_,
+            &security_authority.to_bytes()
+        ));
+        assert!(pubkey_equal(
+            &permissions_data.security_authority_,
+            &master_authority.to_bytes()
+        ));
+    }
+
+    // Nobody can set the permissions of an immutable program
+    bincode::serialize_into(
+        &mut programdata_account.try_borrow_mut_data().unwrap()[..],
+        &UpgradeableLoaderState::ProgramData {
+            slot:                      0,
+            upgrade_authority_address: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        upd_permissions(
+            &program_id,
+            &[
+                upgrade_authority_account.clone(),
+                programdata_account.clone(),
+                permissions_account.clone(),
+                system_program_account.clone()
+            ],
+            &cmd
+        ),
+        Err(OracleError::PermissionDenied.into())
+    );
+}
+
+#[test]
+fn test_permissions() {
+    let program_id = Pubkey::new_unique();
+
+    let mut master_setup = AccountSetup::new_funding();
+    let master_account = master_setup.to_account_info();
+    let mut data_curation_setup = AccountSetup::new_funding();
+    let data_curation_account = data_curation_setup.to_account_info();
+    let mut security_setup = AccountSetup::new_funding();
+    let security_account = security_setup.to_account_info();
+    let mut attacker_setup = AccountSetup::new_funding();
+    let attacker_account = attacker_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_permissions_t>(&permissions_account, PC_VERSION).unwrap();
+    {
+        let mut permissions_data =
+            load_checked::<pc_permissions_t>(&permissions_account, PC_VERSION).unwrap();
+        permissions_data.master_authority_.k1_ = master_account.key.to_bytes();
+        permissions_data.data_curation_authority_.k1_ = data_curation_account.key.to_bytes();
+        permissions_data.security_authority_.k1_ = security_account.key.to_bytes();
+    }
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+
+    let set_min_pub_cmd = cmd_set_min_pub_t {
+        ver_:     PC_VERSION,
+        cmd_:     command_t_e_cmd_set_min_pub as i32,
+        min_pub_: 1,
+    };
+    let add_publisher_cmd = |publisher: &Pubkey| cmd_add_publisher_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_add_publisher as i32,
+        pub_: pc_pub_key_t {
+            k1_: publisher.to_bytes(),
+        },
+    };
+
+    // The master authority can run every admin instruction
+    assert!(set_min_pub(
+        &program_id,
+        &[
+            master_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &set_min_pub_cmd
+    )
+    .is_ok());
+    assert!(add_publisher(
+        &program_id,
+        &[
+            master_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &add_publisher_cmd(master_account.key)
+    )
+    .is_ok());
+
+    // The data curation authority can't manage the publishers
+    assert!(set_min_pub(
+        &program_id,
+        &[
+            data_curation_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &set_min_pub_cmd
+    )
+    .is_ok());
+    assert_eq!(
+        add_publisher(
+            &program_id,
+            &[
+                data_curation_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &add_publisher_cmd(data_curation_account.key)
+        ),
+        Err(OracleError::PermissionDenied.into())
+    );
+
+    // The security authority can only manage the publishers
+    assert_eq!(
+        set_min_pub(
+            &program_id,
+            &[
+                security_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &set_min_pub_cmd
+        ),
+        Err(OracleError::PermissionDenied.into())
+    );
+    assert!(add_publisher(
+        &program_id,
+        &[
+            security_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &add_publisher_cmd(security_account.key)
+    )
+    .is_ok());
+
+    // Anyone else is denied
+    assert_eq!(
+        set_min_pub(
+            &program_id,
+            &[
+                attacker_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &set_min_pub_cmd
+        ),
+        Err(OracleError::PermissionDenied.into())
+    );
+    assert_eq!(
+        load_checked::<pc_price_t>(&price_account, PC_VERSION)
+            .unwrap()
+            .num_,
+        2
+    );
+
+    // The permissions account must be the PDA of the program
+    let mut other_permissions_setup = AccountSetup::new_permissions(&Pubkey::new_unique());
+    let other_permissions_account = other_permissions_setup.to_account_info();
+    assert_eq!(
+        set_min_pub(
+            &program_id,
+            &[
+                master_account.clone(),
+                price_account.clone(),
+                other_permissions_account
+            ],
+            &set_min_pub_cmd
+        ),
+        Err(OracleError::InvalidPermissionsAccount.into())
+    );
+}
+
+fn populate_instruction(
+    master_authority: &Pubkey,
+    data_curation_authority: &Pubkey,
+    security_authority: &Pubkey,
+) -> cmd_upd_permissions_t {
+    cmd_upd_permissions_t {
+        ver_:                     PC_VERSION,
+        cmd_:                     command_t_e_cmd_upd_permissions as i32,
+        master_authority_:        pc_pub_key_t {
+            k1_: master_authority.to_bytes(),
+        },
+        data_curation_authority_: pc_pub_key_t {
+            k1_: data_curation_authority.to_bytes(),
+        },
+        security_authority_:      pc_pub_key_t {
+            k1_: security_authority.to_bytes(),
+        },
+    }
+}
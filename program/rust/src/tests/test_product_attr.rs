@@ -0,0 +1,251 @@
+use std::mem::size_of;
+
+use solana_program::account_info::AccountInfo;
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    cmd_hdr_t,
+    command_t_e_cmd_del_product_attr,
+    command_t_e_cmd_set_product_attr,
+    command_t_e_cmd_upd_product,
+    pc_prod_t,
+    PythAccount,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::instruction::ProductAttribute;
+use crate::rust_oracle::{
+    del_product_attr,
+    set_product_attr,
+    upd_product,
+};
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
+use crate::utils::try_convert;
+
+#[test]
+fn test_set_product_attr() {
+    let set_hdr = populate_hdr(command_t_e_cmd_set_product_attr);
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account = product_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+
+    let accounts = [
+        funding_account.clone(),
+        product_account.clone(),
+        permissions_account.clone(),
+    ];
+
+    assert!(upd_product(
+        &program_id,
+        &accounts,
+        &populate_hdr(command_t_e_cmd_upd_product),
+        &populate_attributes(&["symbol", "BTC/USD", "asset_type", "Crypto", "base", "BTC"])
+    )
+    .is_ok());
+
+    // Update an existing attribute with a longer value
+    assert!(set_product_attr(
+        &program_id,
+        &accounts,
+        &set_hdr,
+        b"asset_type",
+        b"Cryptocurrency"
+    )
+    .is_ok());
+    assert_eq!(
+        get_key_values(&product_account),
+        [
+            "symbol",
+            "BTC/USD",
+            "asset_type",
+            "Cryptocurrency",
+            "base",
+            "BTC"
+        ]
+    );
+
+    // With a shorter value
+    assert!(set_product_attr(&program_id, &accounts, &set_hdr, b"symbol", b"BTC").is_ok());
+    assert_eq!(
+        get_key_values(&product_account),
+        [
+            "symbol",
+            "BTC",
+            "asset_type",
+            "Cryptocurrency",
+            "base",
+            "BTC"
+        ]
+    );
+
+    // A new attribute is added at the end
+    assert!(set_product_attr(&program_id, &accounts, &set_hdr, b"quote_currency", b"USD").is_ok());
+    assert_eq!(
+        get_key_values(&product_account),
+        [
+            "symbol",
+            "BTC",
+            "asset_type",
+            "Cryptocurrency",
+            "base",
+            "BTC",
+            "quote_currency",
+            "USD"
+        ]
+    );
+    {
+        let product_data = load_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+        assert_eq!(product_data.size_, pc_prod_t::INITIAL_SIZE + 65);
+    }
+
+    // Values can be empty
+    assert!(set_product_attr(&program_id, &accounts, &set_hdr, b"base", b"").is_ok());
+    assert_eq!(
+        get_key_values(&product_account),
+        [
+            "symbol",
+            "BTC",
+            "asset_type",
+            "Cryptocurrency",
+            "base",
+            "",
+            "quote_currency",
+            "USD"
+        ]
+    );
+
+    // The product account must have room for the new attribute
+    let long_value = [b'x'; 255];
+    assert!(set_product_attr(
+        &program_id,
+        &accounts,
+        &set_hdr,
+        b"description",
+        &long_value
+    )
+    .is_ok());
+    assert_eq!(
+        set_product_attr(
+            &program_id,
+            &accounts,
+            &set_hdr,
+            b"more_description",
+            &long_value
+        ),
+        Err(OracleError::MetadataTooLarge.into())
+    );
+    assert_eq!(get_key_values(&product_account).len(), 10);
+}
+
+#[test]
+fn test_del_product_attr() {
+    let del_hdr = populate_hdr(command_t_e_cmd_del_product_attr);
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account = product_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+
+    let accounts = [
+        funding_account.clone(),
+        product_account.clone(),
+        permissions_account.clone(),
+    ];
+
+    assert!(upd_product(
+        &program_id,
+        &accounts,
+        &populate_hdr(command_t_e_cmd_upd_product),
+        &populate_attributes(&["symbol", "BTC/USD", "asset_type", "Crypto", "base", "BTC"])
+    )
+    .is_ok());
+
+    assert!(del_product_attr(&program_id, &accounts, &del_hdr, b"asset_type").is_ok());
+    assert_eq!(
+        get_key_values(&product_account),
+        ["symbol", "BTC/USD", "base", "BTC"]
+    );
+    {
+        let product_data = load_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+        assert_eq!(product_data.size_, pc_prod_t::INITIAL_SIZE + 24);
+    }
+
+    // The attribute doesn't exist anymore
+    assert_eq!(
+        del_product_attr(&program_id, &accounts, &del_hdr, b"asset_type"),
+        Err(OracleError::AttributeNotFound.into())
+    );
+
+    // Keys are matched exactly
+    assert_eq!(
+        del_product_attr(&program_id, &accounts, &del_hdr, b"sym"),
+        Err(OracleError::AttributeNotFound.into())
+    );
+
+    assert!(del_product_attr(&program_id, &accounts, &del_hdr, b"base").is_ok());
+    assert!(del_product_attr(&program_id, &accounts, &del_hdr, b"symbol").is_ok());
+    assert!(get_key_values(&product_account).is_empty());
+    {
+        let product_data = load_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+        assert_eq!(product_data.size_, pc_prod_t::INITIAL_SIZE);
+    }
+}
+
+fn populate_hdr(cmd: u32) -> cmd_hdr_t {
+    cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: cmd as i32,
+    }
+}
+
+// Pair up the strings into key-value pairs
+fn populate_attributes(strings: &[&str]) -> Vec<ProductAttribute> {
+    strings
+        .chunks_exact(2)
+        .map(|kv| (kv[0].as_bytes().to_vec(), kv[1].as_bytes().to_vec()))
+        .collect()
+}
+
+// Read the key-value list of product_account
+fn get_key_values(product_account: &AccountInfo) -> Vec<String> {
+    let account_size: usize = try_convert(
+        load_checked::<pc_prod_t>(product_account, PC_VERSION)
+            .unwrap()
+            .size_,
+    )
+    .unwrap();
+    let data = product_account.try_borrow_data().unwrap();
+    let mut kv_data = &data[size_of::<pc_prod_t>()..account_size];
+    let mut strings = vec![];
+    while !kv_data.is_empty() {
+        let len = kv_data[0] as usize;
+        strings.push(String::from_utf8(kv_data[1..1 + len].to_vec()).unwrap());
+        kv_data = &kv_data[1 + len..];
+    }
+    strings
+}
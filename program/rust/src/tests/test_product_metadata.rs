@@ -0,0 +1,156 @@
+use std::mem::size_of;
+
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    cmd_hdr_t,
+    command_t_e_cmd_upd_product,
+    pc_prod_t,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::rust_oracle::upd_product;
+use crate::sdk::{
+    load_product_metadata,
+    parse_product_metadata,
+    serialize_product_metadata,
+    MetadataError,
+    ProductMetadata,
+};
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
+
+#[test]
+fn test_product_metadata() {
+    let hdr = cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_upd_product as i32,
+    };
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account = product_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+
+    assert_eq!(
+        load_product_metadata(&product_account.try_borrow_data().unwrap()),
+        Ok(ProductMetadata::new())
+    );
+
+    let metadata = populate_metadata(&[("symbol", "BTC/USD"), ("base", "BTC"), ("quote", "")]);
+    let attributes: Vec<_> = metadata
+        .iter()
+        .map(|(key, value)| (key.as_bytes().to_vec(), value.as_bytes().to_vec()))
+        .collect();
+    assert!(upd_product(
+        &program_id,
+        &[
+            funding_account.clone(),
+            product_account.clone(),
+            permissions_account.clone()
+        ],
+        &hdr,
+        &attributes
+    )
+    .is_ok());
+
+    // The metadata stored by upd_product is the serialized metadata
+    {
+        let data = product_account.try_borrow_data().unwrap();
+        assert_eq!(load_product_metadata(&data), Ok(metadata.clone()));
+        let serialized = serialize_product_metadata(&metadata).unwrap();
+        assert_eq!(
+            data[size_of::<pc_prod_t>()..size_of::<pc_prod_t>() + serialized.len()],
+            serialized
+        );
+    }
+
+    // The account must be a product account
+    load_checked::<pc_prod_t>(&product_account, PC_VERSION)
+        .unwrap()
+        .ver_ = PC_VERSION + 1;
+    assert_eq!(
+        load_product_metadata(&product_account.try_borrow_data().unwrap()),
+        Err(MetadataError::InvalidAccount)
+    );
+}
+
+#[test]
+fn test_product_metadata_errors() {
+    assert_eq!(
+        parse_product_metadata(b"\x03key\x05value\x03key"),
+        Err(MetadataError::MissingValue { offset: 10 })
+    );
+    assert_eq!(
+        parse_product_metadata(b"\x03key\x05val"),
+        Err(MetadataError::Truncated { offset: 4 })
+    );
+    assert_eq!(
+        parse_product_metadata(b"\x03key\x01\xff"),
+        Err(MetadataError::InvalidUtf8 { offset: 4 })
+    );
+    assert_eq!(
+        parse_product_metadata(b"\x03key\x01a\x03key\x01b"),
+        Err(MetadataError::DuplicateKey {
+            key: "key".to_string(),
+        })
+    );
+
+    assert_eq!(
+        serialize_product_metadata(&populate_metadata(&[("key", "a"), ("key", "b")])),
+        Err(MetadataError::DuplicateKey {
+            key: "key".to_string(),
+        })
+    );
+
+    let long_value = "x".repeat(256);
+    assert_eq!(
+        serialize_product_metadata(&populate_metadata(&[("key", &long_value)])),
+        Err(MetadataError::Oversize { len: 256 })
+    );
+    let long_value = "x".repeat(255);
+    let metadata = populate_metadata(&[("key", &long_value)]);
+    assert_eq!(
+        parse_product_metadata(&serialize_product_metadata(&metadata).unwrap()),
+        Ok(metadata)
+    );
+}
+
+#[test]
+fn test_product_metadata_round_trip() {
+    // The keys are not sorted, parsing and serializing keeps their order
+    let metadata_bytes = b"\x06symbol\x07BTC/USD\x05quote\x03USD\x04base\x03BTC\x00\x00";
+    let metadata = parse_product_metadata(metadata_bytes).unwrap();
+    assert_eq!(
+        metadata,
+        populate_metadata(&[
+            ("symbol", "BTC/USD"),
+            ("quote", "USD"),
+            ("base", "BTC"),
+            ("", "")
+        ])
+    );
+    assert_eq!(
+        serialize_product_metadata(&metadata).unwrap(),
+        metadata_bytes.to_vec()
+    );
+}
+
+fn populate_metadata(kvs: &[(&str, &str)]) -> ProductMetadata {
+    kvs.iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
@@ -0,0 +1,181 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    cmd_upd_price_batch_entry_t,
+    cmd_upd_price_batch_t,
+    cmd_upd_price_t,
+    command_t_e_cmd_upd_price,
+    command_t_e_cmd_upd_price_batch,
+    pc_price_t,
+    PC_COMP_SIZE,
+    PC_EXTRA_COMP_SIZE,
+    PC_STATUS_TRADING,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::rust_oracle::{
+    upd_price,
+    upd_price_batch,
+};
+use crate::sdk::find_publisher_index;
+use crate::tests::test_utils::{
+    update_clock_slot,
+    AccountSetup,
+};
+use crate::time_machine_types::{
+    PriceAccountWrapper,
+    MAX_NUM_COMPONENTS,
+};
+use crate::utils::pubkey_assign;
+
+#[test]
+fn test_publisher_index() {
+    let program_id = Pubkey::new_unique();
+
+    let mut price_setup = AccountSetup::new::<PriceAccountWrapper>(&program_id);
+    let mut price_account = price_setup.to_account_info();
+    price_account.is_signer = false;
+    initialize_pyth_account_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+    load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION)
+        .unwrap()
+        .initialize_time_machine()
+        .unwrap();
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.to_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+    update_clock_slot(&mut clock_account, 1);
+
+    let mut publisher_setups: Vec<AccountSetup> = (0..MAX_NUM_COMPONENTS)
+        .map(|_| AccountSetup::new_funding())
+        .collect();
+    let publishers: Vec<AccountInfo> = publisher_setups
+        .iter_mut()
+        .map(|setup| setup.to_account_info())
+        .collect();
+    set_publishers(&price_account, &publishers);
+
+    let last = MAX_NUM_COMPONENTS - 1;
+    {
+        let data = price_account.try_borrow_data().unwrap();
+        assert_eq!(
+            find_publisher_index(&data, publishers[last].key),
+            Ok(Some(last as u32))
+        );
+        assert_eq!(find_publisher_index(&data, publishers[3].key), Ok(Some(3)));
+        assert_eq!(find_publisher_index(&data, &Pubkey::new_unique()), Ok(None));
+
+        // The data of RPC clients is not aligned
+        let mut buffer = vec![0u8; data.len() + 1];
+        buffer[1..].copy_from_slice(&data);
+        assert_eq!(
+            find_publisher_index(&buffer[1..], publishers[last].key),
+            Ok(Some(last as u32))
+        );
+    }
+
+    // The right index, a wrong one and one out of range all find the publisher
+    for (pub_slot, pub_index) in [(1, last as u32), (2, 0), (3, u32::MAX)] {
+        assert!(upd_price(
+            &program_id,
+            &[
+                publishers[last].clone(),
+                price_account.clone(),
+                clock_account.clone()
+            ],
+            &populate_upd_price(pub_index, pub_slot)
+        )
+        .is_ok());
+        assert_eq!(
+            load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION)
+                .unwrap()
+                .extra_components[last - PC_COMP_SIZE as usize]
+                .latest_
+                .pub_slot_,
+            pub_slot
+        );
+    }
+
+    // The index of a component beyond the number of components is not trusted
+    load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION)
+        .unwrap()
+        .price_data
+        .num_extra_ = PC_EXTRA_COMP_SIZE - 1;
+    assert_eq!(
+        upd_price(
+            &program_id,
+            &[
+                publishers[last].clone(),
+                price_account.clone(),
+                clock_account.clone()
+            ],
+            &populate_upd_price(last as u32, 4)
+        ),
+        Err(OracleError::UnknownPublisher.into())
+    );
+
+    // The entries of a batch carry an index as well
+    let price_accounts = [price_account.clone()];
+    let mut batch_accounts = vec![publishers[1].clone(), clock_account.clone()];
+    batch_accounts.extend_from_slice(&price_accounts);
+    for pub_index in [1, 2] {
+        assert!(upd_price_batch(
+            &program_id,
+            &batch_accounts,
+            &cmd_upd_price_batch_t {
+                ver_:              PC_VERSION,
+                cmd_:              command_t_e_cmd_upd_price_batch as i32,
+                no_fail_on_error_: 0,
+                num_:              1,
+            },
+            &[cmd_upd_price_batch_entry_t {
+                status_:    PC_STATUS_TRADING,
+                pub_index_: pub_index,
+                price_:     100,
+                conf_:      1,
+                pub_slot_:  pub_index.into(),
+            }]
+        )
+        .is_ok());
+    }
+    assert_eq!(
+        load_checked::<pc_price_t>(&price_account, PC_VERSION)
+            .unwrap()
+            .comp_[1]
+            .latest_
+            .pub_slot_,
+        2
+    );
+}
+
+fn set_publishers(price_account: &AccountInfo, publishers: &[AccountInfo]) {
+    let mut price_data = load_checked::<PriceAccountWrapper>(price_account, PC_VERSION).unwrap();
+    price_data.price_data.num_ = publishers.len().min(PC_COMP_SIZE as usize) as u32;
+    price_data.price_data.num_extra_ =
+        publishers.len().saturating_sub(PC_COMP_SIZE as usize) as u32;
+    for (i, publisher) in publishers.iter().enumerate() {
+        let component = match i.checked_sub(PC_COMP_SIZE as usize) {
+            None => &mut price_data.price_data.comp_[i],
+            Some(extra_index) => &mut price_data.extra_components[extra_index],
+        };
+        pubkey_assign(&mut component.pub_, &publisher.key.to_bytes());
+    }
+}
+
+fn populate_upd_price(pub_index: u32, pub_slot: u64) -> cmd_upd_price_t {
+    cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price as i32,
+        status_:    PC_STATUS_TRADING,
+        pub_index_: pub_index,
+        price_:     100,
+        conf_:      1,
+        pub_slot_:  pub_slot,
+    }
+}
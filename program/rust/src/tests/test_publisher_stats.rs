@@ -0,0 +1,323 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Epoch;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+use std::mem::size_of;
+
+use crate::c_oracle_header::{
+    cmd_add_publisher_t,
+    cmd_del_publisher_t,
+    cmd_hdr_t,
+    cmd_upd_price_t,
+    command_t_e_cmd_add_publisher,
+    command_t_e_cmd_del_publisher,
+    command_t_e_cmd_resize_price_account,
+    command_t_e_cmd_upd_price,
+    pc_price_t,
+    pc_pub_key_t,
+    PC_STATUS_TRADING,
+    PC_STATUS_UNKNOWN,
+    PC_VERSION,
+    PUBLISHER_STATS_SIZE,
+    TIME_MACHINE_STRUCT_SIZE,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::rust_oracle::{
+    add_publisher,
+    del_publisher,
+    resize_price_account,
+    upd_price,
+};
+use crate::sdk::read_publisher_stats;
+use crate::tests::test_utils::{
+    set_master_authority,
+    update_clock_slot,
+    AccountSetup,
+};
+use crate::time_machine_types::{
+    PriceAccountWrapper,
+    SMA_GRANULARITY,
+};
+use crate::utils::pubkey_assign;
+
+#[test]
+fn test_publisher_stats() {
+    let program_id = Pubkey::new_unique();
+
+    let mut publisher_a_setup = AccountSetup::new_funding();
+    let publisher_a = publisher_a_setup.to_account_info();
+    let mut publisher_b_setup = AccountSetup::new_funding();
+    let publisher_b = publisher_b_setup.to_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccountWrapper>(&program_id);
+    let mut price_account = price_setup.to_account_info();
+    price_account.is_signer = false;
+    initialize_pyth_account_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+    {
+        let mut price_data =
+            load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        price_data.initialize_time_machine().unwrap();
+        price_data.price_data.num_ = 2;
+        pubkey_assign(
+            &mut price_data.price_data.comp_[0].pub_,
+            &publisher_a.key.to_bytes(),
+        );
+        pubkey_assign(
+            &mut price_data.price_data.comp_[1].pub_,
+            &publisher_b.key.to_bytes(),
+        );
+    }
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.to_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+
+
+    // No component price to aggregate yet
+    publish(
+        &program_id,
+        &publisher_a,
+        &price_account,
+        &mut clock_account,
+        2,
+        1,
+    );
+    // The price of A is aggregated, the confidence of B exceeds 100 / PC_MAX_CI_DIVISOR
+    publish(
+        &program_id,
+        &publisher_b,
+        &price_account,
+        &mut clock_account,
+        10,
+        2,
+    );
+    assert_eq!(
+        load_checked::<pc_price_t>(&price_account, PC_VERSION)
+            .unwrap()
+            .comp_[1]
+            .latest_
+            .status_,
+        PC_STATUS_UNKNOWN
+    );
+    // The price of A is too old, the price of B has status UNKNOWN, nothing is aggregated
+    publish(
+        &program_id,
+        &publisher_b,
+        &price_account,
+        &mut clock_account,
+        2,
+        40,
+    );
+    // The price of A is still too old, the price of B is aggregated
+    publish(
+        &program_id,
+        &publisher_a,
+        &price_account,
+        &mut clock_account,
+        2,
+        41,
+    );
+
+    {
+        let price_data = load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(
+            price_data.price_data.comp_[0].agg_.status_,
+            PC_STATUS_TRADING
+        );
+        assert_eq!(price_data.price_data.agg_.status_, PC_STATUS_TRADING);
+
+        let stats_a = price_data.publisher_stats[0];
+        assert_eq!(stats_a.num_updates, 2);
+        assert_eq!(stats_a.num_aggregated, 1);
+        assert_eq!(stats_a.num_stale, 2);
+        assert_eq!(stats_a.num_conf_exceeded, 0);
+        assert_eq!(stats_a.last_aggregate_slot, 2);
+
+        let stats_b = price_data.publisher_stats[1];
+        assert_eq!(stats_b.num_updates, 2);
+        assert_eq!(stats_b.num_aggregated, 1);
+        assert_eq!(stats_b.num_stale, 0);
+        assert_eq!(stats_b.num_conf_exceeded, 1);
+        assert_eq!(stats_b.last_aggregate_slot, 41);
+    }
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+    let admin_accounts = [
+        funding_account.clone(),
+        price_account.clone(),
+        permissions_account.clone(),
+    ];
+
+    // A new publisher starts without statistics
+    load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION)
+        .unwrap()
+        .publisher_stats[2]
+        .num_updates = 1;
+    let publisher_c = pc_pub_key_t::new_unique();
+    assert!(add_publisher(
+        &program_id,
+        &admin_accounts,
+        &cmd_add_publisher_t {
+            ver_: PC_VERSION,
+            cmd_: command_t_e_cmd_add_publisher as i32,
+            pub_: publisher_c,
+        }
+    )
+    .is_ok());
+    assert_eq!(
+        load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION)
+            .unwrap()
+            .publisher_stats[2]
+            .num_updates,
+        0
+    );
+    publish(
+        &program_id,
+        &publisher_b,
+        &price_account,
+        &mut clock_account,
+        2,
+        42,
+    );
+
+    // The statistics move with the components of the remaining publishers
+    assert!(del_publisher(
+        &program_id,
+        &admin_accounts,
+        &cmd_del_publisher_t {
+            ver_: PC_VERSION,
+            cmd_: command_t_e_cmd_del_publisher as i32,
+            pub_: pc_pub_key_t {
+                k1_: publisher_a.key.to_bytes(),
+            },
+        }
+    )
+    .is_ok());
+    {
+        let price_data = load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.publisher_stats[0].num_updates, 3);
+        assert_eq!(price_data.publisher_stats[0].last_aggregate_slot, 42);
+        assert_eq!(price_data.publisher_stats[1].num_updates, 0);
+        assert_eq!(price_data.publisher_stats[2].num_updates, 0);
+    }
+    {
+        let data = price_account.try_borrow_data().unwrap();
+        let stats = read_publisher_stats(&data).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].num_conf_exceeded, 1);
+    }
+}
+
+#[test]
+fn test_resize_price_account_stats() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let system_program_key = system_program::id();
+    let mut system_program_balance = 0;
+    let system_program_account = AccountInfo::new(
+        &system_program_key,
+        false,
+        false,
+        &mut system_program_balance,
+        &mut [],
+        &system_program_key,
+        true,
+        Epoch::default(),
+    );
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+
+    let accounts = [
+        funding_account.clone(),
+        price_account.clone(),
+        system_program_account.clone(),
+        permissions_account.clone(),
+    ];
+    let hdr = cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_resize_price_account as i32,
+    };
+
+    // An account resized before the publisher statistics existed keeps its time machine
+    let size_without_stats = size_of::<PriceAccountWrapper>() - PUBLISHER_STATS_SIZE as usize;
+    price_account.realloc(size_without_stats, false).unwrap();
+    {
+        let mut data = price_account.try_borrow_mut_data().unwrap();
+        let granularity_offset = size_without_stats - TIME_MACHINE_STRUCT_SIZE as usize;
+        data[granularity_offset..granularity_offset + 8]
+            .copy_from_slice(&SMA_GRANULARITY.to_le_bytes());
+    }
+    **price_account.try_borrow_mut_lamports().unwrap() =
+        Rent::default().minimum_balance(size_without_stats);
+
+    assert!(resize_price_account(&program_id, &accounts, &hdr).is_ok());
+    assert_eq!(price_account.data_len(), size_of::<PriceAccountWrapper>());
+    {
+        let price_data = load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(
+            price_data.time_machine.sma_tracker.granularity,
+            SMA_GRANULARITY
+        );
+        assert!(price_data
+            .publisher_stats
+            .iter()
+            .all(|stats| stats.num_updates == 0 && stats.last_aggregate_slot == 0));
+    }
+
+    // Resizing again does nothing, the transfer of the system program is not executed in tests
+    **price_account.try_borrow_mut_lamports().unwrap() =
+        Rent::default().minimum_balance(size_of::<PriceAccountWrapper>());
+    assert!(resize_price_account(&program_id, &accounts, &hdr).is_ok());
+    assert_eq!(price_account.data_len(), size_of::<PriceAccountWrapper>());
+}
+
+fn publish<'a>(
+    program_id: &Pubkey,
+    publisher: &AccountInfo<'a>,
+    price_account: &AccountInfo<'a>,
+    clock_account: &mut AccountInfo<'a>,
+    conf: u64,
+    slot: u64,
+) {
+    update_clock_slot(clock_account, slot);
+    assert!(upd_price(
+        program_id,
+        &[
+            publisher.clone(),
+            price_account.clone(),
+            clock_account.clone()
+        ],
+        &populate_instruction(100, conf, slot)
+    )
+    .is_ok());
+}
+
+fn populate_instruction(price: i64, conf: u64, pub_slot: u64) -> cmd_upd_price_t {
+    cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price as i32,
+        status_:    PC_STATUS_TRADING,
+        price_:     price,
+        conf_:      conf,
+        pub_slot_:  pub_slot,
+        pub_index_: 0,
+    }
+}
@@ -0,0 +1,194 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    cmd_query_time_machine_t,
+    command_t_e_cmd_query_time_machine,
+    pc_price_t,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::rust_oracle::query_time_machine;
+use crate::tests::test_utils::{
+    update_clock_timestamp,
+    AccountSetup,
+};
+use crate::time_machine_types::{
+    PriceAccountWrapper,
+    SMA_GRANULARITY,
+    SMA_NUM_BUCKETS,
+};
+
+const EXPO: i32 = -3;
+
+#[test]
+fn test_query_time_machine() {
+    let mut cmd = populate_instruction(SMA_GRANULARITY);
+
+    let program_id = Pubkey::new_unique();
+
+    let mut price_setup = AccountSetup::new::<PriceAccountWrapper>(&program_id);
+    let mut price_account = price_setup.to_account_info();
+    price_account.is_signer = false;
+    price_account.is_writable = false;
+    initialize_pyth_account_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.to_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+    update_clock_timestamp(&mut clock_account, 5 * SMA_GRANULARITY);
+
+    {
+        let mut price_data =
+            load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        price_data.initialize_time_machine().unwrap();
+        price_data.price_data.expo_ = EXPO;
+
+        // The time machine has no data
+        assert_eq!(
+            price_data
+                .time_machine
+                .get_sma(EXPO, 5 * SMA_GRANULARITY, SMA_GRANULARITY),
+            Err(OracleError::SmaUnavailable)
+        );
+
+        // Bucket 1 : 10, 30, bucket 2 is empty, bucket 3 : 50, bucket 4 : 100
+        let tracker = &mut price_data.time_machine.sma_tracker;
+        tracker.add_datapoint(SMA_GRANULARITY, 10, 1).unwrap();
+        tracker.add_datapoint(SMA_GRANULARITY + 1, 30, 3).unwrap();
+        tracker.add_datapoint(3 * SMA_GRANULARITY, 50, 5).unwrap();
+        tracker.add_datapoint(4 * SMA_GRANULARITY, 100, 10).unwrap();
+    }
+
+    assert!(query_time_machine(
+        &program_id,
+        &[price_account.clone(), clock_account.clone()],
+        &cmd
+    )
+    .is_ok());
+
+    {
+        let price_data = load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+
+        // Bucket 4 hasn't been closed by an aggregate in bucket 5 but it is over
+        let sma = price_data
+            .time_machine
+            .get_sma(EXPO, 5 * SMA_GRANULARITY, SMA_GRANULARITY)
+            .unwrap();
+        assert_eq!(sma.price, 100);
+        assert_eq!(sma.conf, 10);
+        assert_eq!(sma.expo, EXPO);
+        assert_eq!(sma.num_buckets, 1);
+
+        // Bucket 2 is skipped
+        let sma = price_data
+            .time_machine
+            .get_sma(EXPO, 5 * SMA_GRANULARITY, 3 * SMA_GRANULARITY)
+            .unwrap();
+        assert_eq!(sma.price, 75);
+        assert_eq!(sma.conf, 7);
+        assert_eq!(sma.num_buckets, 2);
+
+        let sma = price_data
+            .time_machine
+            .get_sma(
+                EXPO,
+                5 * SMA_GRANULARITY,
+                SMA_NUM_BUCKETS as i64 * SMA_GRANULARITY,
+            )
+            .unwrap();
+        assert_eq!(sma.price, 56);
+        assert_eq!(sma.conf, 5);
+        assert_eq!(sma.num_buckets, 3);
+
+        // Same window, computed from the running sums
+        let sma = price_data
+            .time_machine
+            .get_sma(
+                EXPO,
+                4 * SMA_GRANULARITY,
+                SMA_NUM_BUCKETS as i64 * SMA_GRANULARITY,
+            )
+            .unwrap();
+        assert_eq!(sma.price, 35);
+        assert_eq!(sma.conf, 3);
+        assert_eq!(sma.num_buckets, 2);
+
+        // The window has no data
+        assert_eq!(
+            price_data
+                .time_machine
+                .get_sma(EXPO, 10 * SMA_GRANULARITY, 3 * SMA_GRANULARITY),
+            Err(OracleError::SmaUnavailable)
+        );
+        // The window is not a multiple of the granularity, or doesn't fit in the time machine
+        for window in [
+            SMA_GRANULARITY + 1,
+            0,
+            -SMA_GRANULARITY,
+            (SMA_NUM_BUCKETS as i64 + 1) * SMA_GRANULARITY,
+        ] {
+            assert_eq!(
+                price_data
+                    .time_machine
+                    .get_sma(EXPO, 5 * SMA_GRANULARITY, window),
+                Err(OracleError::InvalidSmaWindow)
+            );
+        }
+    }
+
+    cmd = populate_instruction(SMA_GRANULARITY - 1);
+    assert_eq!(
+        query_time_machine(
+            &program_id,
+            &[price_account.clone(), clock_account.clone()],
+            &cmd
+        ),
+        Err(OracleError::InvalidSmaWindow.into())
+    );
+    cmd = populate_instruction(SMA_GRANULARITY);
+    update_clock_timestamp(&mut clock_account, 10 * SMA_GRANULARITY);
+    assert_eq!(
+        query_time_machine(
+            &program_id,
+            &[price_account.clone(), clock_account.clone()],
+            &cmd
+        ),
+        Err(OracleError::SmaUnavailable.into())
+    );
+
+    // Accounts that haven't been resized don't have a time machine
+    let mut small_price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let small_price_account = small_price_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_price_t>(&small_price_account, PC_VERSION).unwrap();
+    assert_eq!(
+        query_time_machine(
+            &program_id,
+            &[small_price_account.clone(), clock_account.clone()],
+            &cmd
+        ),
+        Err(OracleError::InvalidAccountSize.into())
+    );
+
+    // Price account owned by another program
+    assert_eq!(
+        query_time_machine(
+            &Pubkey::new_unique(),
+            &[price_account.clone(), clock_account.clone()],
+            &cmd
+        ),
+        Err(OracleError::InvalidAccountOwner.into())
+    );
+}
+
+fn populate_instruction(window: i64) -> cmd_query_time_machine_t {
+    cmd_query_time_machine_t {
+        ver_:    PC_VERSION,
+        cmd_:    command_t_e_cmd_query_time_machine as i32,
+        window_: window,
+    }
+}
@@ -0,0 +1,178 @@
+use std::mem::size_of;
+
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Epoch;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_program;
+
+use crate::c_oracle_header::{
+    cmd_hdr_t,
+    cmd_resize_product_account_t,
+    command_t_e_cmd_resize_product_account,
+    command_t_e_cmd_upd_product,
+    pc_prod_t,
+    PC_MAX_PROD_ACC_SIZE,
+    PC_PROD_ACC_SIZE,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::rust_oracle::{
+    resize_product_account,
+    upd_product,
+};
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
+
+#[test]
+fn test_resize_product_account() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account = product_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+
+    let system_program_key = system_program::id();
+    let mut system_program_balance = 0;
+    let system_program_account = AccountInfo::new(
+        &system_program_key,
+        false,
+        false,
+        &mut system_program_balance,
+        &mut [],
+        &system_program_key,
+        true,
+        Epoch::default(),
+    );
+
+    let accounts = [
+        funding_account.clone(),
+        product_account.clone(),
+        system_program_account.clone(),
+        permissions_account.clone(),
+    ];
+
+    // The metadata doesn't fit in a product account of the default size
+    let description = "x".repeat(255);
+    let upd_product_hdr = cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_upd_product as i32,
+    };
+    let attributes = [
+        (b"description".to_vec(), description.as_bytes().to_vec()),
+        (b"notes".to_vec(), description.as_bytes().to_vec()),
+    ];
+    assert_eq!(
+        upd_product(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                permissions_account.clone()
+            ],
+            &upd_product_hdr,
+            &attributes
+        ),
+        Err(OracleError::MetadataTooLarge.into())
+    );
+
+    // The system program transfer is not executed in tests, so fund the account beforehand
+    let new_size = 2 * PC_PROD_ACC_SIZE as usize;
+    **product_account.try_borrow_mut_lamports().unwrap() =
+        Rent::default().minimum_balance(new_size);
+
+    assert!(resize_product_account(
+        &program_id,
+        &accounts,
+        &populate_instruction(new_size as u32)
+    )
+    .is_ok());
+    assert_eq!(product_account.data_len(), new_size);
+    {
+        let product_data = load_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+        assert_eq!(product_data.size_, size_of::<pc_prod_t>() as u32);
+    }
+
+    assert!(upd_product(
+        &program_id,
+        &[
+            funding_account.clone(),
+            product_account.clone(),
+            permissions_account.clone()
+        ],
+        &upd_product_hdr,
+        &attributes
+    )
+    .is_ok());
+    {
+        let product_data = load_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+        assert_eq!(
+            product_data.size_ as usize,
+            size_of::<pc_prod_t>() + (1 + 11 + 1 + 255) + (1 + 5 + 1 + 255)
+        );
+    }
+
+    // Product accounts can't shrink or keep their size
+    for size in [PC_PROD_ACC_SIZE, new_size as u32] {
+        assert_eq!(
+            resize_product_account(&program_id, &accounts, &populate_instruction(size)),
+            Err(OracleError::InvalidResize.into())
+        );
+    }
+    assert_eq!(product_account.data_len(), new_size);
+
+    // Product accounts can grow up to PC_MAX_PROD_ACC_SIZE
+    **product_account.try_borrow_mut_lamports().unwrap() =
+        Rent::default().minimum_balance(PC_MAX_PROD_ACC_SIZE as usize);
+    assert!(resize_product_account(
+        &program_id,
+        &accounts,
+        &populate_instruction(PC_MAX_PROD_ACC_SIZE)
+    )
+    .is_ok());
+    assert_eq!(product_account.data_len(), PC_MAX_PROD_ACC_SIZE as usize);
+    assert_eq!(
+        resize_product_account(
+            &program_id,
+            &accounts,
+            &populate_instruction(PC_MAX_PROD_ACC_SIZE + 1)
+        ),
+        Err(OracleError::InvalidResize.into())
+    );
+
+    // The system program is required
+    assert_eq!(
+        resize_product_account(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                funding_account.clone(),
+                permissions_account.clone()
+            ],
+            &populate_instruction(new_size as u32)
+        ),
+        Err(OracleError::InvalidSystemAccount.into())
+    );
+}
+
+fn populate_instruction(size: u32) -> cmd_resize_product_account_t {
+    cmd_resize_product_account_t {
+        ver_:  PC_VERSION,
+        cmd_:  command_t_e_cmd_resize_product_account as i32,
+        size_: size,
+    }
+}
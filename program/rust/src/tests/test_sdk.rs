@@ -0,0 +1,163 @@
+use solana_program::clock::Clock;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    pc_price_t,
+    pc_prod_t,
+    PC_STATUS_TRADING,
+    PC_STATUS_UNKNOWN,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::sdk::{
+    load_price_account,
+    read_price_account,
+    Price,
+};
+use crate::tests::test_utils::AccountSetup;
+
+#[test]
+fn test_sdk() {
+    let program_id = Pubkey::new_unique();
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        price_data.expo_ = -5;
+        price_data.agg_.price_ = 100;
+        price_data.agg_.conf_ = 2;
+        price_data.agg_.status_ = PC_STATUS_TRADING;
+        price_data.timestamp_ = 1000;
+        price_data.twap_.val_ = 90;
+        price_data.twac_.val_ = 3;
+        price_data.prev_price_ = 80;
+        price_data.prev_conf_ = 1;
+        price_data.prev_timestamp_ = 900;
+    }
+
+    {
+        let data = price_account.try_borrow_data().unwrap();
+        let price_data = load_price_account(&data).unwrap();
+        let current = Price {
+            price:        100,
+            conf:         2,
+            expo:         -5,
+            publish_time: 1000,
+        };
+        assert_eq!(price_data.get_current_price(), Some(current));
+        assert_eq!(
+            price_data.get_ema_price(),
+            Price {
+                price:        90,
+                conf:         3,
+                expo:         -5,
+                publish_time: 1000,
+            }
+        );
+        assert_eq!(
+            price_data.get_price_no_older_than(&clock(1010), 10),
+            Some(current)
+        );
+        assert_eq!(price_data.get_price_no_older_than(&clock(1011), 10), None);
+    }
+
+    // When the aggregate is not trading, fall back to the previous price
+    load_checked::<pc_price_t>(&price_account, PC_VERSION)
+        .unwrap()
+        .agg_
+        .status_ = PC_STATUS_UNKNOWN;
+    {
+        let data = price_account.try_borrow_data().unwrap();
+        let price_data = load_price_account(&data).unwrap();
+        assert_eq!(price_data.get_current_price(), None);
+        assert_eq!(
+            price_data.get_price_no_older_than(&clock(1000), 100),
+            Some(Price {
+                price:        80,
+                conf:         1,
+                expo:         -5,
+                publish_time: 900,
+            })
+        );
+        assert_eq!(price_data.get_price_no_older_than(&clock(1000), 99), None);
+    }
+
+    // A price that was never trading is never returned
+    load_checked::<pc_price_t>(&price_account, PC_VERSION)
+        .unwrap()
+        .prev_timestamp_ = 0;
+    {
+        let data = price_account.try_borrow_data().unwrap();
+        let price_data = load_price_account(&data).unwrap();
+        assert_eq!(price_data.get_price_no_older_than(&clock(1000), 1000), None);
+    }
+
+    // RPC buffers are not aligned, they can be read but not loaded
+    {
+        let data = price_account.try_borrow_data().unwrap();
+        let mut buffer = vec![0u8; data.len() + 8];
+        let offset = buffer.as_ptr().align_offset(8) + 1;
+        buffer[offset..offset + data.len()].copy_from_slice(&data);
+        let unaligned = &buffer[offset..offset + data.len()];
+        assert_eq!(
+            load_price_account(unaligned).err(),
+            Some(ProgramError::InvalidArgument)
+        );
+        let price_data = read_price_account(unaligned).unwrap();
+        assert_eq!(price_data.get_ema_price().price, 90);
+        assert_eq!(price_data.prev_timestamp_, 0);
+    }
+
+    // The header is checked
+    {
+        let data = price_account.try_borrow_data().unwrap();
+        assert_eq!(
+            load_price_account(&data[..100]).err(),
+            Some(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            read_price_account(&data[..100]).err(),
+            Some(ProgramError::InvalidArgument)
+        );
+    }
+    load_checked::<pc_price_t>(&price_account, PC_VERSION)
+        .unwrap()
+        .ver_ = PC_VERSION + 1;
+    {
+        let data = price_account.try_borrow_data().unwrap();
+        assert_eq!(
+            load_price_account(&data).err(),
+            Some(OracleError::InvalidAccountVersion.into())
+        );
+    }
+
+    let mut product_setup = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account = product_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_prod_t>(&product_account, PC_VERSION).unwrap();
+    {
+        let data = product_account.try_borrow_data().unwrap();
+        assert_eq!(
+            load_price_account(&data).err(),
+            Some(OracleError::InvalidAccountHeader.into())
+        );
+        assert_eq!(
+            read_price_account(&data).err(),
+            Some(OracleError::InvalidAccountHeader.into())
+        );
+    }
+}
+
+fn clock(unix_timestamp: i64) -> Clock {
+    Clock {
+        unix_timestamp,
+        ..Clock::default()
+    }
+}
@@ -0,0 +1,198 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    cmd_set_max_ci_divisor_t,
+    cmd_upd_price_t,
+    command_t_e_cmd_set_max_ci_divisor,
+    command_t_e_cmd_upd_price,
+    pc_price_t,
+    PC_STATUS_TRADING,
+    PC_STATUS_UNKNOWN,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::rust_oracle::{
+    set_max_ci_divisor,
+    upd_price,
+};
+use crate::tests::test_utils::{
+    set_master_authority,
+    update_clock_slot,
+    AccountSetup,
+};
+use crate::utils::pubkey_assign;
+
+#[test]
+fn test_set_max_ci_divisor() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+
+    assert_eq!(get_max_ci_divisor(&price_account), Ok(0));
+
+    let mut cmd = populate_instruction(1000);
+    assert!(set_max_ci_divisor(
+        &program_id,
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &cmd
+    )
+    .is_ok());
+    assert_eq!(get_max_ci_divisor(&price_account), Ok(1000));
+
+    // A divisor of 1 would let the threshold overflow
+    cmd = populate_instruction(1);
+    assert_eq!(
+        set_max_ci_divisor(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &cmd
+        ),
+        Err(OracleError::InvalidMaxCiDivisor.into())
+    );
+    assert_eq!(get_max_ci_divisor(&price_account), Ok(1000));
+
+    cmd = populate_instruction(0);
+    assert!(set_max_ci_divisor(
+        &program_id,
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &cmd
+    )
+    .is_ok());
+    assert_eq!(get_max_ci_divisor(&price_account), Ok(0));
+}
+
+#[test]
+fn test_upd_price_uses_max_ci_divisor() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let mut price_account = price_setup.to_account_info();
+    price_account.is_signer = false;
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        price_data.num_ = 1;
+        pubkey_assign(
+            &mut price_data.comp_[0].pub_,
+            &funding_account.key.to_bytes(),
+        );
+    }
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.to_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+
+    let accounts = [
+        funding_account.clone(),
+        price_account.clone(),
+        clock_account.clone(),
+    ];
+
+    // The default divisor of 20 rejects a conf of 10% of the price
+    update_clock_slot(&mut clock_account, 1);
+    let mut cmd = populate_upd_price_instruction(100, 10, 1);
+    assert!(upd_price(&program_id, &accounts, &cmd).is_ok());
+    assert_eq!(get_component_status(&price_account), PC_STATUS_UNKNOWN);
+
+    // A divisor of 2 accepts it
+    set_max_ci_divisor_field(&price_account, 2);
+    update_clock_slot(&mut clock_account, 2);
+    cmd = populate_upd_price_instruction(100, 10, 2);
+    assert!(upd_price(&program_id, &accounts, &cmd).is_ok());
+    assert_eq!(get_component_status(&price_account), PC_STATUS_TRADING);
+
+    // Negative prices are compared against the absolute value of the threshold
+    update_clock_slot(&mut clock_account, 3);
+    cmd = populate_upd_price_instruction(-100, 50, 3);
+    assert!(upd_price(&program_id, &accounts, &cmd).is_ok());
+    assert_eq!(get_component_status(&price_account), PC_STATUS_TRADING);
+
+    // A divisor of 1000 rejects a conf of 0.2% of the price, which the default accepts
+    set_max_ci_divisor_field(&price_account, 1000);
+    update_clock_slot(&mut clock_account, 4);
+    cmd = populate_upd_price_instruction(100_000, 200, 4);
+    assert!(upd_price(&program_id, &accounts, &cmd).is_ok());
+    assert_eq!(get_component_status(&price_account), PC_STATUS_UNKNOWN);
+
+    set_max_ci_divisor_field(&price_account, 0);
+    update_clock_slot(&mut clock_account, 5);
+    cmd = populate_upd_price_instruction(100_000, 200, 5);
+    assert!(upd_price(&program_id, &accounts, &cmd).is_ok());
+    assert_eq!(get_component_status(&price_account), PC_STATUS_TRADING);
+}
+
+// Create a set_max_ci_divisor instruction that sets the divisor to max_ci_divisor
+fn populate_instruction(max_ci_divisor: u16) -> cmd_set_max_ci_divisor_t {
+    cmd_set_max_ci_divisor_t {
+        ver_:            PC_VERSION,
+        cmd_:            command_t_e_cmd_set_max_ci_divisor as i32,
+        max_ci_divisor_: max_ci_divisor,
+    }
+}
+
+// Create an upd_price instruction with the provided parameters
+fn populate_upd_price_instruction(price: i64, conf: u64, pub_slot: u64) -> cmd_upd_price_t {
+    cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price as i32,
+        status_:    PC_STATUS_TRADING,
+        price_:     price,
+        conf_:      conf,
+        pub_slot_:  pub_slot,
+        pub_index_: 0,
+    }
+}
+
+fn set_max_ci_divisor_field(account: &AccountInfo, max_ci_divisor: u16) {
+    load_checked::<pc_price_t>(account, PC_VERSION)
+        .unwrap()
+        .max_ci_divisor_ = max_ci_divisor;
+}
+
+fn get_max_ci_divisor(account: &AccountInfo) -> Result<u16, ProgramError> {
+    Ok(load_checked::<pc_price_t>(account, PC_VERSION)?.max_ci_divisor_)
+}
+
+fn get_component_status(account: &AccountInfo) -> u32 {
+    load_checked::<pc_price_t>(account, PC_VERSION)
+        .unwrap()
+        .comp_[0]
+        .latest_
+        .status_
+}
@@ -0,0 +1,171 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::aggregate::upd_aggregate;
+use crate::c_oracle_header::{
+    cmd_set_max_latency_t,
+    command_t_e_cmd_set_max_latency,
+    pc_price_info_t,
+    pc_price_t,
+    PC_MAX_SEND_LATENCY,
+    PC_STATUS_TRADING,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::rust_oracle::set_max_latency;
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
+use crate::OracleError;
+
+#[test]
+fn test_set_max_latency() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+
+    assert_eq!(get_max_latency(&price_account), Ok(0));
+
+    let mut cmd = populate_instruction(100);
+    assert!(set_max_latency(
+        &program_id,
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &cmd
+    )
+    .is_ok());
+    assert_eq!(get_max_latency(&price_account), Ok(100));
+
+    cmd = populate_instruction(5);
+    assert!(set_max_latency(
+        &program_id,
+        &[
+            funding_account.clone(),
+            price_account.clone(),
+            permissions_account.clone()
+        ],
+        &cmd
+    )
+    .is_ok());
+    assert_eq!(get_max_latency(&price_account), Ok(5));
+
+    // The price account doesn't need to sign, but must be writable
+    let mut unsigned_price_account = price_account.clone();
+    unsigned_price_account.is_signer = false;
+    assert!(set_max_latency(
+        &program_id,
+        &[
+            funding_account.clone(),
+            unsigned_price_account.clone(),
+            permissions_account.clone()
+        ],
+        &cmd
+    )
+    .is_ok());
+    unsigned_price_account.is_writable = false;
+    assert_eq!(
+        set_max_latency(
+            &program_id,
+            &[
+                funding_account.clone(),
+                unsigned_price_account,
+                permissions_account.clone()
+            ],
+            &cmd
+        ),
+        Err(OracleError::InvalidWritableAccount.into())
+    );
+
+    // Wrong number of accounts
+    assert_eq!(
+        set_max_latency(
+            &program_id,
+            &[funding_account, permissions_account.clone()],
+            &cmd
+        ),
+        Err(OracleError::InvalidNumberOfAccounts.into())
+    );
+}
+
+#[test]
+fn test_aggregate_uses_max_latency() {
+    let program_id = Pubkey::new_unique();
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+
+    let fresh_slot = 1000;
+    let stale_slot = fresh_slot - u64::from(PC_MAX_SEND_LATENCY) - 5;
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        price_data.num_ = 2;
+        price_data.comp_[0].latest_ = trading_price(100, 10, fresh_slot);
+        price_data.comp_[1].latest_ = trading_price(200, 20, stale_slot);
+    }
+
+    // By default, the stale publisher is left out of the aggregate
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert!(upd_aggregate(&mut price_data, &mut [], fresh_slot + 1, 1));
+        assert_eq!(price_data.num_qt_, 1);
+        assert_eq!(price_data.agg_.price_, 100);
+    }
+
+    // A larger latency lets it in
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        price_data.max_latency_ = 40;
+        assert!(upd_aggregate(&mut price_data, &mut [], fresh_slot + 2, 2));
+        assert_eq!(price_data.num_qt_, 2);
+        assert_eq!(price_data.agg_.price_, 145);
+    }
+
+    // A smaller latency also excludes recent publishers
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        price_data.max_latency_ = 2;
+        assert!(!upd_aggregate(&mut price_data, &mut [], fresh_slot + 3, 3));
+        assert_eq!(price_data.num_qt_, 0);
+    }
+}
+
+// Create a set_max_latency instruction that sets the latency to max_latency
+fn populate_instruction(max_latency: u8) -> cmd_set_max_latency_t {
+    cmd_set_max_latency_t {
+        ver_:         PC_VERSION,
+        cmd_:         command_t_e_cmd_set_max_latency as i32,
+        max_latency_: max_latency,
+    }
+}
+
+fn get_max_latency(account: &AccountInfo) -> Result<u8, ProgramError> {
+    Ok(load_checked::<pc_price_t>(account, PC_VERSION)?.max_latency_)
+}
+
+fn trading_price(price: i64, conf: u64, pub_slot: u64) -> pc_price_info_t {
+    pc_price_info_t {
+        price_:           price,
+        conf_:            conf,
+        status_:          PC_STATUS_TRADING,
+        pub_slot_:        pub_slot,
+        corp_act_status_: 0,
+    }
+}
@@ -0,0 +1,273 @@
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+
+use crate::c_oracle_header::{
+    cmd_query_time_machine_t,
+    cmd_upd_price_t,
+    command_t_e_cmd_query_time_machine,
+    command_t_e_cmd_upd_price,
+    pc_price_t,
+    PC_STATUS_TRADING,
+    PC_VERSION,
+    PUBLISHER_STATS_SIZE,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+    load_time_machine_checked,
+};
+use crate::error::OracleError;
+use crate::rust_oracle::{
+    query_time_machine,
+    upd_price,
+};
+use crate::tests::test_utils::{
+    update_clock_slot,
+    update_clock_timestamp,
+    AccountSetup,
+};
+use crate::time_machine_types::{
+    PriceAccountWrapper,
+    SmaTracker,
+    SMA_GRANULARITY,
+    SMA_NUM_BUCKETS,
+};
+use crate::utils::pubkey_assign;
+use bytemuck::Zeroable;
+use std::mem::size_of;
+
+#[test]
+fn test_sma_tracker() {
+    let mut tracker: SmaTracker<4> = Zeroable::zeroed();
+    assert!(tracker.add_datapoint(0, 1, 1).is_err());
+    assert!(tracker.initialize(0).is_err());
+    tracker.initialize(10).unwrap();
+    assert_eq!(tracker.get_sma(), None);
+
+    // Bucket 10 : prices 10, 20, 30
+    tracker.add_datapoint(100, 10, 1).unwrap();
+    tracker.add_datapoint(105, 20, 2).unwrap();
+    tracker.add_datapoint(109, 30, 6).unwrap();
+    // The current bucket is not included until it's closed
+    assert_eq!(tracker.get_sma(), None);
+    assert_eq!(tracker.current_count, 3);
+
+    // Bucket 11 : price 40
+    tracker.add_datapoint(110, 40, 4).unwrap();
+    assert_eq!(tracker.entries[2].price, 20);
+    assert_eq!(tracker.entries[2].conf, 3);
+    assert_eq!(tracker.get_sma(), Some((20, 3)));
+
+    // Going back in time is ignored
+    tracker.add_datapoint(99, 1000, 1000).unwrap();
+    assert_eq!(tracker.current_count, 1);
+
+    // Bucket 13 : price 60, bucket 12 is skipped
+    tracker.add_datapoint(130, 60, 6).unwrap();
+    assert_eq!(tracker.entries[3].price, 40);
+    assert_eq!(tracker.entries[0].valid, 0);
+    assert_eq!(tracker.running_valid_count, 2);
+    assert_eq!(tracker.get_sma(), Some((30, 3)));
+
+    // Bucket 14 : price 80
+    tracker.add_datapoint(140, 80, 8).unwrap();
+    assert_eq!(tracker.entries[1].price, 60);
+    assert_eq!(tracker.get_sma(), Some((40, 4)));
+
+    // Bucket 15 : wraps around, bucket 14 evicts bucket 10
+    tracker.add_datapoint(150, 100, 10).unwrap();
+    assert_eq!(tracker.entries[2].price, 80);
+    assert_eq!(tracker.running_valid_count, 3);
+    assert_eq!(tracker.get_sma(), Some((60, 6)));
+
+    // More than `NUM_BUCKETS` buckets are skipped, everything is evicted
+    tracker.add_datapoint(1000, 100, 10).unwrap();
+    assert_eq!(tracker.running_valid_count, 0);
+    assert_eq!(tracker.get_sma(), None);
+    tracker.add_datapoint(1010, 100, 10).unwrap();
+    assert_eq!(tracker.get_sma(), Some((100, 10)));
+
+    // Overflowing buckets are ignored
+    tracker.add_datapoint(1020, i64::MAX, 10).unwrap();
+    tracker.add_datapoint(1021, i64::MAX, 10).unwrap();
+    tracker.add_datapoint(1030, 200, 20).unwrap();
+    assert_eq!(tracker.entries[2].valid, 0);
+    assert_eq!(tracker.running_valid_count, 2);
+    assert_eq!(tracker.get_sma(), Some((100, 10)));
+
+    // Running sums that don't match the buckets are an error instead of wrapping around
+    tracker.current_is_valid = 0;
+    tracker.running_conf_sum = 0;
+    assert_eq!(
+        tracker.add_datapoint(1050, 100, 10),
+        Err(OracleError::IntegerCastingError)
+    );
+}
+
+#[test]
+fn test_sma_tracker_epoch_zero() {
+    let mut tracker: SmaTracker<4> = Zeroable::zeroed();
+    tracker.initialize(10).unwrap();
+
+    // Datapoints in bucket -1, then in bucket 0 which closes bucket -1
+    tracker.add_datapoint(-5, 10, 1).unwrap();
+    assert_eq!(tracker.current_epoch, -1);
+    tracker.add_datapoint(0, 20, 2).unwrap();
+    assert_eq!(tracker.current_epoch, 0);
+    assert_eq!(tracker.get_sma(), Some((10, 1)));
+
+    // Bucket 0 is a genuine bucket, its first datapoint doesn't restart the tracker
+    tracker.add_datapoint(5, 40, 4).unwrap();
+    assert_eq!(tracker.current_count, 2);
+    tracker.add_datapoint(10, 0, 0).unwrap();
+    assert_eq!(tracker.entries[0].price, 30);
+    assert_eq!(tracker.get_sma(), Some((20, 2)));
+}
+
+#[test]
+fn test_upd_price_updates_sma() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccountWrapper>(&program_id);
+    let mut price_account = price_setup.to_account_info();
+    price_account.is_signer = false;
+    initialize_pyth_account_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+
+    {
+        let mut price_data =
+            load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        price_data.initialize_time_machine().unwrap();
+        price_data.price_data.num_ = 1;
+        pubkey_assign(
+            &mut price_data.price_data.comp_[0].pub_,
+            &funding_account.key.to_bytes(),
+        );
+        assert_eq!(
+            price_data.time_machine.sma_tracker.granularity,
+            SMA_GRANULARITY
+        );
+    }
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.to_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+
+    // Publish 42 in slot 1, it gets aggregated in slot 2, then 44 gets aggregated in slot 3
+    for (slot, price) in [(1, 42), (2, 44), (3, 44)] {
+        update_clock_slot(&mut clock_account, slot);
+        update_clock_timestamp(&mut clock_account, slot as i64 * SMA_GRANULARITY);
+        let cmd = populate_instruction(price, 2, slot);
+        assert!(upd_price(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                clock_account.clone()
+            ],
+            &cmd
+        )
+        .is_ok());
+    }
+
+    {
+        let price_data = load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        let tracker = &price_data.time_machine.sma_tracker;
+        assert_eq!(tracker.current_epoch, 3);
+        assert_eq!(tracker.current_price_sum, 44);
+        assert_eq!(tracker.entries[2 % SMA_NUM_BUCKETS].price, 42);
+        assert_eq!(tracker.get_sma(), Some((42, 2)));
+    }
+}
+
+#[test]
+fn test_upd_price_updates_sma_without_stats() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    // An account resized before the publisher statistics were added
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let mut price_account = price_setup.to_account_info();
+    price_account.is_signer = false;
+    let size_without_stats = size_of::<PriceAccountWrapper>() - PUBLISHER_STATS_SIZE as usize;
+    price_account.realloc(size_without_stats, false).unwrap();
+    **price_account.try_borrow_mut_lamports().unwrap() =
+        Rent::default().minimum_balance(size_without_stats);
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+    {
+        let mut data = price_account.try_borrow_mut_data().unwrap();
+        let (price_data, time_machine) = load_time_machine_checked(&mut data, PC_VERSION).unwrap();
+        time_machine
+            .sma_tracker
+            .initialize(SMA_GRANULARITY)
+            .unwrap();
+        time_machine.tick_tracker.initialize().unwrap();
+        price_data.num_ = 1;
+        pubkey_assign(
+            &mut price_data.comp_[0].pub_,
+            &funding_account.key.to_bytes(),
+        );
+    }
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.to_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+
+    for (slot, price) in [(1, 42), (2, 44), (3, 44)] {
+        update_clock_slot(&mut clock_account, slot);
+        update_clock_timestamp(&mut clock_account, slot as i64 * SMA_GRANULARITY);
+        assert!(upd_price(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                clock_account.clone()
+            ],
+            &populate_instruction(price, 2, slot)
+        )
+        .is_ok());
+    }
+
+    assert_eq!(price_account.data_len(), size_without_stats);
+    {
+        let mut data = price_account.try_borrow_mut_data().unwrap();
+        let (_, time_machine) = load_time_machine_checked(&mut data, PC_VERSION).unwrap();
+        assert_eq!(time_machine.sma_tracker.current_epoch, 3);
+        assert_eq!(time_machine.sma_tracker.get_sma(), Some((42, 2)));
+        assert_eq!(
+            time_machine.tick_tracker.get(0).unwrap().price_info.price_,
+            44
+        );
+    }
+
+    // The time machine of the account can be queried
+    assert!(query_time_machine(
+        &program_id,
+        &[price_account.clone(), clock_account.clone()],
+        &cmd_query_time_machine_t {
+            ver_:    PC_VERSION,
+            cmd_:    command_t_e_cmd_query_time_machine as i32,
+            window_: SMA_GRANULARITY,
+        }
+    )
+    .is_ok());
+}
+
+// Create an upd_price instruction with the provided parameters
+fn populate_instruction(price: i64, conf: u64, pub_slot: u64) -> cmd_upd_price_t {
+    cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price as i32,
+        status_:    PC_STATUS_TRADING,
+        price_:     price,
+        conf_:      conf,
+        pub_slot_:  pub_slot,
+        pub_index_: 0,
+    }
+}
@@ -0,0 +1,166 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    cmd_upd_price_t,
+    command_t_e_cmd_upd_price,
+    pc_price_info_t,
+    PC_STATUS_TRADING,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::rust_oracle::upd_price;
+use crate::tests::test_utils::{
+    update_clock_slot,
+    update_clock_timestamp,
+    AccountSetup,
+};
+use crate::time_machine_types::{
+    PriceAccountWrapper,
+    TickTracker,
+    NUM_TICKS,
+};
+use crate::utils::pubkey_assign;
+use bytemuck::Zeroable;
+
+#[test]
+fn test_tick_tracker() {
+    let mut tracker: TickTracker<3> = Zeroable::zeroed();
+    tracker.initialize().unwrap();
+    assert!(tracker.get(0).is_none());
+    assert_eq!(tracker.iter().count(), 0);
+
+    for slot in 1..=2 {
+        tracker
+            .add_tick(&price_info(slot as i64 * 10, slot), slot as i64)
+            .unwrap();
+    }
+    assert_eq!(tracker.num_ticks, 2);
+    assert_eq!(tracker.get(0).unwrap().price_info.price_, 20);
+    assert_eq!(tracker.get(1).unwrap().price_info.price_, 10);
+    assert_eq!(tracker.get(1).unwrap().timestamp, 1);
+    assert!(tracker.get(2).is_none());
+
+    // Wrap around, the oldest ticks get overwritten
+    for slot in 3..=7 {
+        tracker
+            .add_tick(&price_info(slot as i64 * 10, slot), slot as i64)
+            .unwrap();
+    }
+    assert_eq!(tracker.num_ticks, 3);
+    assert_eq!(tracker.next_index, 1);
+    assert_eq!(
+        tracker
+            .iter()
+            .map(|tick| tick.price_info.price_)
+            .collect::<Vec<_>>(),
+        vec![70, 60, 50]
+    );
+    assert!(tracker.get(3).is_none());
+    assert_eq!(tracker.get_at_slot(6).unwrap().price_info.price_, 60);
+    assert!(tracker.get_at_slot(4).is_none());
+    assert!(tracker.get_at_slot(8).is_none());
+}
+
+#[test]
+fn test_upd_price_updates_tick_tracker() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut price_setup = AccountSetup::new::<PriceAccountWrapper>(&program_id);
+    let mut price_account = price_setup.to_account_info();
+    price_account.is_signer = false;
+    initialize_pyth_account_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+
+    {
+        let mut price_data =
+            load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        price_data.initialize_time_machine().unwrap();
+        price_data.price_data.num_ = 1;
+        pubkey_assign(
+            &mut price_data.price_data.comp_[0].pub_,
+            &funding_account.key.to_bytes(),
+        );
+    }
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.to_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+
+    // Nothing is published in slots 3 to 39, in slot 40 the latest price is too old to be
+    // aggregated
+    let slots: Vec<u64> = (1..=2).chain(40..=(40 + NUM_TICKS as u64)).collect();
+    for slot in slots {
+        update_clock_slot(&mut clock_account, slot);
+        update_clock_timestamp(&mut clock_account, 100 * slot as i64);
+        let cmd = populate_instruction(100 + slot as i64, 1, slot);
+        assert!(upd_price(
+            &program_id,
+            &[
+                funding_account.clone(),
+                price_account.clone(),
+                clock_account.clone()
+            ],
+            &cmd
+        )
+        .is_ok());
+
+        if slot == 41 {
+            let price_data =
+                load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+            let tracker = &price_data.time_machine.tick_tracker;
+            assert_eq!(tracker.num_ticks, 2);
+
+            let tick = tracker.get(0).unwrap();
+            assert_eq!(tick.price_info.pub_slot_, 41);
+            assert_eq!(tick.price_info.price_, 140);
+            assert_eq!(tick.price_info.status_, PC_STATUS_TRADING);
+            assert_eq!(tick.timestamp, 4100);
+
+            assert_eq!(tracker.get_at_slot(2).unwrap().price_info.price_, 101);
+            for skipped_slot in 3..=40 {
+                assert!(tracker.get_at_slot(skipped_slot).is_none());
+            }
+        }
+    }
+
+    {
+        let price_data = load_checked::<PriceAccountWrapper>(&price_account, PC_VERSION).unwrap();
+        let tracker = &price_data.time_machine.tick_tracker;
+        assert_eq!(tracker.num_ticks, NUM_TICKS as u64);
+        assert_eq!(
+            tracker.get(0).unwrap().price_info.pub_slot_,
+            40 + NUM_TICKS as u64
+        );
+        assert_eq!(tracker.get(NUM_TICKS - 1).unwrap().price_info.pub_slot_, 41);
+        assert!(tracker.get_at_slot(2).is_none());
+    }
+}
+
+fn price_info(price: i64, pub_slot: u64) -> pc_price_info_t {
+    pc_price_info_t {
+        price_:           price,
+        conf_:            1,
+        status_:          PC_STATUS_TRADING,
+        corp_act_status_: 0,
+        pub_slot_:        pub_slot,
+    }
+}
+
+// Create an upd_price instruction with the provided parameters
+fn populate_instruction(price: i64, conf: u64, pub_slot: u64) -> cmd_upd_price_t {
+    cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price as i32,
+        status_:    PC_STATUS_TRADING,
+        price_:     price,
+        conf_:      conf,
+        pub_slot_:  pub_slot,
+        pub_index_: 0,
+    }
+}
@@ -4,19 +4,22 @@ use std::mem::size_of;
 use crate::c_oracle_header::{
     cmd_upd_price_t,
     command_t_e_cmd_upd_price,
+    pc_price_comp_t,
     pc_price_info_t,
     pc_price_t,
+    PC_COMP_SIZE,
+    PC_EXTRA_COMP_SIZE,
     PC_STATUS_TRADING,
     PC_STATUS_UNKNOWN,
     PC_VERSION,
 };
 
+use crate::aggregate::upd_aggregate;
 use crate::deserialize::{
     initialize_pyth_account_checked,
     load_checked,
     load_mut,
 };
-use crate::rust_oracle::c_upd_aggregate;
 use crate::tests::test_utils::AccountSetup;
 #[test]
 fn test_upd_aggregate() {
@@ -70,12 +73,9 @@ fn test_upd_aggregate() {
         price_data.agg_.pub_slot_ = 1000;
         price_data.comp_[0].latest_ = p1;
     }
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1001,
-            1,
-        ));
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert!(upd_aggregate(&mut price_data, &mut [], 1001, 1));
     }
 
     {
@@ -104,12 +104,9 @@ fn test_upd_aggregate() {
         price_data.comp_[1].latest_ = p2;
     }
 
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1001,
-            2,
-        ));
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert!(upd_aggregate(&mut price_data, &mut [], 1001, 2));
     }
 
     {
@@ -140,12 +137,9 @@ fn test_upd_aggregate() {
         price_data.comp_[2].latest_ = p3;
     }
 
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1001,
-            3,
-        ));
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert!(upd_aggregate(&mut price_data, &mut [], 1001, 3));
     }
 
     {
@@ -176,12 +170,9 @@ fn test_upd_aggregate() {
         price_data.comp_[3].latest_ = p4;
     }
 
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1001,
-            4,
-        ));
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert!(upd_aggregate(&mut price_data, &mut [], 1001, 4));
     }
 
     {
@@ -200,12 +191,9 @@ fn test_upd_aggregate() {
         assert_eq!(price_data.prev_timestamp_, 3);
     }
 
-    unsafe {
-        assert!(c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1025,
-            5,
-        ));
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert!(upd_aggregate(&mut price_data, &mut [], 1025, 5));
     }
 
     {
@@ -222,12 +210,9 @@ fn test_upd_aggregate() {
     }
 
     // check what happens when nothing publishes for a while
-    unsafe {
-        assert!(!c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1026,
-            10,
-        ));
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert!(!upd_aggregate(&mut price_data, &mut [], 1026, 10));
     }
     {
         let price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
@@ -242,12 +227,9 @@ fn test_upd_aggregate() {
         assert_eq!(price_data.prev_timestamp_, 5);
     }
 
-    unsafe {
-        assert!(!c_upd_aggregate(
-            price_account.try_borrow_mut_data().unwrap().as_mut_ptr(),
-            1028,
-            12,
-        ));
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert!(!upd_aggregate(&mut price_data, &mut [], 1028, 12));
     }
 
     {
@@ -264,6 +246,78 @@ fn test_upd_aggregate() {
     }
 }
 
+#[test]
+fn test_upd_aggregate_extra_components() {
+    let quote = |price: i64, status: u32| pc_price_info_t {
+        price_:           price,
+        conf_:            10,
+        status_:          status,
+        pub_slot_:        1000,
+        corp_act_status_: 0,
+    };
+
+    let program_id = Pubkey::new_unique();
+
+    let mut price_setup = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account = price_setup.to_account_info();
+    initialize_pyth_account_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+
+    // comp_ is full with 2 valid quotes at 100, the extra components all quote 300
+    let mut extra_components: [pc_price_comp_t; PC_EXTRA_COMP_SIZE as usize] =
+        bytemuck::Zeroable::zeroed();
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        price_data.num_ = PC_COMP_SIZE;
+        price_data.num_extra_ = PC_EXTRA_COMP_SIZE;
+        price_data.last_slot_ = 1000;
+        price_data.agg_.pub_slot_ = 1000;
+        for (i, component) in price_data.comp_.iter_mut().enumerate() {
+            let status = if i < 2 {
+                PC_STATUS_TRADING
+            } else {
+                PC_STATUS_UNKNOWN
+            };
+            component.latest_ = quote(100, status);
+        }
+        for component in extra_components.iter_mut() {
+            component.latest_ = quote(300, PC_STATUS_TRADING);
+        }
+        assert!(upd_aggregate(
+            &mut price_data,
+            &mut extra_components,
+            1001,
+            1
+        ));
+    }
+    {
+        let price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.num_qt_, 2 + PC_EXTRA_COMP_SIZE);
+        assert_eq!(price_data.agg_.price_, 300);
+        assert_eq!(price_data.agg_.conf_, 10);
+        assert_eq!(extra_components[0].agg_.price_, 300);
+    }
+
+    // Only the num_extra_ first extra components are part of the aggregate
+    {
+        let mut price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        price_data.num_extra_ = 1;
+        extra_components[1].agg_ = bytemuck::Zeroable::zeroed();
+        assert!(upd_aggregate(
+            &mut price_data,
+            &mut extra_components,
+            1002,
+            2
+        ));
+    }
+    {
+        let price_data = load_checked::<pc_price_t>(&price_account, PC_VERSION).unwrap();
+        assert_eq!(price_data.num_qt_, 3);
+        // Median of 100 - 10, 100, 100 + 10 twice and 300 - 10, 300, 300 + 10
+        assert_eq!(price_data.agg_.price_, 110);
+        assert_eq!(extra_components[1].agg_.price_, 0);
+    }
+}
+
 // Create an upd_price instruction with the provided parameters
 fn populate_instruction(instruction_data: &mut [u8], price: i64, conf: u64, pub_slot: u64) -> () {
     let mut cmd = load_mut::<cmd_upd_price_t>(instruction_data).unwrap();
@@ -273,5 +327,5 @@ fn populate_instruction(instruction_data: &mut [u8], price: i64, conf: u64, pub_
     cmd.price_ = price;
     cmd.conf_ = conf;
     cmd.pub_slot_ = pub_slot;
-    cmd.unused_ = 0;
+    cmd.pub_index_ = 0;
 }
@@ -0,0 +1,115 @@
+use bytemuck::{
+    bytes_of,
+    bytes_of_mut,
+    Zeroable,
+};
+
+use crate::aggregate::upd_aggregate;
+use crate::c_oracle_header::{
+    pc_price_t,
+    PC_COMP_SIZE,
+    PC_MAX_NUM_DECIMALS,
+    PC_STATUS_HALTED,
+    PC_STATUS_TRADING,
+    PC_STATUS_UNKNOWN,
+};
+use crate::rust_oracle::c_upd_aggregate;
+
+/// xorshift64* pseudo random number generator, so that failures are reproducible
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    /// Uniform in `[0, n)`
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// Random number with a random order of magnitude, up to `max_digits` decimal digits
+    fn magnitude(&mut self, max_digits: u32) -> u64 {
+        let digits = self.below(u64::from(max_digits) + 1) as u32;
+        self.below(10u64.pow(digits)) + self.below(2)
+    }
+
+    fn status(&mut self) -> u32 {
+        match self.below(10) {
+            0 => PC_STATUS_UNKNOWN,
+            1 => PC_STATUS_HALTED,
+            _ => PC_STATUS_TRADING,
+        }
+    }
+}
+
+/// Run the Rust and the C aggregation on random price accounts and check that they produce the
+/// same account data
+#[test]
+fn test_upd_aggregate_matches_c() {
+    let mut rng = Rng(0x05ee_d0f0_ac1e);
+
+    for _ in 0..2000 {
+        let mut price_data: pc_price_t = Zeroable::zeroed();
+        price_data.expo_ =
+            rng.below(2 * u64::from(PC_MAX_NUM_DECIMALS) + 1) as i32 - PC_MAX_NUM_DECIMALS as i32;
+        price_data.num_ = rng.below(u64::from(PC_COMP_SIZE) + 1) as u32;
+        price_data.min_pub_ = rng.below(4) as u8;
+        if rng.below(2) == 0 {
+            price_data.max_latency_ = rng.below(40) as u8;
+        }
+        // Start from a random ema state some of the time
+        if rng.below(4) == 0 {
+            price_data.twap_.numer_ = rng.next() as i64;
+            price_data.twap_.denom_ = rng.next() as i64;
+            price_data.twac_.numer_ = rng.next() as i64;
+            price_data.twac_.denom_ = rng.next() as i64;
+        }
+
+        // Start late enough for the first aggregate to initialize the ema, as it would on chain
+        let mut slot: u64 = 100_000_000 + rng.below(1000);
+        let base_price = rng.magnitude(17) as i64;
+        for _ in 0..10 {
+            for component in price_data.comp_.iter_mut().take(price_data.num_ as usize) {
+                if rng.below(3) == 0 {
+                    continue;
+                }
+                let latest = &mut component.latest_;
+                latest.price_ = match rng.below(10) {
+                    0 => -(rng.magnitude(18) as i64),
+                    1 => rng.magnitude(18) as i64,
+                    _ => base_price + (rng.magnitude(3) as i64) - 500,
+                };
+                latest.conf_ = match rng.below(10) {
+                    0 => rng.next(),
+                    _ => rng.magnitude(4),
+                };
+                latest.status_ = rng.status();
+                latest.pub_slot_ = slot.saturating_sub(rng.below(30));
+            }
+
+            slot += match rng.below(10) {
+                0 => rng.below(10000),
+                1 => 0,
+                _ => 1 + rng.below(3),
+            };
+            let timestamp = rng.next() as i64;
+
+            let mut c_price_data = price_data;
+            let rust_updated = upd_aggregate(&mut price_data, &mut [], slot, timestamp);
+            let c_updated = unsafe {
+                c_upd_aggregate(
+                    bytes_of_mut(&mut c_price_data).as_mut_ptr(),
+                    slot,
+                    timestamp,
+                )
+            };
+
+            assert_eq!(rust_updated, c_updated);
+            assert!(bytes_of(&price_data) == bytes_of(&c_price_data));
+        }
+    }
+}
@@ -1,6 +1,4 @@
-use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
-use std::mem::size_of;
 
 use crate::c_oracle_header::{
     cmd_upd_price_t,
@@ -14,8 +12,8 @@ use crate::c_oracle_header::{
 use crate::deserialize::{
     initialize_pyth_account_checked,
     load_checked,
-    load_mut,
 };
+use crate::error::OracleError;
 use crate::rust_oracle::upd_price;
 use crate::tests::test_utils::{
     update_clock_slot,
@@ -24,8 +22,7 @@ use crate::tests::test_utils::{
 use crate::utils::pubkey_assign;
 #[test]
 fn test_upd_price() {
-    let mut instruction_data = [0u8; size_of::<cmd_upd_price_t>()];
-    populate_instruction(&mut instruction_data, 42, 2, 1);
+    let mut cmd = populate_instruction(42, 2, 1);
 
     let program_id = Pubkey::new_unique();
 
@@ -60,7 +57,7 @@ fn test_upd_price() {
             price_account.clone(),
             clock_account.clone()
         ],
-        &instruction_data
+        &cmd
     )
     .is_ok());
 
@@ -77,7 +74,7 @@ fn test_upd_price() {
     }
 
     // add some prices for current slot - get rejected
-    populate_instruction(&mut instruction_data, 43, 2, 1);
+    cmd = populate_instruction(43, 2, 1);
 
     assert_eq!(
         upd_price(
@@ -87,9 +84,9 @@ fn test_upd_price() {
                 price_account.clone(),
                 clock_account.clone()
             ],
-            &instruction_data
+            &cmd
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::StalePublisherSlot.into())
     );
 
     {
@@ -105,7 +102,7 @@ fn test_upd_price() {
     }
 
     // add next price in new slot triggering snapshot and aggregate calc
-    populate_instruction(&mut instruction_data, 81, 2, 2);
+    cmd = populate_instruction(81, 2, 2);
     update_clock_slot(&mut clock_account, 3);
 
     assert!(upd_price(
@@ -115,7 +112,7 @@ fn test_upd_price() {
             price_account.clone(),
             clock_account.clone()
         ],
-        &instruction_data
+        &cmd
     )
     .is_ok());
 
@@ -132,7 +129,7 @@ fn test_upd_price() {
     }
 
     // next price doesnt change but slot does
-    populate_instruction(&mut instruction_data, 81, 2, 3);
+    cmd = populate_instruction(81, 2, 3);
     update_clock_slot(&mut clock_account, 4);
     assert!(upd_price(
         &program_id,
@@ -141,7 +138,7 @@ fn test_upd_price() {
             price_account.clone(),
             clock_account.clone()
         ],
-        &instruction_data
+        &cmd
     )
     .is_ok());
 
@@ -158,7 +155,7 @@ fn test_upd_price() {
     }
 
     // next price doesnt change and neither does aggregate but slot does
-    populate_instruction(&mut instruction_data, 81, 2, 4);
+    cmd = populate_instruction(81, 2, 4);
     update_clock_slot(&mut clock_account, 5);
     assert!(upd_price(
         &program_id,
@@ -167,7 +164,7 @@ fn test_upd_price() {
             price_account.clone(),
             clock_account.clone()
         ],
-        &instruction_data
+        &cmd
     )
     .is_ok());
 
@@ -184,7 +181,7 @@ fn test_upd_price() {
     }
 
     // try to publish back-in-time
-    populate_instruction(&mut instruction_data, 81, 2, 1);
+    cmd = populate_instruction(81, 2, 1);
     update_clock_slot(&mut clock_account, 5);
     assert_eq!(
         upd_price(
@@ -194,9 +191,9 @@ fn test_upd_price() {
                 price_account.clone(),
                 clock_account.clone()
             ],
-            &instruction_data
+            &cmd
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::StalePublisherSlot.into())
     );
 
     {
@@ -211,7 +208,7 @@ fn test_upd_price() {
         assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
     }
 
-    populate_instruction(&mut instruction_data, 50, 6, 5);
+    cmd = populate_instruction(50, 6, 5);
     update_clock_slot(&mut clock_account, 6);
 
     // Publishing a wide CI results in a status of unknown.
@@ -229,7 +226,7 @@ fn test_upd_price() {
             price_account.clone(),
             clock_account.clone()
         ],
-        &instruction_data
+        &cmd
     )
     .is_ok());
 
@@ -246,7 +243,7 @@ fn test_upd_price() {
     }
 
     // Crank one more time and aggregate should be unknown
-    populate_instruction(&mut instruction_data, 50, 6, 6);
+    cmd = populate_instruction(50, 6, 6);
     update_clock_slot(&mut clock_account, 7);
 
     assert!(upd_price(
@@ -256,7 +253,7 @@ fn test_upd_price() {
             price_account.clone(),
             clock_account.clone()
         ],
-        &instruction_data
+        &cmd
     )
     .is_ok());
 
@@ -274,13 +271,14 @@ fn test_upd_price() {
 }
 
 // Create an upd_price instruction with the provided parameters
-fn populate_instruction(instruction_data: &mut [u8], price: i64, conf: u64, pub_slot: u64) -> () {
-    let mut cmd = load_mut::<cmd_upd_price_t>(instruction_data).unwrap();
-    cmd.ver_ = PC_VERSION;
-    cmd.cmd_ = command_t_e_cmd_upd_price as i32;
-    cmd.status_ = PC_STATUS_TRADING;
-    cmd.price_ = price;
-    cmd.conf_ = conf;
-    cmd.pub_slot_ = pub_slot;
-    cmd.unused_ = 0;
+fn populate_instruction(price: i64, conf: u64, pub_slot: u64) -> cmd_upd_price_t {
+    cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price as i32,
+        status_:    PC_STATUS_TRADING,
+        price_:     price,
+        conf_:      conf,
+        pub_slot_:  pub_slot,
+        pub_index_: 0,
+    }
 }
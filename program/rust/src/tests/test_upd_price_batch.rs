@@ -0,0 +1,144 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    cmd_upd_price_batch_entry_t,
+    cmd_upd_price_batch_t,
+    command_t_e_cmd_upd_price_batch,
+    pc_price_info_t,
+    pc_price_t,
+    PC_STATUS_TRADING,
+    PC_STATUS_UNKNOWN,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::error::OracleError;
+use crate::rust_oracle::upd_price_batch;
+use crate::tests::test_utils::{
+    update_clock_slot,
+    AccountSetup,
+};
+use crate::utils::pubkey_assign;
+
+#[test]
+fn test_upd_price_batch() {
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut clock_setup = AccountSetup::new_clock();
+    let mut clock_account = clock_setup.to_account_info();
+    clock_account.is_signer = false;
+    clock_account.is_writable = false;
+    update_clock_slot(&mut clock_account, 1);
+
+    let mut price_setup_1 = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account_1 = price_setup_1.to_account_info();
+    let mut price_setup_2 = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account_2 = price_setup_2.to_account_info();
+    let mut price_setup_3 = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account_3 = price_setup_3.to_account_info();
+    for price_account in [&price_account_1, &price_account_2, &price_account_3] {
+        initialize_pyth_account_checked::<pc_price_t>(price_account, PC_VERSION).unwrap();
+        let mut price_data = load_checked::<pc_price_t>(price_account, PC_VERSION).unwrap();
+        price_data.num_ = 1;
+        pubkey_assign(
+            &mut price_data.comp_[0].pub_,
+            &funding_account.key.to_bytes(),
+        );
+    }
+
+    let accounts = [
+        funding_account.clone(),
+        clock_account.clone(),
+        price_account_1.clone(),
+        price_account_2.clone(),
+        price_account_3.clone(),
+    ];
+
+    let (cmd, entries) = populate_instruction(false, &[(42, 2, 1), (43, 1, 1), (44, 40, 1)]);
+    assert!(upd_price_batch(&program_id, &accounts, &cmd, &entries).is_ok());
+    assert_eq!(get_latest(&price_account_1).price_, 42);
+    assert_eq!(get_latest(&price_account_1).conf_, 2);
+    assert_eq!(get_latest(&price_account_1).status_, PC_STATUS_TRADING);
+    assert_eq!(get_latest(&price_account_2).price_, 43);
+    assert_eq!(get_latest(&price_account_2).conf_, 1);
+    assert_eq!(get_latest(&price_account_2).pub_slot_, 1);
+    // Each entry goes through the confidence interval check
+    assert_eq!(get_latest(&price_account_3).price_, 44);
+    assert_eq!(get_latest(&price_account_3).status_, PC_STATUS_UNKNOWN);
+
+    // The second entry is not more recent than the published price, so the whole batch fails
+    update_clock_slot(&mut clock_account, 2);
+    let (cmd, entries) = populate_instruction(false, &[(52, 2, 2), (53, 1, 1), (54, 1, 2)]);
+    assert_eq!(
+        upd_price_batch(&program_id, &accounts, &cmd, &entries),
+        Err(OracleError::StalePublisherSlot.into())
+    );
+
+    // Unless the entries are allowed to fail on their own
+    let (cmd, entries) = populate_instruction(true, &[(52, 2, 2), (53, 1, 1), (54, 1, 2)]);
+    assert!(upd_price_batch(&program_id, &accounts, &cmd, &entries).is_ok());
+    assert_eq!(get_latest(&price_account_1).price_, 52);
+    assert_eq!(get_latest(&price_account_2).price_, 43);
+    assert_eq!(get_latest(&price_account_3).price_, 54);
+    assert_eq!(get_latest(&price_account_3).status_, PC_STATUS_TRADING);
+
+    // The aggregate of each price account is updated as with upd_price
+    update_clock_slot(&mut clock_account, 3);
+    let (cmd, entries) = populate_instruction(false, &[(62, 2, 3), (63, 1, 3), (64, 1, 3)]);
+    assert!(upd_price_batch(&program_id, &accounts, &cmd, &entries).is_ok());
+    {
+        let price_data = load_checked::<pc_price_t>(&price_account_1, PC_VERSION).unwrap();
+        assert_eq!(price_data.agg_.price_, 52);
+        assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    }
+    {
+        let price_data = load_checked::<pc_price_t>(&price_account_2, PC_VERSION).unwrap();
+        assert_eq!(price_data.agg_.price_, 43);
+        assert_eq!(price_data.agg_.status_, PC_STATUS_TRADING);
+    }
+
+    // The number of price accounts has to match the number of entries
+    update_clock_slot(&mut clock_account, 4);
+    let (cmd, entries) = populate_instruction(false, &[(72, 2, 4), (73, 3, 4)]);
+    assert_eq!(
+        upd_price_batch(&program_id, &accounts, &cmd, &entries),
+        Err(OracleError::InvalidNumberOfAccounts.into())
+    );
+}
+
+// Create an upd_price_batch instruction with one (price, conf, pub_slot) entry per price account
+fn populate_instruction(
+    no_fail_on_error: bool,
+    entries: &[(i64, u64, u64)],
+) -> (cmd_upd_price_batch_t, Vec<cmd_upd_price_batch_entry_t>) {
+    let cmd = cmd_upd_price_batch_t {
+        ver_:              PC_VERSION,
+        cmd_:              command_t_e_cmd_upd_price_batch as i32,
+        no_fail_on_error_: no_fail_on_error.into(),
+        num_:              entries.len() as u32,
+    };
+    let entries = entries
+        .iter()
+        .map(|(price, conf, pub_slot)| cmd_upd_price_batch_entry_t {
+            status_:    PC_STATUS_TRADING,
+            pub_index_: 0,
+            price_:     *price,
+            conf_:      *conf,
+            pub_slot_:  *pub_slot,
+        })
+        .collect();
+    (cmd, entries)
+}
+
+fn get_latest(price_account: &AccountInfo) -> pc_price_info_t {
+    load_checked::<pc_price_t>(price_account, PC_VERSION)
+        .unwrap()
+        .comp_[0]
+        .latest_
+}
@@ -0,0 +1,157 @@
+use bytemuck::{
+    bytes_of,
+    Zeroable,
+};
+use solana_program::instruction::{
+    AccountMeta,
+    Instruction,
+};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::clock;
+use solana_program_test::{
+    BanksClient,
+    ProgramTest,
+};
+use solana_sdk::account::Account;
+use solana_sdk::signature::{
+    Keypair,
+    Signer,
+};
+use solana_sdk::transaction::Transaction;
+use std::mem::size_of;
+
+use crate::c_oracle_header::{
+    cmd_upd_price_t,
+    command_t_e_cmd_upd_price,
+    PC_ACCTYPE_PRICE,
+    PC_COMP_SIZE,
+    PC_EXTRA_COMP_SIZE,
+    PC_MAGIC,
+    PC_PRICE_T_COMP_OFFSET,
+    PC_STATUS_TRADING,
+    PC_VERSION,
+};
+use crate::time_machine_types::{
+    PriceAccountWrapper,
+    MAX_NUM_COMPONENTS,
+};
+use crate::utils::pubkey_assign;
+
+/// Compute units spent by `upd_price` in the BPF build of the program, for a publisher at the
+/// start, at the end of `comp_` and at the end of the extra components of a full price account,
+/// with the index of its component and without it. Runs on the program built by `cargo test-bpf`.
+#[tokio::test]
+async fn test_upd_price_compute_units() {
+    let program_id = Pubkey::new_unique();
+    let publishers: Vec<Keypair> = (0..MAX_NUM_COMPONENTS).map(|_| Keypair::new()).collect();
+    let price_account = Pubkey::new_unique();
+
+    let mut program_test = ProgramTest::new("pyth_oracle", program_id, None);
+    program_test.add_account(
+        price_account,
+        Account {
+            lamports:   Rent::default().minimum_balance(size_of::<PriceAccountWrapper>()),
+            data:       full_price_account_data(&publishers),
+            owner:      program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let (mut banks_client, payer, _) = program_test.start().await;
+
+    for index in [0, PC_COMP_SIZE as usize - 1, MAX_NUM_COMPONENTS - 1] {
+        let with_index = upd_price_compute_units(
+            &mut banks_client,
+            &payer,
+            &program_id,
+            &publishers[index],
+            &price_account,
+            index as u32,
+        )
+        .await;
+        let without_index = upd_price_compute_units(
+            &mut banks_client,
+            &payer,
+            &program_id,
+            &publishers[index],
+            &price_account,
+            u32::MAX,
+        )
+        .await;
+
+        // The publisher of the first component is the first one compared either way
+        assert!(with_index <= without_index);
+        if index > 0 {
+            assert!(with_index < without_index);
+        }
+    }
+}
+
+/// The data of a price account resized with `resize_price_account` in which every component is
+/// used, by `publishers` in order. The aggregate is already at the latest slot, so that
+/// `upd_price` only looks the publisher up and stores its price.
+fn full_price_account_data(publishers: &[Keypair]) -> Vec<u8> {
+    let mut price_account = PriceAccountWrapper::zeroed();
+    price_account.price_data.magic_ = PC_MAGIC;
+    price_account.price_data.ver_ = PC_VERSION;
+    price_account.price_data.type_ = PC_ACCTYPE_PRICE;
+    price_account.price_data.size_ = PC_PRICE_T_COMP_OFFSET as u32;
+    price_account.price_data.agg_.pub_slot_ = u64::MAX;
+    price_account.initialize_time_machine().unwrap();
+
+    price_account.price_data.num_ = PC_COMP_SIZE;
+    price_account.price_data.num_extra_ = PC_EXTRA_COMP_SIZE;
+    for (i, publisher) in publishers.iter().enumerate() {
+        let component = match i.checked_sub(PC_COMP_SIZE as usize) {
+            None => &mut price_account.price_data.comp_[i],
+            Some(extra_index) => &mut price_account.extra_components[extra_index],
+        };
+        pubkey_assign(&mut component.pub_, &publisher.pubkey().to_bytes());
+    }
+    bytes_of(&price_account).to_vec()
+}
+
+/// Simulate an `upd_price` of `publisher` carrying `pub_index` and return the compute units it
+/// consumed
+async fn upd_price_compute_units(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    publisher: &Keypair,
+    price_account: &Pubkey,
+    pub_index: u32,
+) -> u64 {
+    let cmd = cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price as i32,
+        status_:    PC_STATUS_TRADING,
+        pub_index_: pub_index,
+        price_:     100,
+        conf_:      1,
+        pub_slot_:  1,
+    };
+    let instruction = Instruction {
+        program_id: *program_id,
+        accounts:   vec![
+            AccountMeta::new(publisher.pubkey(), true),
+            AccountMeta::new(*price_account, false),
+            AccountMeta::new_readonly(clock::id(), false),
+        ],
+        data:       bytes_of(&cmd).to_vec(),
+    };
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer, publisher],
+        recent_blockhash,
+    );
+
+    let simulation = banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    simulation.result.unwrap().unwrap();
+    simulation.simulation_details.unwrap().units_consumed
+}
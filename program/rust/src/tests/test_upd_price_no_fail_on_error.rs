@@ -1,6 +1,4 @@
-use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
-use std::mem::size_of;
 
 use crate::c_oracle_header::{
     cmd_upd_price_t,
@@ -14,8 +12,8 @@ use crate::c_oracle_header::{
 use crate::deserialize::{
     initialize_pyth_account_checked,
     load_checked,
-    load_mut,
 };
+use crate::error::OracleError;
 use crate::rust_oracle::{
     upd_price,
     upd_price_no_fail_on_error,
@@ -27,8 +25,6 @@ use crate::tests::test_utils::{
 use crate::utils::pubkey_assign;
 #[test]
 fn test_upd_price_no_fail_on_error_no_fail_on_error() {
-    let mut instruction_data = [0u8; size_of::<cmd_upd_price_t>()];
-
     let program_id = Pubkey::new_unique();
 
     let mut funding_setup = AccountSetup::new_funding();
@@ -45,7 +41,7 @@ fn test_upd_price_no_fail_on_error_no_fail_on_error() {
     clock_account.is_writable = false;
 
     update_clock_slot(&mut clock_account, 1);
-    populate_instruction(&mut instruction_data, 42, 9, 1);
+    let mut cmd = populate_instruction(42, 9, 1);
 
 
     // Check that the normal upd_price fails
@@ -57,9 +53,9 @@ fn test_upd_price_no_fail_on_error_no_fail_on_error() {
                 price_account.clone(),
                 clock_account.clone()
             ],
-            &instruction_data
+            &cmd
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::UnknownPublisher.into())
     );
 
 
@@ -73,7 +69,7 @@ fn test_upd_price_no_fail_on_error_no_fail_on_error() {
             price_account.clone(),
             clock_account.clone()
         ],
-        &instruction_data
+        &cmd
     )
     .is_ok());
 
@@ -105,7 +101,7 @@ fn test_upd_price_no_fail_on_error_no_fail_on_error() {
             price_account.clone(),
             clock_account.clone()
         ],
-        &instruction_data
+        &cmd
     )
     .is_ok());
 
@@ -123,7 +119,7 @@ fn test_upd_price_no_fail_on_error_no_fail_on_error() {
 
     // Invalid updates, such as publishing an update for the current slot,
     // should still fail silently and have no effect.
-    populate_instruction(&mut instruction_data, 55, 22, 1);
+    cmd = populate_instruction(55, 22, 1);
 
     // Check that the normal upd_price fails
     assert_eq!(
@@ -134,9 +130,9 @@ fn test_upd_price_no_fail_on_error_no_fail_on_error() {
                 price_account.clone(),
                 clock_account.clone()
             ],
-            &instruction_data
+            &cmd
         ),
-        Err(ProgramError::InvalidArgument)
+        Err(OracleError::StalePublisherSlot.into())
     );
 
     assert!(upd_price_no_fail_on_error(
@@ -146,7 +142,7 @@ fn test_upd_price_no_fail_on_error_no_fail_on_error() {
             price_account.clone(),
             clock_account.clone()
         ],
-        &instruction_data
+        &cmd
     )
     .is_ok());
 
@@ -165,13 +161,14 @@ fn test_upd_price_no_fail_on_error_no_fail_on_error() {
 
 
 // Create an upd_price_no_fail_on_error instruction with the provided parameters
-fn populate_instruction(instruction_data: &mut [u8], price: i64, conf: u64, pub_slot: u64) -> () {
-    let mut cmd = load_mut::<cmd_upd_price_t>(instruction_data).unwrap();
-    cmd.ver_ = PC_VERSION;
-    cmd.cmd_ = command_t_e_cmd_upd_price_no_fail_on_error as i32;
-    cmd.status_ = PC_STATUS_TRADING;
-    cmd.price_ = price;
-    cmd.conf_ = conf;
-    cmd.pub_slot_ = pub_slot;
-    cmd.unused_ = 0;
+fn populate_instruction(price: i64, conf: u64, pub_slot: u64) -> cmd_upd_price_t {
+    cmd_upd_price_t {
+        ver_:       PC_VERSION,
+        cmd_:       command_t_e_cmd_upd_price_no_fail_on_error as i32,
+        status_:    PC_STATUS_TRADING,
+        price_:     price,
+        conf_:      conf,
+        pub_slot_:  pub_slot,
+        pub_index_: 0,
+    }
 }
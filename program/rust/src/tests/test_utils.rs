@@ -1,7 +1,25 @@
-use crate::c_oracle_header::PythAccount;
+use crate::c_oracle_header::{
+    pc_permissions_t,
+    PythAccount,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::events::Event;
+use crate::utils::get_permissions_address;
 use solana_program::account_info::AccountInfo;
+use solana_program::bpf_loader_upgradeable::{
+    self,
+    UpgradeableLoaderState,
+};
 use solana_program::clock::Epoch;
 use solana_program::native_token::LAMPORTS_PER_SOL;
+use solana_program::program_stubs::{
+    set_syscall_stubs,
+    SyscallStubs,
+};
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
 use solana_program::sysvar::{
@@ -13,6 +31,8 @@ use solana_program::{
     system_program,
     sysvar,
 };
+use std::cell::RefCell;
+use std::sync::Once;
 
 const UPPER_BOUND_OF_ALL_ACCOUNT_SIZES: usize = 20536;
 
@@ -24,26 +44,85 @@ const UPPER_BOUND_OF_ALL_ACCOUNT_SIZES: usize = 20536;
 /// After instantiating the setup `AccountSetup` with `new` (that line will transfer the fields to
 /// the outer scope),  `to_account_info` gives the user an `AccountInfo` pointing to the fields of
 /// the AccountSetup.
+/// The layout mimics the serialized accounts of the Solana runtime so that `AccountInfo::realloc`
+/// works: the original data length is right before the key and the data length right before the
+/// data.
+#[repr(C)]
 pub struct AccountSetup {
-    key:     Pubkey,
-    owner:   Pubkey,
-    balance: u64,
-    size:    usize,
-    data:    [u8; UPPER_BOUND_OF_ALL_ACCOUNT_SIZES],
+    original_size: u32,
+    key:           Pubkey,
+    owner:         Pubkey,
+    balance:       u64,
+    size:          usize,
+    data_len:      u64,
+    data:          [u8; UPPER_BOUND_OF_ALL_ACCOUNT_SIZES],
 }
 
 impl AccountSetup {
     pub fn new<T: PythAccount>(owner: &Pubkey) -> Self {
-        let key = Pubkey::new_unique();
+        Self::new_with_key::<T>(owner, &Pubkey::new_unique())
+    }
+
+    /// Same as `new`, at a given address, e.g. a PDA of the program
+    pub fn new_with_key<T: PythAccount>(owner: &Pubkey, key: &Pubkey) -> Self {
+        let key = *key;
         let owner = owner.clone();
         let balance = Rent::minimum_balance(&Rent::default(), T::minimum_size());
         let size = T::minimum_size();
         let data = [0; UPPER_BOUND_OF_ALL_ACCOUNT_SIZES];
         return AccountSetup {
+            original_size: size as u32,
+            key,
+            owner,
+            balance,
+            size,
+            data_len: size as u64,
+            data,
+        };
+    }
+
+    /// The permissions account of `program_id`, allocated but not initialized
+    pub fn new_permissions(program_id: &Pubkey) -> Self {
+        let key = get_permissions_address(program_id).0;
+        let owner = *program_id;
+        let balance = Rent::minimum_balance(&Rent::default(), pc_permissions_t::minimum_size());
+        let size = pc_permissions_t::minimum_size();
+        let data = [0; UPPER_BOUND_OF_ALL_ACCOUNT_SIZES];
+        return AccountSetup {
+            original_size: size as u32,
+            key,
+            owner,
+            balance,
+            size,
+            data_len: size as u64,
+            data,
+        };
+    }
+
+    /// The programdata account of `program_id`, with `upgrade_authority` as its upgrade
+    /// authority
+    pub fn new_programdata(program_id: &Pubkey, upgrade_authority: &Pubkey) -> Self {
+        let key =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0;
+        let owner = bpf_loader_upgradeable::id();
+        let size = UpgradeableLoaderState::size_of_programdata_metadata();
+        let balance = Rent::minimum_balance(&Rent::default(), size);
+        let mut data = [0; UPPER_BOUND_OF_ALL_ACCOUNT_SIZES];
+        bincode::serialize_into(
+            &mut data[..size],
+            &UpgradeableLoaderState::ProgramData {
+                slot:                      0,
+                upgrade_authority_address: Some(*upgrade_authority),
+            },
+        )
+        .unwrap();
+        return AccountSetup {
+            original_size: size as u32,
             key,
             owner,
             balance,
             size,
+            data_len: size as u64,
             data,
         };
     }
@@ -55,10 +134,12 @@ impl AccountSetup {
         let size = 0;
         let data = [0; UPPER_BOUND_OF_ALL_ACCOUNT_SIZES];
         return AccountSetup {
+            original_size: size as u32,
             key,
             owner,
             balance,
             size,
+            data_len: size as u64,
             data,
         };
     }
@@ -70,10 +151,12 @@ impl AccountSetup {
         let size = clock::Clock::size_of();
         let data = [0u8; UPPER_BOUND_OF_ALL_ACCOUNT_SIZES];
         return AccountSetup {
+            original_size: size as u32,
             key,
             owner,
             balance,
             size,
+            data_len: size as u64,
             data,
         };
     }
@@ -92,8 +175,50 @@ impl AccountSetup {
     }
 }
 
+/// Initialize the permissions account with `master_authority` as its master authority
+pub fn set_master_authority(permissions_account: &AccountInfo, master_authority: &Pubkey) {
+    initialize_pyth_account_checked::<pc_permissions_t>(permissions_account, PC_VERSION).unwrap();
+    load_checked::<pc_permissions_t>(permissions_account, PC_VERSION)
+        .unwrap()
+        .master_authority_
+        .k1_ = master_authority.to_bytes();
+}
+
 pub fn update_clock_slot(clock_account: &mut AccountInfo, slot: u64) {
     let mut clock_data = clock::Clock::from_account_info(clock_account).unwrap();
     clock_data.slot = slot;
     clock_data.to_account_info(clock_account);
 }
+
+pub fn update_clock_timestamp(clock_account: &mut AccountInfo, timestamp: i64) {
+    let mut clock_data = clock::Clock::from_account_info(clock_account).unwrap();
+    clock_data.unix_timestamp = timestamp;
+    clock_data.to_account_info(clock_account);
+}
+
+thread_local! {
+    static EMITTED_EVENTS: RefCell<Vec<Event>> = const { RefCell::new(vec![]) };
+}
+
+/// Syscall stubs that record the events logged with `sol_log_data` by the thread of each test
+struct EventRecorder;
+
+impl SyscallStubs for EventRecorder {
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        EMITTED_EVENTS.with(|events| {
+            events
+                .borrow_mut()
+                .extend(fields.iter().map(|field| Event::decode(field).unwrap()))
+        });
+    }
+}
+
+/// Take the events emitted by the thread of the current test since the last call. Events are only
+/// recorded after the first call.
+pub fn take_emitted_events() -> Vec<Event> {
+    static INSTALL_RECORDER: Once = Once::new();
+    INSTALL_RECORDER.call_once(|| {
+        set_syscall_stubs(Box::new(EventRecorder));
+    });
+    EMITTED_EVENTS.with(|events| events.take())
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use solana_program::account_info::AccountInfo;
+use solana_program::pubkey::Pubkey;
+
+use crate::c_oracle_header::{
+    cmd_add_price,
+    cmd_hdr_t,
+    command_t_e_cmd_add_price,
+    command_t_e_cmd_add_product,
+    pc_map_table_t,
+    pc_price_t,
+    pc_prod_t,
+    PC_ACCTYPE_PRICE,
+    PC_VERSION,
+};
+use crate::deserialize::{
+    initialize_pyth_account_checked,
+    load_checked,
+};
+use crate::rust_oracle::{
+    add_price,
+    add_product,
+};
+use crate::sdk::{
+    AccountWalker,
+    WalkError,
+    WalkItem,
+};
+use crate::tests::test_utils::{
+    set_master_authority,
+    AccountSetup,
+};
+use crate::utils::pubkey_assign;
+
+#[test]
+fn test_walker() {
+    let hdr_add_product = cmd_hdr_t {
+        ver_: PC_VERSION,
+        cmd_: command_t_e_cmd_add_product as i32,
+    };
+    let hdr_add_price = cmd_add_price {
+        ver_:   PC_VERSION,
+        cmd_:   command_t_e_cmd_add_price as i32,
+        expo_:  1,
+        ptype_: 1,
+    };
+
+    let program_id = Pubkey::new_unique();
+
+    let mut funding_setup = AccountSetup::new_funding();
+    let funding_account = funding_setup.to_account_info();
+
+    let mut permissions_setup = AccountSetup::new_permissions(&program_id);
+    let permissions_account = permissions_setup.to_account_info();
+    set_master_authority(&permissions_account, funding_account.key);
+
+    let mut mapping_setup_1 = AccountSetup::new::<pc_map_table_t>(&program_id);
+    let mapping_account_1 = mapping_setup_1.to_account_info();
+    initialize_pyth_account_checked::<pc_map_table_t>(&mapping_account_1, PC_VERSION).unwrap();
+    let mut mapping_setup_2 = AccountSetup::new::<pc_map_table_t>(&program_id);
+    let mapping_account_2 = mapping_setup_2.to_account_info();
+    initialize_pyth_account_checked::<pc_map_table_t>(&mapping_account_2, PC_VERSION).unwrap();
+    pubkey_assign(
+        &mut load_checked::<pc_map_table_t>(&mapping_account_1, PC_VERSION)
+            .unwrap()
+            .next_,
+        &mapping_account_2.key.to_bytes(),
+    );
+
+    let mut product_setup_1 = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account_1 = product_setup_1.to_account_info();
+    let mut product_setup_2 = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account_2 = product_setup_2.to_account_info();
+    let mut product_setup_3 = AccountSetup::new::<pc_prod_t>(&program_id);
+    let product_account_3 = product_setup_3.to_account_info();
+
+    let mut price_setup_1 = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account_1 = price_setup_1.to_account_info();
+    let mut price_setup_2 = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account_2 = price_setup_2.to_account_info();
+    let mut price_setup_3 = AccountSetup::new::<pc_price_t>(&program_id);
+    let price_account_3 = price_setup_3.to_account_info();
+
+    // Mapping 1 lists product 1, whose prices are 2 -> 1, and product 2 without prices. Mapping
+    // 2 lists product 3, whose price is 3.
+    for (mapping_account, product_account) in [
+        (&mapping_account_1, &product_account_1),
+        (&mapping_account_1, &product_account_2),
+        (&mapping_account_2, &product_account_3),
+    ] {
+        assert!(add_product(
+            &program_id,
+            &[
+                funding_account.clone(),
+                mapping_account.clone(),
+                product_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_add_product
+        )
+        .is_ok());
+    }
+    for (product_account, price_account) in [
+        (&product_account_1, &price_account_1),
+        (&product_account_1, &price_account_2),
+        (&product_account_3, &price_account_3),
+    ] {
+        assert!(add_price(
+            &program_id,
+            &[
+                funding_account.clone(),
+                product_account.clone(),
+                price_account.clone(),
+                permissions_account.clone()
+            ],
+            &hdr_add_price
+        )
+        .is_ok());
+    }
+
+    let accounts = [
+        mapping_account_1.clone(),
+        mapping_account_2.clone(),
+        product_account_1.clone(),
+        product_account_2.clone(),
+        product_account_3.clone(),
+        price_account_1.clone(),
+        price_account_2.clone(),
+        price_account_3.clone(),
+    ];
+
+    let items = walk(&accounts).unwrap();
+    assert_eq!(
+        items,
+        [
+            *product_account_1.key,
+            *price_account_2.key,
+            *price_account_1.key,
+            *product_account_2.key,
+            *product_account_3.key,
+            *price_account_3.key,
+        ]
+    );
+
+    // Missing accounts are reported with the account that references them
+    assert_eq!(
+        walk(&accounts[..7]),
+        Err(WalkError::DanglingKey {
+            key:  *price_account_3.key,
+            from: *product_account_3.key,
+        })
+    );
+
+    // So are cycles
+    pubkey_assign(
+        &mut load_checked::<pc_price_t>(&price_account_1, PC_VERSION)
+            .unwrap()
+            .next_,
+        &price_account_2.key.to_bytes(),
+    );
+    assert_eq!(
+        walk(&accounts),
+        Err(WalkError::Cycle {
+            key:  *price_account_2.key,
+            from: *price_account_1.key,
+        })
+    );
+    pubkey_assign(
+        &mut load_checked::<pc_price_t>(&price_account_1, PC_VERSION)
+            .unwrap()
+            .next_,
+        &[0; 32],
+    );
+
+    pubkey_assign(
+        &mut load_checked::<pc_map_table_t>(&mapping_account_2, PC_VERSION)
+            .unwrap()
+            .next_,
+        &mapping_account_1.key.to_bytes(),
+    );
+    assert_eq!(
+        walk(&accounts),
+        Err(WalkError::Cycle {
+            key:  *mapping_account_1.key,
+            from: *mapping_account_2.key,
+        })
+    );
+    pubkey_assign(
+        &mut load_checked::<pc_map_table_t>(&mapping_account_2, PC_VERSION)
+            .unwrap()
+            .next_,
+        &[0; 32],
+    );
+
+    // And zero keys in the product list of a mapping account
+    load_checked::<pc_map_table_t>(&mapping_account_2, PC_VERSION)
+        .unwrap()
+        .num_ = 2;
+    assert_eq!(
+        walk(&accounts),
+        Err(WalkError::ZeroKey {
+            from: *mapping_account_2.key,
+        })
+    );
+    load_checked::<pc_map_table_t>(&mapping_account_2, PC_VERSION)
+        .unwrap()
+        .num_ = 1;
+
+    // Accounts must have the expected type
+    load_checked::<pc_map_table_t>(&mapping_account_2, PC_VERSION)
+        .unwrap()
+        .type_ = PC_ACCTYPE_PRICE;
+    assert_eq!(
+        walk(&accounts),
+        Err(WalkError::InvalidAccount {
+            key:          *mapping_account_2.key,
+            account_type: "mapping",
+        })
+    );
+}
+
+// Walk from the first account of accounts, returning the keys of the accounts in the order they
+// are visited
+fn walk(accounts: &[AccountInfo]) -> Result<Vec<Pubkey>, WalkError> {
+    let dump: HashMap<Pubkey, Vec<u8>> = accounts[1..]
+        .iter()
+        .map(|account| (*account.key, account.try_borrow_data().unwrap().to_vec()))
+        .collect();
+    AccountWalker::new(
+        accounts[0].key,
+        &accounts[0].try_borrow_data().unwrap(),
+        |key| dump.get(key).cloned(),
+    )?
+    .map(|item| {
+        item.map(|item| match item {
+            WalkItem::Product(product) => product.key,
+            WalkItem::Price(price) => price.key,
+        })
+    })
+    .collect()
+}
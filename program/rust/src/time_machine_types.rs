@@ -1,25 +1,435 @@
+use crate::aggregate::{
+    get_max_latency,
+    is_fresh_quote,
+    is_valid_quote,
+};
 use crate::c_oracle_header::{
+    pc_price_comp_t,
+    pc_price_info_t,
     pc_price_t,
     PythAccount,
     EXTRA_PUBLISHER_SPACE,
     PC_ACCTYPE_PRICE,
+    PC_COMP_SIZE,
+    PC_EXTRA_COMP_SIZE,
     PC_PRICE_T_COMP_OFFSET,
+    PC_STATUS_TRADING,
+    TIME_MACHINE_STRUCT_SIZE,
 };
 use crate::error::OracleError;
+use crate::utils::{
+    pubkey_equal,
+    try_convert,
+};
 use bytemuck::{
     Pod,
     Zeroable,
 };
-use solana_program::msg;
+use std::mem::size_of;
+
+/// Width in seconds of the time buckets of the SMA tracker
+pub const SMA_GRANULARITY: i64 = 3600;
+/// Number of time buckets kept by the SMA tracker (24 one hour buckets)
+pub const SMA_NUM_BUCKETS: usize = 24;
+/// Number of aggregate prices kept by the tick tracker
+pub const NUM_TICKS: usize = 30;
+/// Number of components of a resized price account, the `comp_` of its `pc_price_t` followed by
+/// the components stored in its extra publisher space
+pub const MAX_NUM_COMPONENTS: usize = (PC_COMP_SIZE + PC_EXTRA_COMP_SIZE) as usize;
+/// Offset of the Time Machine in a resized price account. The accounts resized before the
+/// publisher statistics were added end with it.
+pub const TIME_MACHINE_OFFSET: usize = size_of::<pc_price_t>() + EXTRA_PUBLISHER_SPACE as usize;
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+/// One fixed-width time bucket of an SMA tracker
+pub struct SmaBucket {
+    /// average of the aggregate prices published during the bucket
+    pub price: i64,
+    /// average of the aggregate confidences published during the bucket
+    pub conf:  u64,
+    /// 1 if the bucket is counted in the running sums of the tracker, 0 otherwise
+    pub valid: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+/// Tracks the simple moving average of the aggregate price and confidence.
+/// Time is split in buckets of `granularity` seconds, the average of each of the last
+/// `NUM_BUCKETS` buckets is stored in a ring buffer and the sum of the valid buckets is kept up to
+/// date as buckets enter and leave the ring.
+pub struct SmaTracker<const NUM_BUCKETS: usize> {
+    /// width of a bucket in seconds
+    pub granularity:         i64,
+    /// index (timestamp / granularity) of the bucket currently being filled
+    pub current_epoch:       i64,
+    /// sum of the aggregate prices published in the current bucket
+    pub current_price_sum:   i64,
+    /// sum of the aggregate confidences published in the current bucket
+    pub current_conf_sum:    u64,
+    /// number of aggregate prices published in the current bucket
+    pub current_count:       u64,
+    /// 0 if the sums of the current bucket overflowed, the bucket is then ignored
+    pub current_is_valid:    u32,
+    /// 1 once the first datapoint was recorded, `current_epoch` is meaningless before
+    pub is_started:          u32,
+    /// sum of the prices of the valid buckets in `entries`
+    pub running_price_sum:   i64,
+    /// sum of the confidences of the valid buckets in `entries`
+    pub running_conf_sum:    u64,
+    /// number of valid buckets in `entries`
+    pub running_valid_count: u64,
+    /// ring buffer of closed buckets, bucket `epoch` lives at index `epoch % NUM_BUCKETS`
+    pub entries:             [SmaBucket; NUM_BUCKETS],
+}
+
+impl<const NUM_BUCKETS: usize> SmaTracker<NUM_BUCKETS> {
+    pub fn initialize(&mut self, granularity: i64) -> Result<(), OracleError> {
+        if granularity <= 0 || NUM_BUCKETS == 0 {
+            return Err(OracleError::Generic);
+        }
+        *self = Zeroable::zeroed();
+        self.granularity = granularity;
+        self.current_is_valid = 1;
+        Ok(())
+    }
+
+    /// Record an aggregate `price` and `conf` published at unix time `timestamp`
+    pub fn add_datapoint(
+        &mut self,
+        timestamp: i64,
+        price: i64,
+        conf: u64,
+    ) -> Result<(), OracleError> {
+        if self.granularity <= 0 {
+            return Err(OracleError::Generic);
+        }
+        let epoch = timestamp.div_euclid(self.granularity);
+
+        if self.is_started == 0 {
+            // First datapoint ever
+            self.current_epoch = epoch;
+            self.is_started = 1;
+        } else if epoch < self.current_epoch {
+            // The clock went backwards, ignore the datapoint
+            return Ok(());
+        } else if epoch > self.current_epoch {
+            self.close_current_epoch(epoch)?;
+        }
+
+        match (
+            self.current_price_sum.checked_add(price),
+            self.current_conf_sum.checked_add(conf),
+        ) {
+            (Some(price_sum), Some(conf_sum)) => {
+                self.current_price_sum = price_sum;
+                self.current_conf_sum = conf_sum;
+            }
+            _ => self.current_is_valid = 0,
+        }
+        self.current_count += 1;
+        Ok(())
+    }
+
+    /// Move the current bucket into the ring buffer, clear the buckets that were skipped and start
+    /// bucket `new_epoch`
+    fn close_current_epoch(&mut self, new_epoch: i64) -> Result<(), OracleError> {
+        let mut bucket = SmaBucket {
+            price: 0,
+            conf:  0,
+            valid: 0,
+        };
+        if self.current_count > 0 && self.current_is_valid == 1 {
+            bucket.price = self.current_price_sum / try_convert::<_, i64>(self.current_count)?;
+            bucket.conf = self.current_conf_sum / self.current_count;
+            bucket.valid = 1;
+        }
+        self.replace_entry(self.current_epoch, bucket)?;
+
+        // Buckets with no aggregate are left empty
+        let num_skipped = (new_epoch - self.current_epoch - 1).min(NUM_BUCKETS as i64);
+        for i in 1..=num_skipped {
+            self.replace_entry(self.current_epoch + i, Zeroable::zeroed())?;
+        }
+
+        self.current_epoch = new_epoch;
+        self.current_price_sum = 0;
+        self.current_conf_sum = 0;
+        self.current_count = 0;
+        self.current_is_valid = 1;
+        Ok(())
+    }
+
+    /// Overwrite the entry of bucket `epoch`, keeping the running sums consistent
+    fn replace_entry(&mut self, epoch: i64, mut bucket: SmaBucket) -> Result<(), OracleError> {
+        let index: usize = try_convert(epoch.rem_euclid(NUM_BUCKETS as i64))?;
+
+        let old = self.entries[index];
+        if old.valid == 1 {
+            self.running_price_sum = self
+                .running_price_sum
+                .checked_sub(old.price)
+                .ok_or(OracleError::IntegerCastingError)?;
+            self.running_conf_sum = self
+                .running_conf_sum
+                .checked_sub(old.conf)
+                .ok_or(OracleError::IntegerCastingError)?;
+            self.running_valid_count = self
+                .running_valid_count
+                .checked_sub(1)
+                .ok_or(OracleError::IntegerCastingError)?;
+        }
+
+        if bucket.valid == 1 {
+            match (
+                self.running_price_sum.checked_add(bucket.price),
+                self.running_conf_sum.checked_add(bucket.conf),
+            ) {
+                (Some(price_sum), Some(conf_sum)) => {
+                    self.running_price_sum = price_sum;
+                    self.running_conf_sum = conf_sum;
+                    self.running_valid_count += 1;
+                }
+                // Buckets that don't fit in the running sums are not counted
+                _ => bucket.valid = 0,
+            }
+        }
+        self.entries[index] = bucket;
+        Ok(())
+    }
+
+    /// Simple moving average of the price and confidence over the valid buckets in the ring
+    /// buffer, `None` if there is no valid bucket
+    pub fn get_sma(&self) -> Option<(i64, u64)> {
+        if self.running_valid_count == 0 {
+            return None;
+        }
+        Some((
+            self.running_price_sum / self.running_valid_count as i64,
+            self.running_conf_sum / self.running_valid_count,
+        ))
+    }
+
+    /// Simple moving average of the price and confidence over the `num_buckets` buckets that
+    /// precede the bucket containing `timestamp`. Each bucket counts once, whatever the number of
+    /// aggregate prices it contains, and buckets without any aggregate price are skipped.
+    /// Returns the averages along with the number of buckets they are computed from, `None` if
+    /// there is no such bucket.
+    pub fn get_sma_over(&self, timestamp: i64, num_buckets: usize) -> Option<(i64, u64, u64)> {
+        if self.granularity <= 0 || num_buckets == 0 || num_buckets > NUM_BUCKETS {
+            return None;
+        }
+        let now_epoch = timestamp.div_euclid(self.granularity);
+        if now_epoch < self.current_epoch {
+            return None;
+        }
+        if now_epoch == self.current_epoch && num_buckets == NUM_BUCKETS {
+            let (price, conf) = self.get_sma()?;
+            return Some((price, conf, self.running_valid_count));
+        }
+
+        let mut price_sum: i128 = 0;
+        let mut conf_sum: u128 = 0;
+        let mut count: u64 = 0;
+        for epoch in (now_epoch - num_buckets as i64)..now_epoch {
+            let bucket = if epoch == self.current_epoch {
+                if self.current_count == 0 || self.current_is_valid == 0 {
+                    continue;
+                }
+                SmaBucket {
+                    price: self.current_price_sum
+                        / try_convert::<_, i64>(self.current_count).ok()?,
+                    conf:  self.current_conf_sum / self.current_count,
+                    valid: 1,
+                }
+            } else if epoch < self.current_epoch {
+                // Since now_epoch >= current_epoch, epoch >= current_epoch - NUM_BUCKETS is still
+                // in the ring buffer
+                let index: usize = try_convert(epoch.rem_euclid(NUM_BUCKETS as i64)).ok()?;
+                self.entries[index]
+            } else {
+                continue;
+            };
+            if bucket.valid == 1 {
+                price_sum += i128::from(bucket.price);
+                conf_sum += u128::from(bucket.conf);
+                count += 1;
+            }
+        }
+
+        if count == 0 {
+            return None;
+        }
+        Some((
+            try_convert(price_sum / i128::from(count)).ok()?,
+            try_convert(conf_sum / u128::from(count)).ok()?,
+            count,
+        ))
+    }
+}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+/// Return data of the query time machine instruction
+pub struct SmaQueryResult {
+    /// simple moving average of the aggregate price
+    pub price:       i64,
+    /// simple moving average of the aggregate confidence
+    pub conf:        u64,
+    /// exponent of `price` and `conf`
+    pub expo:        i32,
+    /// number of buckets that contained an aggregate price during the window
+    pub num_buckets: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+/// An aggregate price along with the unix timestamp at which it was computed
+pub struct Tick {
+    pub price_info: pc_price_info_t,
+    pub timestamp:  i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+/// Keeps the last `NUM_ENTRIES` aggregate prices in a circular buffer
+pub struct TickTracker<const NUM_ENTRIES: usize> {
+    /// index in `entries` where the next tick will be written
+    pub next_index: u64,
+    /// number of ticks stored in `entries`, at most `NUM_ENTRIES`
+    pub num_ticks:  u64,
+    pub entries:    [Tick; NUM_ENTRIES],
+}
+
+impl<const NUM_ENTRIES: usize> TickTracker<NUM_ENTRIES> {
+    pub fn initialize(&mut self) -> Result<(), OracleError> {
+        if NUM_ENTRIES == 0 {
+            return Err(OracleError::Generic);
+        }
+        *self = Zeroable::zeroed();
+        Ok(())
+    }
+
+    /// Record an aggregate price, overwriting the oldest tick if the buffer is full
+    pub fn add_tick(
+        &mut self,
+        price_info: &pc_price_info_t,
+        timestamp: i64,
+    ) -> Result<(), OracleError> {
+        let index: usize = try_convert(self.next_index)?;
+        let tick = self.entries.get_mut(index).ok_or(OracleError::Generic)?;
+        tick.price_info = *price_info;
+        tick.timestamp = timestamp;
+
+        self.next_index = try_convert((index + 1) % NUM_ENTRIES)?;
+        if self.num_ticks < try_convert(NUM_ENTRIES)? {
+            self.num_ticks += 1;
+        }
+        Ok(())
+    }
+
+    /// Get the `age`-th most recent tick, `get(0)` is the latest tick
+    pub fn get(&self, age: usize) -> Option<&Tick> {
+        let num_ticks: usize = try_convert(self.num_ticks).ok()?;
+        let next_index: usize = try_convert(self.next_index).ok()?;
+        if age >= num_ticks || next_index >= NUM_ENTRIES {
+            return None;
+        }
+        self.entries
+            .get((next_index + NUM_ENTRIES - 1 - age) % NUM_ENTRIES)
+    }
+
+    /// Iterate over the stored ticks, from the most recent to the oldest
+    pub fn iter(&self) -> impl Iterator<Item = &Tick> {
+        (0..NUM_ENTRIES).map_while(move |age| self.get(age))
+    }
+
+    /// Get the tick aggregated in `slot`, `None` if no aggregate was computed in that slot or if it
+    /// is no longer stored
+    #[allow(unused)]
+    pub fn get_at_slot(&self, slot: u64) -> Option<&Tick> {
+        self.iter()
+            .take_while(|tick| tick.price_info.pub_slot_ >= slot)
+            .find(|tick| tick.price_info.pub_slot_ == slot)
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
 /// this wraps multiple SMA and tick trackers, and includes all the state
 /// used by the time machine
 pub struct TimeMachineWrapper {
-    //Place holder with the size of the fields I am planning to add
-    place_holder: [u8; 1864],
+    pub sma_tracker:  SmaTracker<SMA_NUM_BUCKETS>,
+    pub tick_tracker: TickTracker<NUM_TICKS>,
+    //Unused space
+    place_holder: [u8; TIME_MACHINE_STRUCT_SIZE as usize
+        - size_of::<SmaTracker<SMA_NUM_BUCKETS>>()
+        - size_of::<TickTracker<NUM_TICKS>>()],
+}
+
+impl TimeMachineWrapper {
+    /// Add the aggregate price of `price_data` to the trackers
+    pub fn add_price(&mut self, price_data: &pc_price_t) -> Result<(), OracleError> {
+        self.sma_tracker.add_datapoint(
+            price_data.timestamp_,
+            price_data.agg_.price_,
+            price_data.agg_.conf_,
+        )?;
+        self.tick_tracker
+            .add_tick(&price_data.agg_, price_data.timestamp_)?;
+        Ok(())
+    }
+
+    /// Simple moving average of the aggregate price over the `window` seconds that precede the SMA
+    /// bucket containing `timestamp`, with exponent `expo`. The bucket containing `timestamp` is
+    /// still being filled and is not included. Fails with `InvalidSmaWindow` if `window` is not a
+    /// multiple of the bucket width (`SMA_GRANULARITY`) of at most `SMA_NUM_BUCKETS` buckets, and
+    /// with `SmaUnavailable` if no aggregate price was published during the window.
+    pub fn get_sma(
+        &self,
+        expo: i32,
+        timestamp: i64,
+        window: i64,
+    ) -> Result<SmaQueryResult, OracleError> {
+        let tracker = &self.sma_tracker;
+        if tracker.granularity <= 0 {
+            return Err(OracleError::SmaUnavailable);
+        }
+        if window <= 0
+            || window % tracker.granularity != 0
+            || window / tracker.granularity > SMA_NUM_BUCKETS as i64
+        {
+            return Err(OracleError::InvalidSmaWindow);
+        }
+        let num_buckets: usize = try_convert(window / tracker.granularity)?;
+        let (price, conf, num_buckets) = tracker
+            .get_sma_over(timestamp, num_buckets)
+            .ok_or(OracleError::SmaUnavailable)?;
+        Ok(SmaQueryResult {
+            price,
+            conf,
+            expo,
+            num_buckets: try_convert(num_buckets)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+/// Counters of the prices of one publisher, used to review the quality of its prices without
+/// replaying the ledger
+pub struct PublisherStats {
+    /// number of prices published by the publisher
+    pub num_updates:         u64,
+    /// number of aggregate prices that included the price of the publisher
+    pub num_aggregated:      u64,
+    /// number of aggregations that rejected the price of the publisher because it was too old
+    pub num_stale:           u64,
+    /// number of prices whose confidence exceeded the threshold of the max confidence divisor,
+    /// their status was set to UNKNOWN
+    pub num_conf_exceeded:   u64,
+    /// last slot in which the price of the publisher was included in the aggregate price
+    pub last_aggregate_slot: u64,
 }
 
 #[derive(Copy, Clone)]
@@ -27,22 +437,208 @@ pub struct TimeMachineWrapper {
 /// wraps everything stored in a price account
 pub struct PriceAccountWrapper {
     //an instance of the c price_t type
-    pub price_data:            pc_price_t,
-    //space for more publishers
-    pub extra_publisher_space: [u8; EXTRA_PUBLISHER_SPACE as usize],
+    pub price_data:        pc_price_t,
+    //components of the publishers past the first PC_COMP_SIZE ones, they directly follow the
+    //comp_ of price_data
+    pub extra_components:  [pc_price_comp_t; PC_EXTRA_COMP_SIZE as usize],
+    //unused part of the space for more publishers
+    extra_publisher_space: [u8; EXTRA_PUBLISHER_SPACE as usize
+        - size_of::<[pc_price_comp_t; PC_EXTRA_COMP_SIZE as usize]>()],
     //TimeMachine
-    pub time_machine:          TimeMachineWrapper,
+    pub time_machine:      TimeMachineWrapper,
+    //statistics of the publisher of each component, at the same index
+    pub publisher_stats:   [PublisherStats; MAX_NUM_COMPONENTS],
 }
 impl PriceAccountWrapper {
     pub fn initialize_time_machine(&mut self) -> Result<(), OracleError> {
-        msg!("implement me");
+        self.time_machine = Zeroable::zeroed();
+        self.time_machine.sma_tracker.initialize(SMA_GRANULARITY)?;
+        self.time_machine.tick_tracker.initialize()?;
         Ok(())
     }
 
-    pub fn add_price_to_time_machine(&mut self) -> Result<(), OracleError> {
-        msg!("implement me");
+    /// Count a price published by the publisher of the component at `publisher_index`
+    pub fn record_publisher_update(
+        &mut self,
+        publisher_index: usize,
+        conf_exceeded: bool,
+    ) -> Result<(), OracleError> {
+        let stats = self
+            .publisher_stats
+            .get_mut(publisher_index)
+            .ok_or(OracleError::Generic)?;
+        stats.num_updates = stats.num_updates.saturating_add(1);
+        if conf_exceeded {
+            stats.num_conf_exceeded = stats.num_conf_exceeded.saturating_add(1);
+        }
         Ok(())
     }
+
+    /// Count the component prices that the aggregation of slot `clock_slot` included or rejected
+    /// because they were too old. Has to be called after the aggregation, which copies the latest
+    /// component prices to the aggregate snapshots.
+    pub fn record_aggregation(&mut self, clock_slot: u64, aggregate_updated: bool) {
+        let max_latency = get_max_latency(&self.price_data);
+        let num_components = num_components(&self.price_data);
+        for (component, stats) in self
+            .price_data
+            .comp_
+            .iter()
+            .chain(self.extra_components.iter())
+            .zip(self.publisher_stats.iter_mut())
+            .take(num_components)
+        {
+            let quote = &component.agg_;
+            if quote.status_ == PC_STATUS_TRADING && !is_fresh_quote(quote, clock_slot, max_latency)
+            {
+                stats.num_stale = stats.num_stale.saturating_add(1);
+            } else if aggregate_updated && is_valid_quote(quote, clock_slot, max_latency) {
+                stats.num_aggregated = stats.num_aggregated.saturating_add(1);
+                stats.last_aggregate_slot = clock_slot;
+            }
+        }
+    }
+
+    /// Reset the statistics of the component at `publisher_index`, for a new publisher
+    pub fn reset_publisher_stats(&mut self, publisher_index: usize) -> Result<(), OracleError> {
+        *self
+            .publisher_stats
+            .get_mut(publisher_index)
+            .ok_or(OracleError::Generic)? = Zeroable::zeroed();
+        Ok(())
+    }
+
+    /// Remove the statistics of the component at `publisher_index`, shifting the following ones
+    /// like the components of a deleted publisher
+    pub fn remove_publisher_stats(&mut self, publisher_index: usize) -> Result<(), OracleError> {
+        if publisher_index >= self.publisher_stats.len() {
+            return Err(OracleError::Generic);
+        }
+        self.publisher_stats
+            .copy_within(publisher_index + 1.., publisher_index);
+        self.reset_publisher_stats(self.publisher_stats.len() - 1)
+    }
+}
+
+/// Number of components of `price_data`, the `num_` ones of its `comp_` followed by the
+/// `num_extra_` ones of its extra publisher space. `num_` stays at most `PC_COMP_SIZE` so that the
+/// readers of `pc_price_t` that only know `comp_` keep working.
+pub fn num_components(price_data: &pc_price_t) -> usize {
+    price_data.num_ as usize + price_data.num_extra_ as usize
+}
+
+/// The component at `index` in the table made of the `comp_` of `price_data` followed by
+/// `extra_components`
+pub fn get_component<'a>(
+    price_data: &'a pc_price_t,
+    extra_components: &'a [pc_price_comp_t],
+    index: usize,
+) -> Option<&'a pc_price_comp_t> {
+    match index.checked_sub(PC_COMP_SIZE as usize) {
+        None => price_data.comp_.get(index),
+        Some(extra_index) => extra_components.get(extra_index),
+    }
+}
+
+/// Mutable version of `get_component`
+pub fn get_component_mut<'a>(
+    price_data: &'a mut pc_price_t,
+    extra_components: &'a mut [pc_price_comp_t],
+    index: usize,
+) -> Option<&'a mut pc_price_comp_t> {
+    match index.checked_sub(PC_COMP_SIZE as usize) {
+        None => price_data.comp_.get_mut(index),
+        Some(extra_index) => extra_components.get_mut(extra_index),
+    }
+}
+
+/// Index of the component of `publisher` among the components of `price_data`, followed by
+/// `extra_components`
+pub fn find_publisher(
+    price_data: &pc_price_t,
+    extra_components: &[pc_price_comp_t],
+    publisher: &[u8],
+) -> Option<usize> {
+    price_data
+        .comp_
+        .iter()
+        .chain(extra_components.iter())
+        .take(num_components(price_data))
+        .position(|component| pubkey_equal(&component.pub_, publisher))
+}
+
+/// Same as `find_publisher`, but the component at `index_hint` is checked first, so that a
+/// publisher that knows its index doesn't pay for the scan of the components before it
+pub fn find_publisher_with_hint(
+    price_data: &pc_price_t,
+    extra_components: &[pc_price_comp_t],
+    publisher: &[u8],
+    index_hint: usize,
+) -> Option<usize> {
+    if index_hint < num_components(price_data) {
+        if let Some(component) = get_component(price_data, extra_components, index_hint) {
+            if pubkey_equal(&component.pub_, publisher) {
+                return Some(index_hint);
+            }
+        }
+    }
+    find_publisher(price_data, extra_components, publisher)
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for SmaBucket {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for SmaBucket {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl<const NUM_BUCKETS: usize> Zeroable for SmaTracker<NUM_BUCKETS> {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl<const NUM_BUCKETS: usize> Pod for SmaTracker<NUM_BUCKETS> {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for SmaQueryResult {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for SmaQueryResult {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for Tick {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for Tick {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl<const NUM_ENTRIES: usize> Zeroable for TickTracker<NUM_ENTRIES> {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl<const NUM_ENTRIES: usize> Pod for TickTracker<NUM_ENTRIES> {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for TimeMachineWrapper {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for TimeMachineWrapper {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Zeroable for PublisherStats {
+}
+
+#[cfg(target_endian = "little")]
+unsafe impl Pod for PublisherStats {
 }
 
 #[cfg(target_endian = "little")]
@@ -62,12 +658,17 @@ impl PythAccount for PriceAccountWrapper {
 pub mod tests {
     use crate::c_oracle_header::{
         PRICE_ACCOUNT_SIZE,
+        PUBLISHER_STATS_SIZE,
         TIME_MACHINE_STRUCT_SIZE,
     };
     use crate::time_machine_types::{
         PriceAccountWrapper,
+        PublisherStats,
         TimeMachineWrapper,
+        MAX_NUM_COMPONENTS,
+        TIME_MACHINE_OFFSET,
     };
+    use bytemuck::Zeroable;
     use std::mem::size_of;
     #[test]
     ///test that the size defined in C matches that
@@ -82,6 +683,18 @@ pub mod tests {
     );
     }
     #[test]
+    ///test that the size of the publisher statistics defined in C matches that
+    ///defined in Rust
+    fn c_publisher_stats_size_is_correct() {
+        assert_eq!(
+        size_of::<[PublisherStats; MAX_NUM_COMPONENTS]>(),
+        PUBLISHER_STATS_SIZE.try_into().unwrap(),
+        "expected PUBLISHER_STATS_SIZE ({}) in oracle.h to the same as the size of the publisher statistics ({})",
+        PUBLISHER_STATS_SIZE,
+        size_of::<[PublisherStats; MAX_NUM_COMPONENTS]>()
+    );
+    }
+    #[test]
     ///test that priceAccountWrapper has a correct size
     fn c_price_account_size_is_correct() {
         assert_eq!(
@@ -92,4 +705,13 @@ pub mod tests {
         size_of::<PriceAccountWrapper>()
     );
     }
+    #[test]
+    ///test that TIME_MACHINE_OFFSET is the offset of the time machine in PriceAccountWrapper
+    fn time_machine_offset_is_correct() {
+        let price_account: PriceAccountWrapper = Zeroable::zeroed();
+        assert_eq!(
+            &price_account.time_machine as *const _ as usize - &price_account as *const _ as usize,
+            TIME_MACHINE_OFFSET
+        );
+    }
 }
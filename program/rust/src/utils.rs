@@ -1,14 +1,47 @@
 use crate::c_oracle_header::{
     cmd_upd_price_t,
+    command_t_e_cmd_add_mapping,
+    command_t_e_cmd_add_mapping_pda,
+    command_t_e_cmd_add_price,
+    command_t_e_cmd_add_price_pda,
+    command_t_e_cmd_add_product,
+    command_t_e_cmd_add_product_pda,
+    command_t_e_cmd_add_publisher,
+    command_t_e_cmd_del_price,
+    command_t_e_cmd_del_product,
+    command_t_e_cmd_del_product_attr,
+    command_t_e_cmd_del_publisher,
+    command_t_e_cmd_init_mapping,
+    command_t_e_cmd_init_mapping_pda,
+    command_t_e_cmd_init_price,
+    command_t_e_cmd_resize_price_account,
+    command_t_e_cmd_resize_product_account,
+    command_t_e_cmd_set_max_ci_divisor,
+    command_t_e_cmd_set_max_latency,
+    command_t_e_cmd_set_min_pub,
+    command_t_e_cmd_set_product_attr,
     command_t_e_cmd_upd_price,
     command_t_e_cmd_upd_price_no_fail_on_error,
+    command_t_e_cmd_upd_product,
     pc_acc,
+    pc_permissions_t,
     pc_pub_key_t,
+    MAPPING_SEED,
     PC_MAX_NUM_DECIMALS,
+    PC_MIN_VERSION,
+    PC_VERSION,
+    PERMISSIONS_SEED,
+    PRICE_SEED,
+    PRODUCT_SEED,
+};
+use crate::deserialize::{
+    load_account_as,
+    load_checked_bytes,
 };
-use crate::deserialize::load_account_as;
 use crate::OracleError;
 use solana_program::account_info::AccountInfo;
+use solana_program::bpf_loader_upgradeable;
+use solana_program::bpf_loader_upgradeable::UpgradeableLoaderState;
 use solana_program::program_error::ProgramError;
 use solana_program::program_memory::sol_memset;
 use solana_program::pubkey::Pubkey;
@@ -45,6 +78,131 @@ pub fn check_valid_funding_account(account: &AccountInfo) -> Result<(), ProgramE
     )
 }
 
+/// Check that `funding_account` is a valid funding account and that the permissions account of
+/// the program allows it to run `command`
+pub fn check_permissioned_funding_account(
+    program_id: &Pubkey,
+    funding_account: &AccountInfo,
+    permissions_account: &AccountInfo,
+    command: u32,
+) -> Result<(), ProgramError> {
+    check_valid_funding_account(funding_account)?;
+    pyth_assert(
+        *permissions_account.key == get_permissions_address(program_id).0
+            && permissions_account.owner == program_id,
+        OracleError::InvalidPermissionsAccount.into(),
+    )?;
+
+    let data = permissions_account.try_borrow_data()?;
+    let permissions = load_checked_bytes::<pc_permissions_t>(&data, PC_VERSION)?;
+    pyth_assert(
+        is_authorized(permissions, funding_account.key, command),
+        OracleError::PermissionDenied.into(),
+    )
+}
+
+/// Returns `true` if `permissions` allow `key` to run `command`. The master authority can run
+/// every admin instruction, the security authority manages the publishers and the data curation
+/// authority manages the rest of the mapping, product and price accounts.
+pub fn is_authorized(permissions: &pc_permissions_t, key: &Pubkey, command: u32) -> bool {
+    let key = key.to_bytes();
+    pubkey_equal(&permissions.master_authority_, &key)
+        || match command {
+            command_t_e_cmd_add_publisher | command_t_e_cmd_del_publisher => {
+                pubkey_equal(&permissions.security_authority_, &key)
+            }
+            command_t_e_cmd_init_mapping
+            | command_t_e_cmd_add_mapping
+            | command_t_e_cmd_add_product
+            | command_t_e_cmd_upd_product
+            | command_t_e_cmd_set_product_attr
+            | command_t_e_cmd_del_product_attr
+            | command_t_e_cmd_resize_product_account
+            | command_t_e_cmd_del_product
+            | command_t_e_cmd_add_price
+            | command_t_e_cmd_init_price
+            | command_t_e_cmd_set_min_pub
+            | command_t_e_cmd_set_max_latency
+            | command_t_e_cmd_set_max_ci_divisor
+            | command_t_e_cmd_resize_price_account
+            | command_t_e_cmd_del_price
+            | command_t_e_cmd_init_mapping_pda
+            | command_t_e_cmd_add_mapping_pda
+            | command_t_e_cmd_add_product_pda
+            | command_t_e_cmd_add_price_pda => {
+                pubkey_equal(&permissions.data_curation_authority_, &key)
+            }
+            _ => false,
+        }
+}
+
+/// The address and bump seed of the permissions account of `program_id`
+pub fn get_permissions_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PERMISSIONS_SEED.as_bytes()], program_id)
+}
+
+/// The address and bump seed of the mapping account of `program_id` that follows
+/// `tail_mapping_account`, or of the first mapping account if `None`
+pub fn get_mapping_address(
+    program_id: &Pubkey,
+    tail_mapping_account: Option<&Pubkey>,
+) -> (Pubkey, u8) {
+    match tail_mapping_account {
+        Some(tail_mapping_account) => Pubkey::find_program_address(
+            &[MAPPING_SEED.as_bytes(), tail_mapping_account.as_ref()],
+            program_id,
+        ),
+        None => Pubkey::find_program_address(&[MAPPING_SEED.as_bytes()], program_id),
+    }
+}
+
+/// The address and bump seed of the product account of `program_id` created with `seed`
+pub fn get_product_address(program_id: &Pubkey, seed: &[u8]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PRODUCT_SEED.as_bytes(), seed], program_id)
+}
+
+/// The address and bump seed of the price account of `program_id` for `product_account` and
+/// `price_type`
+pub fn get_price_address(
+    program_id: &Pubkey,
+    product_account: &Pubkey,
+    price_type: u32,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PRICE_SEED.as_bytes(),
+            product_account.as_ref(),
+            &price_type.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// The upgrade authority of `program_id`, read from `programdata_account`, the account of the
+/// upgradeable loader that holds the program. `None` if the program is immutable.
+pub fn get_upgrade_authority(
+    program_id: &Pubkey,
+    programdata_account: &AccountInfo,
+) -> Result<Option<Pubkey>, ProgramError> {
+    let (programdata_address, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    pyth_assert(
+        *programdata_account.key == programdata_address
+            && bpf_loader_upgradeable::check_id(programdata_account.owner),
+        OracleError::InvalidProgramDataAccount.into(),
+    )?;
+
+    // The data starts with the `UpgradeableLoaderState` of the account, followed by the program
+    let data = programdata_account.try_borrow_data()?;
+    match bincode::deserialize::<UpgradeableLoaderState>(&data) {
+        Ok(UpgradeableLoaderState::ProgramData {
+            upgrade_authority_address,
+            ..
+        }) => Ok(upgrade_authority_address),
+        _ => Err(OracleError::InvalidProgramDataAccount.into()),
+    }
+}
+
 pub fn valid_signable_account(
     program_id: &Pubkey,
     account: &AccountInfo,
@@ -89,7 +247,7 @@ pub fn check_valid_fresh_account(account: &AccountInfo) -> Result<(), ProgramErr
 pub fn check_exponent_range(expo: i32) -> Result<(), ProgramError> {
     pyth_assert(
         expo >= -(PC_MAX_NUM_DECIMALS as i32) && expo <= PC_MAX_NUM_DECIMALS as i32,
-        ProgramError::InvalidArgument,
+        OracleError::InvalidExponent.into(),
     )
 }
 
@@ -106,6 +264,10 @@ pub fn pubkey_equal(target: &pc_pub_key_t, source: &[u8]) -> bool {
     unsafe { target.k1_ == *source }
 }
 
+pub fn pubkey_to_solana(key: &pc_pub_key_t) -> Pubkey {
+    Pubkey::new_from_array(unsafe { key.k1_ })
+}
+
 /// Convert `x: T` into a `U`, returning the appropriate `OracleError` if the conversion fails.
 pub fn try_convert<T, U: TryFrom<T>>(x: T) -> Result<U, OracleError> {
     // Note: the error here assumes we're only applying this function to integers right now.
@@ -127,6 +289,14 @@ pub fn read_pc_str_t(source: &[u8]) -> Result<&[u8], ProgramError> {
     }
 }
 
+/// Append `source` to `target` as a `pc_str_t`. Fails if `source` is longer than 255 bytes.
+pub fn write_pc_str_t(target: &mut Vec<u8>, source: &[u8]) -> Result<(), ProgramError> {
+    let tag_len: u8 = try_convert(source.len())?;
+    target.push(tag_len);
+    target.extend_from_slice(source);
+    Ok(())
+}
+
 fn valid_writable_account(program_id: &Pubkey, account: &AccountInfo, minimum_size: usize) -> bool {
     account.is_writable
         && account.owner == program_id
@@ -145,6 +315,11 @@ pub fn check_valid_writable_account(
     )
 }
 
+/// Returns `true` if the program supports instructions and accounts of `version`
+pub fn is_supported_version(version: u32) -> bool {
+    (PC_MIN_VERSION..=PC_VERSION).contains(&version)
+}
+
 /// Checks whether this instruction is trying to update an individual publisher's price (`true`) or
 /// is only trying to refresh the aggregate (`false`)
 pub fn is_component_update(cmd_args: &cmd_upd_price_t) -> Result<bool, ProgramError> {
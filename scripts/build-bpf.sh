@@ -37,7 +37,7 @@ rm ./target/*-keypair.json
 #build Rust and link it with C
 cd "${PYTH_DIR}"
 cargo clean
-cargo test-bpf
+cargo test-bpf --features "test-c-aggregate test-bpf"
 cargo clean
 cargo build-bpf
 